@@ -0,0 +1,305 @@
+use crate::bech32::auto_bech32_to_hex;
+use crate::events::Event;
+use crate::nips::nip1::{NIP1Error, NotesPager};
+use crate::nostr_client::{Client, ClientError};
+use std::cmp::Reverse;
+use std::io::{BufRead, Write};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("The client has an error: {}", _0)]
+    ClientError(#[from] ClientError),
+
+    #[error("NIP1 Error: {}", _0)]
+    NIP1Error(#[from] NIP1Error),
+
+    #[error("IO Error: {}", _0)]
+    IoError(#[from] std::io::Error),
+
+    #[error("Serde Error: {}", _0)]
+    SerdeError(#[from] serde_json::Error),
+
+    #[error("Invalid event at line {}: {}", _0, _1)]
+    InvalidEvent(usize, serde_json::Error),
+
+    #[error("The relay {} failed, the export would miss its events: {}", _0, _1)]
+    RelayError(String, ClientError),
+}
+
+/// Write events as JSONL, one JSON event per line (the usual Nostr archive format)
+///
+/// The events are written one by one, pass a lazy iterator to stream a large archive
+/// # Example
+/// ```rust
+/// use nostr_rust::{archive::{read_jsonl, write_jsonl}, events::Event};
+///
+/// let event = Event {
+///   id: "event_id".to_string(),
+///   pub_key: "pubkey".to_string(),
+///   created_at: 0,
+///   kind: 1,
+///   tags: vec![],
+///   content: "Hello\nNostr!".to_string(),
+///   sig: String::new(),
+/// };
+///
+/// let mut archive: Vec<u8> = Vec::new();
+/// write_jsonl(&[event.clone(), event], &mut archive).unwrap();
+/// assert_eq!(archive.iter().filter(|byte| **byte == b'\n').count(), 2);
+///
+/// let events: Vec<Event> = read_jsonl(archive.as_slice()).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(events.len(), 2);
+/// assert_eq!(events[0].content, "Hello\nNostr!");
+/// ```
+pub fn write_jsonl<'a, W: Write>(
+    events: impl IntoIterator<Item = &'a Event>,
+    mut writer: W,
+) -> Result<(), ArchiveError> {
+    for event in events {
+        serde_json::to_writer(&mut writer, event)?;
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Read the events of a JSONL archive lazily, one event per line, blank lines are skipped
+///
+/// The signatures are not checked, see `Event::verify`
+/// # Example
+/// ```rust
+/// use nostr_rust::archive::{read_jsonl, ArchiveError};
+///
+/// let archive = "\n{\"id\":\"event_id\",\"pubkey\":\"pubkey\",\"created_at\":0,\"kind\":1,\"tags\":[],\"content\":\"\",\"sig\":\"\"}\nnot an event\n";
+/// let mut events = read_jsonl(archive.as_bytes());
+///
+/// assert_eq!(events.next().unwrap().unwrap().id, "event_id");
+/// assert!(matches!(events.next(), Some(Err(ArchiveError::InvalidEvent(3, _)))));
+/// assert!(events.next().is_none());
+/// ```
+pub fn read_jsonl<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Event, ArchiveError>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(
+                serde_json::from_str(&line)
+                    .map_err(|error| ArchiveError::InvalidEvent(index + 1, error)),
+            ),
+            Err(error) => Some(Err(error.into())),
+        })
+}
+
+/// Events of a page of the export, an error if a relay failed
+fn export_page(
+    pager: &mut NotesPager,
+    events_per_relay: crate::nostr_client::EventsPerRelay,
+) -> Result<Vec<Event>, ArchiveError> {
+    let mut pages: Vec<Vec<Event>> = Vec::new();
+
+    for (relay, events) in events_per_relay {
+        match events {
+            Ok(events) => pages.push(events),
+            Err(error) => return Err(ArchiveError::RelayError(relay, error)),
+        }
+    }
+
+    Ok(pager.add_page(pages))
+}
+
+impl Client {
+    #[cfg(not(feature = "async"))]
+    /// Write every event of an author (hex or npub) as JSONL, optionally of the given kinds only,
+    /// and return the number of events written
+    ///
+    /// The history is queried page by page going back in time, each page being written before the
+    /// next one is requested so the whole history is never held in memory. Every relay is waited
+    /// for on each page, until EOSE or the query timeout (see `Client::set_query_timeout`): a
+    /// relay which fails stops the export with `ArchiveError::RelayError` rather than leaving its
+    /// events out. An event is written once, the pages are written newest first
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    ///
+    /// let file = std::fs::File::create("/tmp/archive.jsonl").unwrap();
+    /// let count = client.export_events_to("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", Some(&[0, 1, 3]), std::io::BufWriter::new(file)).unwrap();
+    /// println!("{} events exported", count);
+    /// ```
+    pub fn export_events_to<W: Write>(
+        &mut self,
+        pubkey: &str,
+        kinds: Option<&[u16]>,
+        mut writer: W,
+    ) -> Result<usize, ArchiveError> {
+        let hex_pubkey = auto_bech32_to_hex(pubkey).map_err(NIP1Error::from)?;
+        let mut pager = NotesPager::new(hex_pubkey, kinds.unwrap_or_default(), usize::MAX);
+        let mut count = 0;
+
+        while let Some(filter) = pager.next_filter() {
+            let events_per_relay = self.get_events_of_per_relay(vec![filter])?;
+            let events = export_page(&mut pager, events_per_relay)?;
+
+            write_jsonl(&events, &mut writer)?;
+            count += events.len();
+        }
+
+        Ok(count)
+    }
+
+    #[cfg(feature = "async")]
+    /// Write every event of an author (hex or npub) as JSONL, optionally of the given kinds only,
+    /// and return the number of events written
+    ///
+    /// The history is queried page by page going back in time, each page being written before the
+    /// next one is requested so the whole history is never held in memory. Every relay is waited
+    /// for on each page, until EOSE or the query timeout (see `Client::set_query_timeout`): a
+    /// relay which fails stops the export with `ArchiveError::RelayError` rather than leaving its
+    /// events out. An event is written once, the pages are written newest first
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// #[tokio::test]
+    /// async fn test_export_events_to() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let file = std::fs::File::create("/tmp/archive.jsonl").unwrap();
+    ///     let count = client.export_events_to("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", Some(&[0, 1, 3]), std::io::BufWriter::new(file)).await.unwrap();
+    ///     println!("{} events exported", count);
+    /// }
+    /// ```
+    pub async fn export_events_to<W: Write>(
+        &mut self,
+        pubkey: &str,
+        kinds: Option<&[u16]>,
+        mut writer: W,
+    ) -> Result<usize, ArchiveError> {
+        let hex_pubkey = auto_bech32_to_hex(pubkey).map_err(NIP1Error::from)?;
+        let mut pager = NotesPager::new(hex_pubkey, kinds.unwrap_or_default(), usize::MAX);
+        let mut count = 0;
+
+        while let Some(filter) = pager.next_filter() {
+            let events_per_relay = self.get_events_of_per_relay(vec![filter]).await?;
+            let events = export_page(&mut pager, events_per_relay)?;
+
+            write_jsonl(&events, &mut writer)?;
+            count += events.len();
+        }
+
+        Ok(count)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get every event of an author (hex or npub), optionally of the given kinds only, newest first
+    ///
+    /// The events are queried like `Client::export_events_to` but kept in memory, prefer
+    /// `Client::export_events_to` for a large history
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{archive::write_jsonl, nostr_client::Client};
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    ///
+    /// let events = client.export_events("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", Some(&[0, 1, 3])).unwrap();
+    /// write_jsonl(&events, std::io::stdout()).unwrap();
+    /// ```
+    ///
+    /// With a mock relay storing more events in the same second than a page holds (see
+    /// `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, nostr_client::Client, req::ReqFilter, websocket::SimplifiedWS, Identity};
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, str::FromStr, thread};
+    /// use tungstenite::Message;
+    ///
+    /// let identity = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    /// let stored: Vec<_> = (0..150)
+    ///     .map(|n| EventPrepare::builder(&identity.public_key_str).created_at(1000 + n / 120).content(&n.to_string()).build().unwrap().to_event(&identity, 0))
+    ///     .collect();
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let url = format!("ws://{}", listener.local_addr().unwrap());
+    /// thread::spawn(move || {
+    ///     let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///
+    ///     while let Ok(message) = relay.read_message() {
+    ///         let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///         if request[0] != "REQ" {
+    ///             continue;
+    ///         }
+    ///
+    ///         // Newest first, up to the limit, like a relay
+    ///         let filter: ReqFilter = serde_json::from_value(request[2].clone()).unwrap();
+    ///         let mut events: Vec<_> = stored.iter().filter(|event| filter.matches(event)).collect();
+    ///         events.sort_by_key(|event| std::cmp::Reverse(event.created_at));
+    ///         events.truncate(filter.limit.map_or(usize::MAX, |limit| limit as usize));
+    ///
+    ///         for event in events {
+    ///             relay.write_message(Message::text(json!(["EVENT", request[1], event]).to_string())).unwrap();
+    ///         }
+    ///         relay.write_message(Message::text(json!(["EOSE", request[1]]).to_string())).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let (socket, _) = tungstenite::connect(&url).unwrap();
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// client.add_connected_relay(&url, SimplifiedWS::from_socket(&url, socket).unwrap()).unwrap();
+    ///
+    /// let events = client.export_events(&identity.public_key_str, None).unwrap();
+    /// assert_eq!(events.len(), 150);
+    /// ```
+    pub fn export_events(
+        &mut self,
+        pubkey: &str,
+        kinds: Option<&[u16]>,
+    ) -> Result<Vec<Event>, ArchiveError> {
+        let hex_pubkey = auto_bech32_to_hex(pubkey).map_err(NIP1Error::from)?;
+        let mut pager = NotesPager::new(hex_pubkey, kinds.unwrap_or_default(), usize::MAX);
+        let mut events: Vec<Event> = Vec::new();
+
+        while let Some(filter) = pager.next_filter() {
+            let events_per_relay = self.get_events_of_per_relay(vec![filter])?;
+            events.extend(export_page(&mut pager, events_per_relay)?);
+        }
+
+        events.sort_by_key(|event| (Reverse(event.created_at), event.id.clone()));
+        Ok(events)
+    }
+
+    #[cfg(feature = "async")]
+    /// Get every event of an author (hex or npub), optionally of the given kinds only, newest first
+    ///
+    /// The events are queried like `Client::export_events_to` but kept in memory, prefer
+    /// `Client::export_events_to` for a large history
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{archive::write_jsonl, nostr_client::Client};
+    ///
+    /// #[tokio::test]
+    /// async fn test_export_events() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let events = client.export_events("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", Some(&[0, 1, 3])).await.unwrap();
+    ///     write_jsonl(&events, std::io::stdout()).unwrap();
+    /// }
+    /// ```
+    pub async fn export_events(
+        &mut self,
+        pubkey: &str,
+        kinds: Option<&[u16]>,
+    ) -> Result<Vec<Event>, ArchiveError> {
+        let hex_pubkey = auto_bech32_to_hex(pubkey).map_err(NIP1Error::from)?;
+        let mut pager = NotesPager::new(hex_pubkey, kinds.unwrap_or_default(), usize::MAX);
+        let mut events: Vec<Event> = Vec::new();
+
+        while let Some(filter) = pager.next_filter() {
+            let events_per_relay = self.get_events_of_per_relay(vec![filter]).await?;
+            events.extend(export_page(&mut pager, events_per_relay)?);
+        }
+
+        events.sort_by_key(|event| (Reverse(event.created_at), event.id.clone()));
+        Ok(events)
+    }
+}
@@ -1,7 +1,9 @@
+use crate::events::Address;
 use bech32::{FromBase32, ToBase32};
 use hex::FromHexError;
 use thiserror::Error;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ToBech32Kind {
     SecretKey,
     PublicKey,
@@ -15,6 +17,12 @@ pub enum Bech32Error {
 
     #[error("Bech32 given key is not a {0}")]
     InvalidKey(String),
+
+    #[error("TLV value is too long ({0} bytes, 255 max)")]
+    TlvValueTooLong(usize),
+
+    #[error("Invalid TLV data")]
+    InvalidTlv,
 }
 
 impl From<bech32::Error> for Bech32Error {
@@ -45,39 +53,39 @@ pub fn to_bech32(kind: ToBech32Kind, key: &str) -> Result<String, Bech32Error> {
             if key.starts_with("nsec") {
                 Ok(key)
             } else if key.starts_with("npub") || key.starts_with("note") {
-                return Err(Bech32Error::InvalidKey("npub or note".to_string()));
+                Err(Bech32Error::InvalidKey("npub or note".to_string()))
             } else {
-                return Ok(bech32::encode(
+                Ok(bech32::encode(
                     "nsec",
                     hex::decode(key)?.to_base32(),
                     bech32::Variant::Bech32,
-                )?);
+                )?)
             }
         }
         ToBech32Kind::PublicKey => {
             if key.starts_with("npub") {
                 Ok(key)
             } else if key.starts_with("nsec") || key.starts_with("note") {
-                return Err(Bech32Error::InvalidKey("nsec or note".to_string()));
+                Err(Bech32Error::InvalidKey("nsec or note".to_string()))
             } else {
-                return Ok(bech32::encode(
+                Ok(bech32::encode(
                     "npub",
                     hex::decode(key)?.to_base32(),
                     bech32::Variant::Bech32,
-                )?);
+                )?)
             }
         }
         ToBech32Kind::Note => {
             if key.starts_with("note") {
                 Ok(key)
             } else if key.starts_with("nsec") || key.starts_with("npub") {
-                return Err(Bech32Error::InvalidKey("nsec or npub".to_string()));
+                Err(Bech32Error::InvalidKey("nsec or npub".to_string()))
             } else {
-                return Ok(bech32::encode(
+                Ok(bech32::encode(
                     "note",
                     hex::decode(key)?.to_base32(),
                     bech32::Variant::Bech32,
-                )?);
+                )?)
             }
         }
     }
@@ -102,9 +110,9 @@ pub fn from_hb_to_hex(kind: ToBech32Kind, key: &str) -> Result<String, Bech32Err
 
                 Ok(hex_str)
             } else if key.starts_with("npub") {
-                return Err(Bech32Error::InvalidKey("npub".to_string()));
+                Err(Bech32Error::InvalidKey("npub".to_string()))
             } else {
-                return Ok(key);
+                Ok(key)
             }
         }
         ToBech32Kind::PublicKey => {
@@ -115,9 +123,9 @@ pub fn from_hb_to_hex(kind: ToBech32Kind, key: &str) -> Result<String, Bech32Err
 
                 Ok(hex_str)
             } else if key.starts_with("nsec") {
-                return Err(Bech32Error::InvalidKey("nsec".to_string()));
+                Err(Bech32Error::InvalidKey("nsec".to_string()))
             } else {
-                return Ok(key);
+                Ok(key)
             }
         }
         ToBech32Kind::Note => {
@@ -128,25 +136,194 @@ pub fn from_hb_to_hex(kind: ToBech32Kind, key: &str) -> Result<String, Bech32Err
 
                 Ok(hex_str)
             } else if key.starts_with("nsec") || key.starts_with("npub") {
-                return Err(Bech32Error::InvalidKey("nsec or npub".to_string()));
+                Err(Bech32Error::InvalidKey("nsec or npub".to_string()))
             } else {
-                return Ok(key);
+                Ok(key)
             }
         }
     }
 }
 
-/// Transform a string (bech32 or hex) into an hex string
-pub fn auto_bech32_to_hex(key: &str) -> Result<String, Bech32Error> {
-    let key = key.to_string();
+/// Encode TLV entries (type, value) into a bech32 string with the given prefix
+fn encode_tlv(hrp: &str, entries: &[(u8, &[u8])]) -> Result<String, Bech32Error> {
+    let mut data: Vec<u8> = vec![];
+
+    for (tlv_type, value) in entries {
+        let length =
+            u8::try_from(value.len()).map_err(|_| Bech32Error::TlvValueTooLong(value.len()))?;
+        data.push(*tlv_type);
+        data.push(length);
+        data.extend_from_slice(value);
+    }
+
+    Ok(bech32::encode(
+        hrp,
+        data.to_base32(),
+        bech32::Variant::Bech32,
+    )?)
+}
+
+/// Decode the TLV entries (type, value) of a bech32 string with the given prefix
+pub(crate) fn decode_tlv(hrp: &str, value: &str) -> Result<Vec<(u8, Vec<u8>)>, Bech32Error> {
+    let (prefix, data, _) = bech32::decode(value)?;
+    if prefix != hrp {
+        return Err(Bech32Error::InvalidKey(hrp.to_string()));
+    }
+
+    let data = Vec::<u8>::from_base32(&data)?;
+    let mut entries: Vec<(u8, Vec<u8>)> = vec![];
+    let mut rest = data.as_slice();
+
+    while let [tlv_type, length, tail @ ..] = rest {
+        let value = tail
+            .get(..*length as usize)
+            .ok_or(Bech32Error::InvalidTlv)?;
+        entries.push((*tlv_type, value.to_vec()));
+        rest = &tail[*length as usize..];
+    }
 
-    if key.starts_with("nsec") {
-        from_hb_to_hex(ToBech32Kind::SecretKey, &key)
-    } else if key.starts_with("npub") {
-        from_hb_to_hex(ToBech32Kind::PublicKey, &key)
-    } else if key.starts_with("note") {
-        from_hb_to_hex(ToBech32Kind::Note, &key)
+    if !rest.is_empty() {
+        return Err(Bech32Error::InvalidTlv);
+    }
+
+    Ok(entries)
+}
+
+/// Encode an event id (hex or note) and relay hints into a "nevent" string (NIP-19)
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::bech32::to_nevent;
+/// let id = "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4";
+///
+/// let nevent = to_nevent(id, &[]).unwrap();
+/// assert_eq!(nevent, "nevent1qqs0qwpdjvkackrkhtfln30ahp8mfs400n80a0a5j8cnl0z8cw8c4eq6rmgeg");
+///
+/// let nevent = to_nevent(id, &["wss://relay.damus.io".to_string()]).unwrap();
+/// assert_eq!(nevent, "nevent1qqs0qwpdjvkackrkhtfln30ahp8mfs400n80a0a5j8cnl0z8cw8c4eqpz3mhxue69uhhyetvv9ujuerpd46hxtnfdu4f8uep");
+/// ```
+pub fn to_nevent(event_id: &str, relays: &[String]) -> Result<String, Bech32Error> {
+    let id = hex::decode(from_hb_to_hex(ToBech32Kind::Note, event_id)?)?;
+    if id.len() != 32 {
+        return Err(Bech32Error::InvalidHex);
+    }
+
+    let mut entries: Vec<(u8, &[u8])> = vec![(0, &id)];
+    entries.extend(relays.iter().map(|relay| (1, relay.as_bytes())));
+
+    encode_tlv("nevent", &entries)
+}
+
+/// Encode the address of a parameterized replaceable event and relay hints into a "naddr" string (NIP-19)
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{bech32::{decode_naddr, to_naddr}, events::Address};
+///
+/// let address = Address {
+///   kind: 30023,
+///   pub_key: "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+///   identifier: "my-article".to_string(),
+/// };
+/// let relays = vec!["wss://relay.damus.io".to_string()];
+///
+/// let naddr = to_naddr(&address, &relays).unwrap();
+/// assert_eq!(naddr, "naddr1qq9x67fdv9e8g6trd3jsz9rhwden5te0wfjkccte9ejxzmt4wvhxjmczyzyywp9aggtjrc5jakllgt4hw4rluy2udlucykcglsmxhexdd85lvqcyqqq823cexgfk5");
+/// assert_eq!(decode_naddr(&naddr).unwrap(), (address, relays));
+///
+/// assert!(decode_naddr("nevent1qqs0qwpdjvkackrkhtfln30ahp8mfs400n80a0a5j8cnl0z8cw8c4eq6rmgeg").is_err());
+/// ```
+pub fn to_naddr(address: &Address, relays: &[String]) -> Result<String, Bech32Error> {
+    let pub_key = hex::decode(&address.pub_key)?;
+    if pub_key.len() != 32 {
+        return Err(Bech32Error::InvalidHex);
+    }
+    let kind = u32::from(address.kind).to_be_bytes();
+
+    let mut entries: Vec<(u8, &[u8])> = vec![(0, address.identifier.as_bytes())];
+    entries.extend(relays.iter().map(|relay| (1, relay.as_bytes())));
+    entries.push((2, &pub_key));
+    entries.push((3, &kind));
+
+    encode_tlv("naddr", &entries)
+}
+
+/// Decode a "naddr" string (NIP-19) into the address and the relay hints
+///
+/// Unknown TLV types are ignored, the identifier, author and kind are required
+pub fn decode_naddr(naddr: &str) -> Result<(Address, Vec<String>), Bech32Error> {
+    let mut identifier: Option<String> = None;
+    let mut pub_key: Option<String> = None;
+    let mut kind: Option<u16> = None;
+    let mut relays: Vec<String> = vec![];
+
+    for (tlv_type, value) in decode_tlv("naddr", naddr)? {
+        match tlv_type {
+            0 => identifier = Some(String::from_utf8(value).map_err(|_| Bech32Error::InvalidTlv)?),
+            1 => relays.push(String::from_utf8(value).map_err(|_| Bech32Error::InvalidTlv)?),
+            2 if value.len() == 32 => pub_key = Some(hex::encode(value)),
+            3 => {
+                let bytes: [u8; 4] = value.try_into().map_err(|_| Bech32Error::InvalidTlv)?;
+                kind = Some(
+                    u16::try_from(u32::from_be_bytes(bytes))
+                        .map_err(|_| Bech32Error::InvalidTlv)?,
+                );
+            }
+            2 => return Err(Bech32Error::InvalidTlv),
+            _ => {}
+        }
+    }
+
+    let address = Address {
+        kind: kind.ok_or(Bech32Error::InvalidTlv)?,
+        pub_key: pub_key.ok_or(Bech32Error::InvalidTlv)?,
+        identifier: identifier.ok_or(Bech32Error::InvalidTlv)?,
+    };
+
+    Ok((address, relays))
+}
+
+/// Detect the kind of a bech32 string from its prefix (HRP), `None` for a hex string or another
+/// prefix (nevent, naddr...)
+///
+/// The checksum is not verified, see `from_hb_to_hex`. A malformed string with one of the
+/// prefixes, e.g. "npubxyz", is still detected so that decoding it fails instead of taking it
+/// for hex
+/// # Example
+/// ```rust
+/// use nostr_rust::bech32::{detect_bech32_kind, ToBech32Kind};
+///
+/// assert_eq!(detect_bech32_kind("npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6"), Some(ToBech32Kind::PublicKey));
+/// assert_eq!(detect_bech32_kind("note17quzmyedm3v8dwknl8zlmwz0knp27lxwl6lmfy03877y0su03tjqcqymya"), Some(ToBech32Kind::Note));
+/// assert_eq!(detect_bech32_kind("npubxyz"), Some(ToBech32Kind::PublicKey));
+/// assert_eq!(detect_bech32_kind("3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d"), None);
+/// assert_eq!(detect_bech32_kind("nevent1qqs0qwpdjvkackrkhtfln30ahp8mfs400n80a0a5j8cnl0z8cw8c4eq6rmgeg"), None);
+/// ```
+pub fn detect_bech32_kind(value: &str) -> Option<ToBech32Kind> {
+    if value.starts_with("nsec") {
+        Some(ToBech32Kind::SecretKey)
+    } else if value.starts_with("npub") {
+        Some(ToBech32Kind::PublicKey)
+    } else if value.starts_with("note") {
+        Some(ToBech32Kind::Note)
     } else {
-        Ok(key)
+        None
+    }
+}
+
+/// Transform a string (bech32 or hex) into an hex string
+/// # Example
+/// ```rust
+/// use nostr_rust::bech32::auto_bech32_to_hex;
+///
+/// let hex = "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d";
+/// assert_eq!(auto_bech32_to_hex("npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6").unwrap(), hex);
+/// assert_eq!(auto_bech32_to_hex(hex).unwrap(), hex);
+/// assert!(auto_bech32_to_hex("npubxyz").is_err());
+/// ```
+pub fn auto_bech32_to_hex(key: &str) -> Result<String, Bech32Error> {
+    match detect_bech32_kind(key) {
+        Some(kind) => from_hb_to_hex(kind, key),
+        None => Ok(key.to_string()),
     }
 }
@@ -0,0 +1,78 @@
+use crate::events::Event;
+use crate::req::ReqFilter;
+use std::collections::HashSet;
+
+/// Storage of events used by `Client::get_events_of` before querying the relays
+///
+/// The client gets the cached events matching each filter, then stores every event received from the relays
+pub trait EventCache: Send {
+    /// Get the stored events matching the filter
+    fn get(&self, filter: &ReqFilter) -> Vec<Event>;
+
+    /// Store an event
+    fn put(&mut self, event: &Event);
+}
+
+/// Cache storing nothing, used by default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoCache;
+
+impl EventCache for NoCache {
+    fn get(&self, _filter: &ReqFilter) -> Vec<Event> {
+        vec![]
+    }
+
+    fn put(&mut self, _event: &Event) {}
+}
+
+/// Cache keeping every event in memory
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{cache::{EventCache, MemoryCache}, events::Event, req::ReqFilter};
+///
+/// let mut cache = MemoryCache::default();
+/// cache.put(&Event {
+///   id: "event_id".to_string(),
+///   pub_key: "pubkey".to_string(),
+///   created_at: 0,
+///   kind: 1,
+///   tags: vec![],
+///   content: String::new(),
+///   sig: String::new(),
+/// });
+///
+/// assert_eq!(cache.get(&ReqFilter { kinds: Some(vec![1]), ..Default::default() }).len(), 1);
+/// assert_eq!(cache.get(&ReqFilter { kinds: Some(vec![0]), ..Default::default() }).len(), 0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCache {
+    events: Vec<Event>,
+    /// Ids of the stored events
+    ids: HashSet<String>,
+}
+
+impl EventCache for MemoryCache {
+    fn get(&self, filter: &ReqFilter) -> Vec<Event> {
+        let mut events: Vec<Event> = self
+            .events
+            .iter()
+            .filter(|event| filter.matches(event))
+            .cloned()
+            .collect();
+
+        if let Some(limit) = filter.limit {
+            // Keep the newest events
+            events.sort_by_key(|event| std::cmp::Reverse(event.created_at));
+            events.truncate(limit as usize);
+        }
+
+        events
+    }
+
+    fn put(&mut self, event: &Event) {
+        if self.ids.insert(event.id.clone()) {
+            self.events.push(event.clone());
+        }
+    }
+}
@@ -1,12 +1,19 @@
 use std::fmt;
 
 use secp256k1::{schnorr::Signature, KeyPair, XOnlyPublicKey, SECP256K1};
+use serde::de::{self, Deserializer, Unexpected, Visitor};
 use serde_derive::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use thiserror::Error;
 
+use crate::bech32::{
+    auto_bech32_to_hex, from_hb_to_hex, to_bech32, to_nevent, Bech32Error, ToBech32Kind,
+};
+use crate::utils::{get_timestamp, jittered_timestamp, tag_value};
 use crate::Identity;
 
 /// EventPrepare is the struct used to prepare an event before publishing it (signing it and assigning it an id)
@@ -27,6 +34,39 @@ pub struct EventPrepare {
 }
 
 impl EventPrepare {
+    /// Start building an event, the recommended way to create an `EventPrepare`
+    ///
+    /// `created_at` defaults to now, `kind` to 1 (text note) and `tags` to none
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, utils::get_timestamp, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = EventPrepare::builder(&identity.public_key_str)
+    ///   .kind(1)
+    ///   .content("hi")
+    ///   .tag(["t", "rust"])
+    ///   .build()
+    ///   .unwrap()
+    ///   .to_event(&identity, 0);
+    ///
+    /// assert!(event.created_at >= get_timestamp() - 1);
+    /// assert_eq!(event.tags, vec![vec!["t".to_string(), "rust".to_string()]]);
+    /// event.verify().unwrap();
+    ///
+    /// assert!(EventPrepare::builder("not a public key").build().is_err());
+    /// ```
+    pub fn builder(pub_key: &str) -> EventPrepareBuilder {
+        EventPrepareBuilder {
+            pub_key: pub_key.to_string(),
+            created_at: None,
+            kind: 1,
+            tags: vec![],
+            content: String::new(),
+        }
+    }
+
     /// get_content returns the content of the event to be signed
     /// # Example
     /// ```rust
@@ -44,6 +84,24 @@ impl EventPrepare {
     ///
     /// assert_eq!(event.get_content(), format!("[0,\"c5aec31e83bdf980939b5ef7c6bcaa2be8bd39d38667da58ba6dba240eb8b69d\",{},0,[],\"content\"]", actual_time));
     /// ```
+    ///
+    /// The escaping follows NIP-01 (and `JSON.stringify`, used by most clients): `\n`, `\"`, `\\`,
+    /// `\r`, `\t`, `\b` and `\f` are escaped, the other control characters are escaped as `\u00XX`
+    /// and every other character, emoji included, is kept verbatim
+    /// ```rust
+    /// use nostr_rust::events::EventPrepare;
+    ///
+    /// let event = EventPrepare {
+    ///    pub_key: env!("PUBLIC_KEY").to_string(),
+    ///    created_at: 0,
+    ///    kind: 1,
+    ///    tags: vec![],
+    ///    content: "a\"b\\c\nd\re\tf\u{8}g\u{c}h😀é\u{1}/".to_string(),
+    /// };
+    ///
+    /// assert_eq!(event.get_content(), r#"[0,"c5aec31e83bdf980939b5ef7c6bcaa2be8bd39d38667da58ba6dba240eb8b69d",0,1,[],"a\"b\\c\nd\re\tf\bg\fh😀é\u0001/"]"#);
+    /// assert_eq!(event.get_content_id(), "e55657f2bb1416332e69659b54d245a5e14dbfe09c5a03bd992aff6a394c538b");
+    /// ```
     pub fn get_content(&self) -> String {
         json!([
             0,
@@ -75,6 +133,48 @@ impl EventPrepare {
         sha256::digest(self.get_content())
     }
 
+    /// Get the exact pre-image hashed into the id (see `get_content`), to compare it byte for
+    /// byte with the one of another implementation when the ids disagree
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::EventPrepare;
+    ///
+    /// let event = EventPrepare {
+    ///   pub_key: env!("PUBLIC_KEY").to_string(),
+    ///   created_at: 0, // Don't use this in production
+    ///   kind: 0,
+    ///   tags: vec![],
+    ///   content: "content".to_string(),
+    /// };
+    ///
+    /// let reference = format!(r#"[0,"{}",0,0,[],"content"]"#, env!("PUBLIC_KEY"));
+    /// assert_eq!(event.debug_content_preimage().as_bytes(), reference.as_bytes());
+    /// ```
+    pub fn debug_content_preimage(&self) -> String {
+        self.get_content()
+    }
+
+    /// Check that the id computed from the event is the expected one (hex, case insensitive)
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::EventPrepare;
+    ///
+    /// let event = EventPrepare {
+    ///   pub_key: "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798".to_string(),
+    ///   created_at: 0, // Don't use this in production
+    ///   kind: 0,
+    ///   tags: vec![],
+    ///   content: "content".to_string(),
+    /// };
+    ///
+    /// assert!(event.verify_id_matches("91d69032185bdebe4265ff9445087da23b284e7eaca1fde2436e8d27a3a8327e"));
+    /// assert!(event.verify_id_matches("91D69032185BDEBE4265FF9445087DA23B284E7EACA1FDE2436E8D27A3A8327E"));
+    /// assert!(!event.verify_id_matches("0000000000000000000000000000000000000000000000000000000000000000"));
+    /// ```
+    pub fn verify_id_matches(&self, expected: &str) -> bool {
+        self.get_content_id().eq_ignore_ascii_case(expected)
+    }
+
     /// Transform the event to NostrEvent
     /// # Example
     /// ```rust
@@ -113,6 +213,43 @@ impl EventPrepare {
     /// assert_eq!(nostr_event_pow.pub_key, env!("PUBLIC_KEY"));
     /// assert_eq!(nostr_event_pow.sig.len(), 128);
     /// ```
+    ///
+    /// The tags are signed exactly as given, they are never reordered nor deduplicated (the proof
+    /// of work only appends a "nonce" tag)
+    /// ```rust
+    /// use std::str::FromStr;
+    /// use nostr_rust::{events::EventPrepare, Identity};
+    ///
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let tags = vec![
+    ///   vec!["e".to_string(), "aa".to_string(), "".to_string(), "root".to_string()],
+    ///   vec!["p".to_string(), "bb".to_string()],
+    ///   vec!["e".to_string(), "cc".to_string(), "".to_string(), "reply".to_string()],
+    ///   vec!["p".to_string(), "bb".to_string()],
+    /// ];
+    ///
+    /// let mut event = EventPrepare {
+    ///   pub_key: env!("PUBLIC_KEY").to_string(),
+    ///   created_at: 0, // Don't use this in production
+    ///   kind: 1,
+    ///   tags: tags.clone(),
+    ///   content: "reply".to_string(),
+    /// };
+    ///
+    /// let nostr_event = event.to_event(&identity, 0);
+    /// assert_eq!(nostr_event.tags, tags);
+    /// assert_eq!(nostr_event.id, "3bb7365e4504708853d6839d01e182fbc8ab75f886cd481e8adca7ef29fdb6d3");
+    ///
+    /// // Another order gives another id
+    /// event.tags.swap(0, 2);
+    /// assert_eq!(event.to_event(&identity, 0).id, "18a7fdf95d53df0868fe310de66b1843e39eaaabef5596725cd696df37209494");
+    ///
+    /// // The proof of work keeps the tags and appends the nonce
+    /// event.tags = tags.clone();
+    /// let nostr_event_pow = event.to_event(&identity, 4);
+    /// assert_eq!(nostr_event_pow.tags[..4], tags[..]);
+    /// assert_eq!(nostr_event_pow.tags[4][0], "nonce");
+    /// ```
     pub fn to_event(&mut self, secret_key: &Identity, difficulty_target: u16) -> Event {
         if difficulty_target > 0 {
             self.to_pow_event(difficulty_target).unwrap();
@@ -139,190 +276,1625 @@ impl EventPrepare {
             sig: signature,
         }
     }
-}
 
-/// Event is the struct used to represent a Nostr event
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Event {
-    /// 32-bytes sha256 of the serialized event data
-    pub id: String,
-    /// 32-bytes hex-encoded public key of the event creator
-    #[serde(rename = "pubkey")]
-    pub pub_key: String,
-    /// unix timestamp in seconds
-    pub created_at: u64,
-    /// integer
-    /// 0: NostrEvent
-    pub kind: u16,
-    /// Tags
-    pub tags: Vec<Vec<String>>,
-    /// arbitrary string
-    pub content: String,
-    /// 64-bytes signature of the sha256 hash of the serialized event data, which is the same as the "id" field
-    pub sig: String,
-}
+    #[cfg(feature = "net")]
+    /// Append a ["client", name] tag, unless the event already has a client tag
+    pub(crate) fn with_client_tag(mut self, client_tag: Option<&str>) -> Self {
+        let has_client_tag = self
+            .tags
+            .iter()
+            .any(|tag| tag.first().is_some_and(|name| name == "client"));
 
-#[derive(Error, Debug, Eq, PartialEq)]
-pub enum EventError {
-    #[error("Secp256k1 Error: {}", _0)]
-    Secp256k1Error(secp256k1::Error),
-}
+        if let (Some(name), false) = (client_tag, has_client_tag) {
+            self.tags.push(vec!["client".to_string(), name.to_string()]);
+        }
 
-impl From<secp256k1::Error> for EventError {
-    fn from(err: secp256k1::Error) -> Self {
-        Self::Secp256k1Error(err)
+        self
     }
-}
 
-impl Event {
-    /// get_content returns the content of the event
+    /// Get the id of the event as bytes, which is the sha256 digest to sign
     /// # Example
     /// ```rust
-    /// use nostr_rust::{events::EventPrepare, utils::get_timestamp, Identity};
-    /// use std::str::FromStr;
+    /// use nostr_rust::events::EventPrepare;
     ///
-    /// let actual_time = get_timestamp();
-    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
     /// let event = EventPrepare {
-    ///    pub_key: env!("PUBLIC_KEY").to_string(),
-    ///    created_at: get_timestamp(),
-    ///    kind: 0,
-    ///    tags: vec![],
-    ///    content: "content".to_string(),
-    /// }.to_event(&identity, 0);
-    /// assert_eq!(event.get_content(), format!("[0,\"{}\",{},0,[],\"content\"]", env!("PUBLIC_KEY"), actual_time));
+    ///   pub_key: env!("PUBLIC_KEY").to_string(),
+    ///   created_at: 0, // Don't use this in production
+    ///   kind: 0,
+    ///   tags: vec![],
+    ///   content: "content".to_string(),
+    /// };
+    ///
+    /// assert_eq!(hex::encode(event.id_bytes()), event.get_content_id());
     /// ```
-    pub fn get_content(&self) -> String {
-        json!([
-            0,
-            self.pub_key,
-            self.created_at,
-            self.kind,
-            self.tags,
-            self.content
-        ])
-        .to_string()
+    pub fn id_bytes(&self) -> [u8; 32] {
+        let mut id = [0u8; 32];
+        hex::decode_to_slice(self.get_content_id(), &mut id)
+            .expect("sha256 digest is 32 bytes hex encoded");
+        id
     }
 
-    /// Get the id of the event which is the sha256 hash of the content
+    /// Build the event with a signature produced outside of the crate (on a hardware wallet for example)
+    ///
+    /// The signature must be the schnorr signature of `id_bytes`
     /// # Example
     /// ```rust
-    /// use nostr_rust::{events::EventPrepare, Identity};
-    /// use std::str::FromStr;
-    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// use nostr_rust::{events::EventPrepare, keys};
+    /// use secp256k1::{KeyPair, Message, SECP256K1};
+    ///
+    /// let (secret_key, public_key) = keys::get_random_secret_key();
     /// let event = EventPrepare {
-    ///   pub_key: env!("PUBLIC_KEY").to_string(),
+    ///   pub_key: keys::normalize_public_key(&public_key.to_string()),
     ///   created_at: 0, // Don't use this in production
-    ///   kind: 0,
+    ///   kind: 1,
     ///   tags: vec![],
     ///   content: "content".to_string(),
-    /// }.to_event(&identity, 0);
+    /// };
     ///
-    /// assert_eq!(event.get_content_id().len(), 64);
+    /// // Sign on another device
+    /// let message = Message::from_slice(&event.id_bytes()).unwrap();
+    /// let signature = SECP256K1.sign_schnorr(&message, &KeyPair::from_secret_key(SECP256K1, &secret_key));
+    ///
+    /// let event = event.finalize_with_signature(*signature.as_ref());
+    /// assert_eq!(event.id, event.get_content_id());
+    /// event.verify().unwrap();
     /// ```
-    pub fn get_content_id(&self) -> String {
-        sha256::digest(self.get_content())
+    pub fn finalize_with_signature(self, sig: [u8; 64]) -> Event {
+        Event {
+            id: self.get_content_id(),
+            pub_key: self.pub_key,
+            created_at: self.created_at,
+            kind: self.kind,
+            tags: self.tags,
+            content: self.content,
+            sig: hex::encode(sig),
+        }
     }
+}
 
-    /// Get the id of the event which is the sha256 hash of the content
+/// Builder of `EventPrepare`, see `EventPrepare::builder`
+#[derive(Debug, Clone)]
+pub struct EventPrepareBuilder {
+    pub_key: String,
+    created_at: Option<u64>,
+    kind: u16,
+    tags: Vec<Vec<String>>,
+    content: String,
+}
+
+impl EventPrepareBuilder {
+    pub fn kind(mut self, kind: u16) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn content(mut self, content: &str) -> Self {
+        self.content = content.to_string();
+        self
+    }
+
+    /// Append a tag
+    pub fn tag<T: Into<String>>(mut self, tag: impl IntoIterator<Item = T>) -> Self {
+        self.tags.push(tag.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Append several tags
+    pub fn tags(mut self, tags: &[Vec<String>]) -> Self {
+        self.tags.extend_from_slice(tags);
+        self
+    }
+
+    /// Add the NIP-70 protected tag (`["-"]`), relays supporting it only accept the event from
+    /// its author
     /// # Example
     /// ```rust
     /// use nostr_rust::{events::EventPrepare, Identity};
     /// use std::str::FromStr;
     ///
     /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = EventPrepare::builder(&identity.public_key_str)
+    ///   .content("Only for my relays")
+    ///   .protected()
+    ///   .build()
+    ///   .unwrap()
+    ///   .to_event(&identity, 0);
     ///
-    /// let event = EventPrepare {
-    ///   pub_key: env!("PUBLIC_KEY").to_string(),
-    ///   created_at: 0, // Don't use this in production
-    ///   kind: 0,
-    ///   tags: vec![],
-    ///   content: "content".to_string(),
-    /// }.to_event(&identity, 0);
+    /// assert!(event.is_protected());
+    /// ```
+    pub fn protected(self) -> Self {
+        if self.tags.iter().any(|tag| tag.len() == 1 && tag[0] == "-") {
+            return self;
+        }
+
+        self.tag(["-"])
+    }
+
+    /// Set the creation date, now by default
+    pub fn created_at(mut self, created_at: u64) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Set the creation date to now randomly backdated by up to `max_past`, see
+    /// `utils::jittered_timestamp`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, utils::get_timestamp, Identity};
+    /// use std::{str::FromStr, time::Duration};
     ///
-    /// event.verify().unwrap()
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = EventPrepare::builder(&identity.public_key_str)
+    ///   .jittered_created_at(Duration::from_secs(3600))
+    ///   .build()
+    ///   .unwrap();
+    ///
+    /// assert!(event.created_at <= get_timestamp());
+    /// assert!(event.created_at >= get_timestamp() - 3601);
     /// ```
-    pub fn verify(&self) -> Result<(), EventError> {
-        let message = secp256k1::Message::from_hashed_data::<secp256k1::hashes::sha256::Hash>(
-            self.get_content().as_bytes(),
-        );
+    pub fn jittered_created_at(self, max_past: Duration) -> Self {
+        self.created_at(jittered_timestamp(max_past))
+    }
 
-        SECP256K1.verify_schnorr(
-            &Signature::from_str(&self.sig)?,
-            &message,
-            &XOnlyPublicKey::from_str(&self.pub_key)?,
-        )?;
-        Ok(())
+    /// Build the event, fails if the public key is not a valid hex encoded public key
+    pub fn build(self) -> Result<EventPrepare, EventError> {
+        XOnlyPublicKey::from_str(&self.pub_key)?;
+
+        Ok(EventPrepare {
+            pub_key: self.pub_key,
+            created_at: self.created_at.unwrap_or_else(get_timestamp),
+            kind: self.kind,
+            tags: self.tags,
+            content: self.content,
+        })
     }
 }
 
-impl fmt::Display for Event {
-    /// Return the serialized event
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", serde_json::to_string(&self).unwrap())
-    }
+/// Event is the struct used to represent a Nostr event
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Event {
+    /// 32-bytes sha256 of the serialized event data
+    pub id: String,
+    /// 32-bytes hex-encoded public key of the event creator
+    #[serde(rename = "pubkey")]
+    pub pub_key: String,
+    /// unix timestamp in seconds
+    pub created_at: u64,
+    /// integer
+    /// 0: NostrEvent
+    ///
+    /// A kind sent as a string (`"1"`) or a float (`1.0`) is accepted
+    #[serde(deserialize_with = "deserialize_kind")]
+    pub kind: u16,
+    /// Tags
+    pub tags: Vec<Vec<String>>,
+    /// arbitrary string
+    pub content: String,
+    /// 64-bytes signature of the sha256 hash of the serialized event data, which is the same as the "id" field
+    pub sig: String,
 }
 
-/// Extract events from a string
+/// Deserialize a kind sent as an integer, a float without fractional part or a string
 ///
 /// # Example
 /// ```rust
-/// use nostr_rust::events::extract_events;
+/// use nostr_rust::events::Event;
 ///
-/// let txt = "[\"EVENT\",\"deb0ab5bd829d1642c926b7897b078d027ca41870d0a499c1fd76e4b5af5ccbd\",{\"id\":\"f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4\",\"kind\":1,\"pubkey\":\"884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6\",\"created_at\":1673131597,\"content\":\"Does anyone know a good crate rust to handle a Lightning node?\",\"tags\":[],\"sig\":\"53a629bae11dace9b487700cbe8e85058a3d7b6989e1e0bdd6eb4fb0201a3779742682f65ca37782c0cb93019a170e0a368bb033dfce1102df71420e24e2b784\"}]";
+/// let event: Event = serde_json::from_str(r#"{"id":"","pubkey":"","created_at":0,"kind":"7","tags":[],"content":"","sig":"","extra":true}"#).unwrap();
+/// assert_eq!(event.kind, 7);
 ///
-/// let events = extract_events(txt);
-/// assert_eq!(events.len(), 1);
+/// let event: Event = serde_json::from_str(r#"{"id":"","pubkey":"","created_at":0,"kind":1.0,"tags":[],"content":"","sig":""}"#).unwrap();
+/// assert_eq!(event.kind, 1);
+///
+/// assert!(serde_json::from_str::<Event>(r#"{"id":"","pubkey":"","created_at":0,"kind":70000,"tags":[],"content":"","sig":""}"#).is_err());
 /// ```
-pub fn extract_events(message: &str) -> Vec<Event> {
-    let mut events = vec![];
-    let json = serde_json::from_str::<serde_json::Value>(message);
-
-    if json.is_err() {
-        return events;
-    }
-
-    let json = json.unwrap();
+fn deserialize_kind<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+    struct KindVisitor;
 
-    if !json.is_array() {
-        return events;
-    }
+    impl<'de> Visitor<'de> for KindVisitor {
+        type Value = u16;
 
-    let json = json.as_array().unwrap();
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an event kind between 0 and 65535")
+        }
 
-    for event in json {
-        if !event.is_object() {
-            continue;
+        fn visit_u64<E: de::Error>(self, value: u64) -> Result<u16, E> {
+            u16::try_from(value).map_err(|_| E::invalid_value(Unexpected::Unsigned(value), &self))
         }
 
-        let event = serde_json::from_value::<Event>(event.clone());
+        fn visit_i64<E: de::Error>(self, value: i64) -> Result<u16, E> {
+            u16::try_from(value).map_err(|_| E::invalid_value(Unexpected::Signed(value), &self))
+        }
 
-        if event.is_err() {
-            continue;
+        fn visit_f64<E: de::Error>(self, value: f64) -> Result<u16, E> {
+            if value.fract() == 0.0 && (0.0..=u16::MAX as f64).contains(&value) {
+                Ok(value as u16)
+            } else {
+                Err(E::invalid_value(Unexpected::Float(value), &self))
+            }
         }
 
-        events.push(event.unwrap());
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<u16, E> {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+        }
     }
 
-    events
+    deserializer.deserialize_any(KindVisitor)
 }
 
-/// Extract events from a websocket message
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum EventError {
+    #[error("Secp256k1 Error: {}", _0)]
+    Secp256k1Error(secp256k1::Error),
+
+    #[error("Invalid event id, it must be 32 bytes hex encoded")]
+    InvalidEventId,
+
+    #[error("Invalid address, the format must be <kind>:<pubkey>:<identifier>")]
+    InvalidAddress,
+}
+
+/// Verify the signatures of events, the results are in the order of the events
 ///
+/// The events are verified on several threads with the `parallel` feature, one after the other
+/// otherwise
 /// # Example
 /// ```rust
-/// use nostr_rust::events::extract_events_ws;
-/// use tungstenite::Message;
+/// use nostr_rust::{events::{verify_batch, EventPrepare}, Identity};
+/// use std::str::FromStr;
 ///
-/// let txt = "[\"EVENT\",\"deb0ab5bd829d1642c926b7897b078d027ca41870d0a499c1fd76e4b5af5ccbd\",{\"id\":\"f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4\",\"kind\":1,\"pubkey\":\"884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6\",\"created_at\":1673131597,\"content\":\"Does anyone know a good crate rust to handle a Lightning node?\",\"tags\":[],\"sig\":\"53a629bae11dace9b487700cbe8e85058a3d7b6989e1e0bdd6eb4fb0201a3779742682f65ca37782c0cb93019a170e0a368bb033dfce1102df71420e24e2b784\"}]";
+/// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+/// let event = EventPrepare::builder(&identity.public_key_str).content("hi").build().unwrap().to_event(&identity, 0);
+/// let mut forged = event.clone();
+/// forged.content = "bye".to_string();
 ///
-/// let events = extract_events_ws(&Message::Text(txt.to_string()));
-/// assert_eq!(events.len(), 1);
+/// let results = verify_batch(&[event, forged]);
+/// assert!(results[0].is_ok());
+/// assert!(results[1].is_err());
 /// ```
-pub fn extract_events_ws(message: &crate::Message) -> Vec<Event> {
-    if message.is_text() {
-        return extract_events(message.to_text().unwrap());
+pub fn verify_batch(events: &[Event]) -> Vec<Result<(), EventError>> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        events.par_iter().map(Event::verify).collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        events.iter().map(Event::verify).collect()
+    }
+}
+
+/// Event compared and hashed by its id only, to deduplicate events in a `HashSet` while
+/// keeping the full events
+/// # Example
+/// ```rust
+/// use nostr_rust::events::{Event, EventById};
+/// use std::collections::HashSet;
+///
+/// let event = Event {
+///   id: "event_id".to_string(),
+///   pub_key: "pubkey".to_string(),
+///   created_at: 0,
+///   kind: 1,
+///   tags: vec![],
+///   content: String::new(),
+///   sig: String::new(),
+/// };
+/// let mut copy = event.clone();
+/// copy.tags.push(vec!["client".to_string(), "another relay".to_string()]);
+///
+/// let mut events: HashSet<EventById> = HashSet::new();
+/// assert!(events.insert(EventById(event)));
+/// assert!(!events.insert(EventById(copy)));
+/// assert!(events.contains("event_id"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventById(pub Event);
+
+impl PartialEq for EventById {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.id == other.0.id
+    }
+}
+
+impl Eq for EventById {}
+
+impl std::hash::Hash for EventById {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.id.hash(state);
+    }
+}
+
+/// Look up an `EventById` by id, hashed like the id itself
+impl std::borrow::Borrow<str> for EventById {
+    fn borrow(&self) -> &str {
+        &self.0.id
+    }
+}
+
+impl std::ops::Deref for EventById {
+    type Target = Event;
+
+    fn deref(&self) -> &Event {
+        &self.0
+    }
+}
+
+impl From<Event> for EventById {
+    fn from(event: Event) -> Self {
+        Self(event)
+    }
+}
+
+/// Address of a parameterized replaceable event, referenced by "a" tags
+/// # Example
+/// ```rust
+/// use nostr_rust::events::Address;
+/// use std::str::FromStr;
+///
+/// let coordinate = "30023:884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6:my-article";
+/// let address = Address::from_str(coordinate).unwrap();
+/// assert_eq!(address.kind, 30023);
+/// assert_eq!(address.identifier, "my-article");
+/// assert_eq!(address.to_string(), coordinate);
+///
+/// assert!(Address::from_str("30023:884704bd").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Address {
+    pub kind: u16,
+    /// 32-bytes hex-encoded public key of the event creator
+    pub pub_key: String,
+    /// Value of the "d" tag
+    pub identifier: String,
+}
+
+impl FromStr for Address {
+    type Err = EventError;
+
+    fn from_str(coordinate: &str) -> Result<Self, Self::Err> {
+        let mut parts = coordinate.splitn(3, ':');
+
+        let kind = parts
+            .next()
+            .and_then(|kind| kind.parse().ok())
+            .ok_or(EventError::InvalidAddress)?;
+        let pub_key = parts.next().ok_or(EventError::InvalidAddress)?;
+        let identifier = parts.next().ok_or(EventError::InvalidAddress)?;
+
+        XOnlyPublicKey::from_str(pub_key).map_err(|_| EventError::InvalidAddress)?;
+
+        Ok(Self {
+            kind,
+            pub_key: pub_key.to_string(),
+            identifier: identifier.to_string(),
+        })
+    }
+}
+
+impl Address {
+    /// Get the "a" tag referencing the address
+    pub fn to_tag(&self) -> Vec<String> {
+        vec!["a".to_string(), self.to_string()]
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.kind, self.pub_key, self.identifier)
+    }
+}
+
+/// Validated event id (32 bytes sha256 of the serialized event)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EventId([u8; 32]);
+
+impl EventId {
+    /// Parse a hex encoded event id
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::EventId;
+    ///
+    /// let id = EventId::from_hex("f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4").unwrap();
+    /// assert_eq!(id.to_hex(), "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4");
+    ///
+    /// assert!(EventId::from_hex("f0382d93").is_err());
+    /// assert!(EventId::from_hex("not an id").is_err());
+    /// ```
+    pub fn from_hex(id: &str) -> Result<Self, EventError> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(id, &mut bytes).map_err(|_| EventError::InvalidEventId)?;
+        Ok(Self(bytes))
+    }
+
+    /// Parse a bech32 encoded event id ("note")
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::EventId;
+    ///
+    /// let id = EventId::from_bech32("note17quzmyedm3v8dwknl8zlmwz0knp27lxwl6lmfy03877y0su03tjqcqymya").unwrap();
+    /// assert_eq!(id.to_hex(), "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4");
+    /// assert_eq!(id.to_bech32(), "note17quzmyedm3v8dwknl8zlmwz0knp27lxwl6lmfy03877y0su03tjqcqymya");
+    ///
+    /// assert!(EventId::from_bech32("f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4").is_err());
+    /// ```
+    pub fn from_bech32(id: &str) -> Result<Self, EventError> {
+        if !id.starts_with("note") {
+            return Err(EventError::InvalidEventId);
+        }
+
+        let hex_id =
+            from_hb_to_hex(ToBech32Kind::Note, id).map_err(|_| EventError::InvalidEventId)?;
+        Self::from_hex(&hex_id)
+    }
+
+    /// Get the hex encoded event id
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Get the bech32 encoded event id ("note")
+    pub fn to_bech32(&self) -> String {
+        to_bech32(ToBech32Kind::Note, &self.to_hex()).expect("32 bytes are bech32 encodable")
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Get the tag referencing the event ("e" tag)
+    pub fn to_tag(&self) -> Vec<String> {
+        vec!["e".to_string(), self.to_hex()]
+    }
+}
+
+impl FromStr for EventId {
+    type Err = EventError;
+
+    /// Parse a hex or bech32 ("note") encoded event id
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        if id.starts_with("note") {
+            Self::from_bech32(id)
+        } else {
+            Self::from_hex(id)
+        }
+    }
+}
+
+impl fmt::Display for EventId {
+    /// Return the hex encoded event id
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl From<secp256k1::Error> for EventError {
+    fn from(err: secp256k1::Error) -> Self {
+        Self::Secp256k1Error(err)
+    }
+}
+
+impl Event {
+    /// get_content returns the content of the event
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, utils::get_timestamp, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let actual_time = get_timestamp();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = EventPrepare {
+    ///    pub_key: env!("PUBLIC_KEY").to_string(),
+    ///    created_at: get_timestamp(),
+    ///    kind: 0,
+    ///    tags: vec![],
+    ///    content: "content".to_string(),
+    /// }.to_event(&identity, 0);
+    /// assert_eq!(event.get_content(), format!("[0,\"{}\",{},0,[],\"content\"]", env!("PUBLIC_KEY"), actual_time));
+    /// ```
+    pub fn get_content(&self) -> String {
+        json!([
+            0,
+            self.pub_key,
+            self.created_at,
+            self.kind,
+            self.tags,
+            self.content
+        ])
+        .to_string()
+    }
+
+    /// Get the id of the event which is the sha256 hash of the content
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, Identity};
+    /// use std::str::FromStr;
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = EventPrepare {
+    ///   pub_key: env!("PUBLIC_KEY").to_string(),
+    ///   created_at: 0, // Don't use this in production
+    ///   kind: 0,
+    ///   tags: vec![],
+    ///   content: "content".to_string(),
+    /// }.to_event(&identity, 0);
+    ///
+    /// assert_eq!(event.get_content_id().len(), 64);
+    /// ```
+    pub fn get_content_id(&self) -> String {
+        sha256::digest(self.get_content())
+    }
+
+    /// Get the id of the event which is the sha256 hash of the content
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let event = EventPrepare {
+    ///   pub_key: env!("PUBLIC_KEY").to_string(),
+    ///   created_at: 0, // Don't use this in production
+    ///   kind: 0,
+    ///   tags: vec![],
+    ///   content: "content".to_string(),
+    /// }.to_event(&identity, 0);
+    ///
+    /// event.verify().unwrap()
+    /// ```
+    pub fn verify(&self) -> Result<(), EventError> {
+        self.verify_with(&XOnlyPublicKey::from_str(&self.pub_key)?)
+    }
+
+    /// Verify the signature of the event against the given public key instead of the embedded one
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let other = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000004").unwrap();
+    ///
+    /// let event = EventPrepare {
+    ///   pub_key: identity.public_key_str.clone(),
+    ///   created_at: 0, // Don't use this in production
+    ///   kind: 0,
+    ///   tags: vec![],
+    ///   content: "content".to_string(),
+    /// }.to_event(&identity, 0);
+    ///
+    /// assert!(event.verify_with(&identity.public_key.x_only_public_key().0).is_ok());
+    /// assert!(event.verify_with(&other.public_key.x_only_public_key().0).is_err());
+    /// assert_eq!(event.signature().unwrap().to_string(), event.sig);
+    /// ```
+    pub fn verify_with(&self, pub_key: &XOnlyPublicKey) -> Result<(), EventError> {
+        let message = secp256k1::Message::from_hashed_data::<secp256k1::hashes::sha256::Hash>(
+            self.get_content().as_bytes(),
+        );
+
+        SECP256K1.verify_schnorr(&self.signature()?, &message, pub_key)?;
+        Ok(())
+    }
+
+    /// Get the parsed schnorr signature of the event
+    pub fn signature(&self) -> Result<Signature, EventError> {
+        Ok(Signature::from_str(&self.sig)?)
+    }
+
+    /// Get the validated id of the event
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let mut event = Event {
+    ///   id: "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4".to_string(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    /// assert_eq!(event.event_id().unwrap().to_string(), event.id);
+    ///
+    /// event.id = "invalid".to_string();
+    /// assert!(event.event_id().is_err());
+    /// ```
+    pub fn event_id(&self) -> Result<EventId, EventError> {
+        EventId::from_hex(&self.id)
+    }
+
+    /// Get the shareable "note" of the event (NIP-19), empty if the event id is invalid
+    pub fn to_note(&self) -> String {
+        self.event_id().map(|id| id.to_bech32()).unwrap_or_default()
+    }
+
+    /// Get the shareable "nevent" of the event with relay hints (NIP-19)
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let event = Event {
+    ///   id: "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4".to_string(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert_eq!(event.to_note(), "note17quzmyedm3v8dwknl8zlmwz0knp27lxwl6lmfy03877y0su03tjqcqymya");
+    /// assert_eq!(event.to_nevent(&[]).unwrap(), "nevent1qqs0qwpdjvkackrkhtfln30ahp8mfs400n80a0a5j8cnl0z8cw8c4eq6rmgeg");
+    /// assert!(event.to_nevent(&["wss://relay.damus.io".to_string()]).unwrap().starts_with("nevent1"));
+    /// ```
+    pub fn to_nevent(&self, relays: &[String]) -> Result<String, Bech32Error> {
+        to_nevent(&self.id, relays)
+    }
+
+    /// Get the subject of the event (NIP-14 "subject" tag)
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![vec!["subject".to_string(), "Nostr meetup".to_string()]],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert_eq!(event.subject(), Some("Nostr meetup"));
+    /// ```
+    pub fn subject(&self) -> Option<&str> {
+        self.first_tag_value("subject")
+    }
+
+    /// Classify the event in its thread from its "e" tags (NIP-10)
+    ///
+    /// Both the marked tags and the deprecated positional tags are understood. An event without
+    /// "e" tag is `ThreadRole::Standalone`: only the replies tell it starts a thread, see
+    /// `Event::thread_role_in`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::{Event, ThreadRole};
+    ///
+    /// let event = |kind: u16, content: &str, tags: Vec<Vec<&str>>| Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind,
+    ///   tags: tags.into_iter().map(|tag| tag.into_iter().map(str::to_string).collect()).collect(),
+    ///   content: content.to_string(),
+    ///   sig: String::new(),
+    /// };
+    /// let reply = |parent: &str, root: &str| ThreadRole::Reply { parent: parent.to_string(), root: root.to_string() };
+    ///
+    /// assert_eq!(event(1, "", vec![vec!["p", "pk"]]).thread_role(), ThreadRole::Standalone);
+    /// assert_eq!(event(30023, "", vec![]).thread_role(), ThreadRole::Standalone);
+    ///
+    /// // Marked tags
+    /// assert_eq!(event(1, "", vec![vec!["e", "r", "", "root"]]).thread_role(), reply("r", "r"));
+    /// assert_eq!(event(1, "", vec![vec!["e", "r", "", "root"], vec!["e", "p", "", "reply"]]).thread_role(), reply("p", "r"));
+    /// assert_eq!(event(1, "", vec![vec!["e", "p", "", "reply"]]).thread_role(), reply("p", "p"));
+    /// assert_eq!(event(1, "", vec![vec!["e", "m", "", "mention"]]).thread_role(), ThreadRole::Mention);
+    /// assert_eq!(event(1, "", vec![vec!["e", "m", "", "mention"], vec!["e", "r", "", "root"]]).thread_role(), reply("r", "r"));
+    ///
+    /// // Positional tags
+    /// assert_eq!(event(1, "", vec![vec!["e", "r"]]).thread_role(), reply("r", "r"));
+    /// assert_eq!(event(1, "", vec![vec!["e", "r"], vec!["e", "p"]]).thread_role(), reply("p", "r"));
+    /// assert_eq!(event(1, "", vec![vec!["e", "r"], vec!["e", "m"], vec!["e", "p"]]).thread_role(), reply("p", "r"));
+    /// assert_eq!(event(1, "see #[0]", vec![vec!["e", "m"]]).thread_role(), ThreadRole::Mention);
+    /// assert_eq!(event(1, "see #[1]", vec![vec!["e", "r"], vec!["e", "m"]]).thread_role(), reply("r", "r"));
+    /// ```
+    pub fn thread_role(&self) -> ThreadRole {
+        if let Some((parent, root)) = thread_ids(self) {
+            ThreadRole::Reply {
+                parent: parent.to_string(),
+                root: root.to_string(),
+            }
+        } else if self.tags_by_name("e").any(|tag| tag.len() >= 2) {
+            ThreadRole::Mention
+        } else {
+            ThreadRole::Standalone
+        }
+    }
+
+    /// Classify the event in its thread like `Event::thread_role`, an event without "e" tag is
+    /// `ThreadRole::Root` if one of the given events replies to it in its thread
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::{Event, ThreadRole};
+    ///
+    /// let event = |id: &str, tags: Vec<Vec<&str>>| Event {
+    ///   id: id.to_string(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: tags.into_iter().map(|tag| tag.into_iter().map(str::to_string).collect()).collect(),
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// let note = event("r", vec![]);
+    /// let reply = event("p", vec![vec!["e", "r", "", "root"]]);
+    /// let mention = event("m", vec![vec!["e", "r", "", "mention"]]);
+    ///
+    /// assert_eq!(note.thread_role_in(&[reply.clone()]), ThreadRole::Root);
+    /// assert_eq!(note.thread_role_in(&[mention]), ThreadRole::Standalone);
+    /// assert_eq!(note.thread_role_in(&[]), ThreadRole::Standalone);
+    /// assert_eq!(reply.thread_role_in(&[note]), reply.thread_role());
+    /// ```
+    pub fn thread_role_in(&self, events: &[Event]) -> ThreadRole {
+        match self.thread_role() {
+            ThreadRole::Standalone
+                if events
+                    .iter()
+                    .any(|event| thread_ids(event).is_some_and(|(_, root)| root == self.id)) =>
+            {
+                ThreadRole::Root
+            }
+            role => role,
+        }
+    }
+
+    /// Get the category of the kind of the event, see `KindClass`
+    pub fn kind_class(&self) -> KindClass {
+        KindClass::of(self.kind)
+    }
+
+    /// Get the address of an addressable event (kinds 30000-39999), referenced by "a" tags
+    ///
+    /// An addressable event without "d" tag has an empty identifier
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let mut event = Event {
+    ///   id: String::new(),
+    ///   pub_key: "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///   created_at: 0,
+    ///   kind: 30023,
+    ///   tags: vec![vec!["d".to_string(), "my-article".to_string()]],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// let address = event.address().unwrap();
+    /// assert_eq!(address.to_string(), "30023:884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6:my-article");
+    /// assert_eq!(address.to_tag()[0], "a");
+    ///
+    /// event.tags.clear();
+    /// assert_eq!(event.address().unwrap().identifier, "");
+    ///
+    /// event.kind = 1;
+    /// assert!(event.address().is_none());
+    /// ```
+    pub fn address(&self) -> Option<Address> {
+        if self.kind_class() != KindClass::Addressable {
+            return None;
+        }
+
+        Some(Address {
+            kind: self.kind,
+            pub_key: self.pub_key.clone(),
+            identifier: self.first_tag_value("d").unwrap_or_default().to_string(),
+        })
+    }
+
+    /// Get the name of the client which published the event ("client" tag)
+    pub fn client_name(&self) -> Option<&str> {
+        self.first_tag_value("client")
+    }
+
+    /// Check if the event has the NIP-70 protected tag (`["-"]`): only its author may publish it,
+    /// relays supporting NIP-70 reject it when sent by anyone else
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let mut event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![vec!["-".to_string(), "not the protected tag".to_string()]],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    /// assert!(!event.is_protected());
+    ///
+    /// event.tags.push(vec!["-".to_string()]);
+    /// assert!(event.is_protected());
+    /// ```
+    pub fn is_protected(&self) -> bool {
+        self.tags.iter().any(|tag| tag.len() == 1 && tag[0] == "-")
+    }
+
+    /// Get the expiration timestamp of the event (NIP-40 "expiration" tag)
+    pub fn expiration(&self) -> Option<u64> {
+        self.first_tag_value("expiration")?.parse().ok()
+    }
+
+    /// Check if the event is expired (NIP-40), events without expiration never expire
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::Event, utils::get_timestamp};
+    ///
+    /// let mut event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    /// assert!(!event.is_expired());
+    ///
+    /// event.tags.push(vec!["expiration".to_string(), (get_timestamp() - 10).to_string()]);
+    /// assert!(event.is_expired());
+    /// assert!(!event.is_expired_at(get_timestamp() - 20));
+    /// ```
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(get_timestamp())
+    }
+
+    /// Check if the event is expired at the given timestamp (NIP-40)
+    pub fn is_expired_at(&self, timestamp: u64) -> bool {
+        self.expiration()
+            .is_some_and(|expiration| expiration <= timestamp)
+    }
+
+    /// Get the creation date of the event as a `SystemTime`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 1673131597,
+    ///   kind: 1,
+    ///   tags: vec![],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert_eq!(event.created_at_system_time(), UNIX_EPOCH + Duration::from_secs(1673131597));
+    /// ```
+    pub fn created_at_system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.created_at)
+    }
+
+    /// Get the time elapsed since the creation of the event
+    ///
+    /// Events dated in the future (clock skew) have an age of zero
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::Event, utils::get_timestamp};
+    /// use std::time::Duration;
+    ///
+    /// let mut event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: get_timestamp() - 7200,
+    ///   kind: 1,
+    ///   tags: vec![],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    /// assert!(event.age() >= Duration::from_secs(7200));
+    ///
+    /// event.created_at = get_timestamp() + 3600;
+    /// assert_eq!(event.age(), Duration::ZERO);
+    /// ```
+    pub fn age(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(self.created_at_system_time())
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// Get the tags with the given name (first element of the tag)
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![
+    ///     vec!["e".to_string(), "event_id".to_string()],
+    ///     vec![],
+    ///     vec!["p".to_string()],
+    ///     vec!["e".to_string(), "other_event_id".to_string()],
+    ///   ],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert_eq!(event.tags_by_name("e").count(), 2);
+    /// assert_eq!(event.tags_by_name("p").count(), 1);
+    /// assert_eq!(event.tags_by_name("t").count(), 0);
+    /// ```
+    pub fn tags_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Vec<String>> {
+        self.tags
+            .iter()
+            .filter(move |tag| tag_value(tag, 0) == Some(name))
+    }
+
+    /// Get the value (second element) of the first tag with the given name having a value
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![
+    ///     vec!["p".to_string()],
+    ///     vec!["p".to_string(), "pubkey".to_string()],
+    ///   ],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert_eq!(event.first_tag_value("p"), Some("pubkey"));
+    /// assert_eq!(event.first_tag_value("e"), None);
+    /// ```
+    pub fn first_tag_value(&self, name: &str) -> Option<&str> {
+        self.tag_values(name).into_iter().next()
+    }
+
+    /// Get the event ids referenced in the "e" tags
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![
+    ///     vec!["e".to_string()],
+    ///     vec!["e".to_string(), "event_id".to_string(), "".to_string(), "reply".to_string()],
+    ///   ],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert_eq!(event.referenced_event_ids(), vec!["event_id"]);
+    /// ```
+    pub fn referenced_event_ids(&self) -> Vec<&str> {
+        self.tag_values("e")
+    }
+
+    /// Get the public keys referenced in the "p" tags
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![
+    ///     vec!["p".to_string(), "pubkey".to_string()],
+    ///     vec!["p".to_string()],
+    ///   ],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert_eq!(event.referenced_pubkeys(), vec!["pubkey"]);
+    /// ```
+    pub fn referenced_pubkeys(&self) -> Vec<&str> {
+        self.tag_values("p")
+    }
+
+    /// Check if an "e" tag references the event id (hex or note), as a reply or as a mention
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{bech32::{to_bech32, ToBech32Kind}, events::Event};
+    ///
+    /// let id = "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4";
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![vec!["e".to_string()], vec!["e".to_string(), id.to_string()]],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert!(event.references_event(id));
+    /// assert!(event.references_event(&to_bech32(ToBech32Kind::Note, id).unwrap()));
+    /// assert!(!event.references_event("other_id"));
+    /// ```
+    pub fn references_event(&self, id: &str) -> bool {
+        references(self.tag_values("e"), id)
+    }
+
+    /// Check if a "p" tag references the public key (hex or npub)
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{bech32::{to_bech32, ToBech32Kind}, events::Event};
+    ///
+    /// let pubkey = "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6";
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![vec!["p".to_string(), pubkey.to_string()]],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert!(event.references_pubkey(pubkey));
+    /// assert!(event.references_pubkey(&to_bech32(ToBech32Kind::PublicKey, pubkey).unwrap()));
+    /// assert!(!event.references_pubkey("other_pubkey"));
+    /// ```
+    pub fn references_pubkey(&self, pubkey: &str) -> bool {
+        references(self.tag_values("p"), pubkey)
+    }
+
+    /// Check if the event directly replies to the event id (hex or note), see `Event::thread_role`
+    ///
+    /// A mention or the root of the thread of a nested reply is not a direct reply
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::Event;
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![
+    ///     vec!["e", "root_id", "", "root"],
+    ///     vec!["e", "parent_id", "", "reply"],
+    ///     vec!["e", "mention_id", "", "mention"],
+    ///   ]
+    ///   .into_iter()
+    ///   .map(|tag| tag.into_iter().map(str::to_string).collect())
+    ///   .collect(),
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert!(event.is_reply_to("parent_id"));
+    /// assert!(!event.is_reply_to("root_id"));
+    /// assert!(!event.is_reply_to("mention_id"));
+    /// ```
+    pub fn is_reply_to(&self, id: &str) -> bool {
+        thread_ids(self).is_some_and(|(parent, _)| references([parent], id))
+    }
+
+    /// Values of all the tags with the given name, skipping the tags without value
+    fn tag_values(&self, name: &str) -> Vec<&str> {
+        self.tags
+            .iter()
+            .filter(|tag| tag_value(tag, 0) == Some(name))
+            .filter_map(|tag| tag_value(tag, 1))
+            .collect()
+    }
+
+    /// Split the content into text and mentions, resolving the `#[index]` placeholders with the tags
+    ///
+    /// Placeholders pointing to a missing or malformed tag are left as text
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::events::{ContentSegment, Event};
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 1,
+    ///   tags: vec![
+    ///     vec!["p".to_string(), "pubkey".to_string()],
+    ///     vec!["e".to_string(), "event_id".to_string()],
+    ///   ],
+    ///   content: "hello #[0], see #[1] and #[2]".to_string(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert_eq!(event.resolve_mentions(), vec![
+    ///   ContentSegment::Text("hello ".to_string()),
+    ///   ContentSegment::PublicKey("pubkey".to_string()),
+    ///   ContentSegment::Text(", see ".to_string()),
+    ///   ContentSegment::Event("event_id".to_string()),
+    ///   ContentSegment::Text(" and #[2]".to_string()),
+    /// ]);
+    /// ```
+    pub fn resolve_mentions(&self) -> Vec<ContentSegment> {
+        let mut segments: Vec<ContentSegment> = Vec::new();
+        let mut text = String::new();
+        let mut rest = self.content.as_str();
+
+        while let Some(start) = rest.find("#[") {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            let mention = rest[2..].find(']').and_then(|end| {
+                let index = rest[2..2 + end].parse::<usize>().ok()?;
+                let tag = self.tags.get(index)?;
+                let value = tag_value(tag, 1)?.to_string();
+
+                let segment = match tag_value(tag, 0)? {
+                    "p" => ContentSegment::PublicKey(value),
+                    "e" => ContentSegment::Event(value),
+                    _ => return None,
+                };
+
+                Some((segment, end + 3))
+            });
+
+            match mention {
+                Some((segment, length)) => {
+                    if !text.is_empty() {
+                        segments.push(ContentSegment::Text(std::mem::take(&mut text)));
+                    }
+
+                    segments.push(segment);
+                    rest = &rest[length..];
+                }
+                None => {
+                    text.push_str("#[");
+                    rest = &rest[2..];
+                }
+            }
+        }
+
+        text.push_str(rest);
+
+        if !text.is_empty() {
+            segments.push(ContentSegment::Text(text));
+        }
+
+        segments
+    }
+}
+
+/// Part of the content of an event, see `Event::resolve_mentions`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentSegment {
+    /// Raw text
+    Text(String),
+    /// Mention of a public key ("p" tag)
+    PublicKey(String),
+    /// Mention of an event ("e" tag)
+    Event(String),
+}
+
+impl fmt::Display for Event {
+    /// Return the serialized event
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", serde_json::to_string(&self).unwrap())
+    }
+}
+
+/// Extract events from a string
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::events::extract_events;
+///
+/// let txt = "[\"EVENT\",\"deb0ab5bd829d1642c926b7897b078d027ca41870d0a499c1fd76e4b5af5ccbd\",{\"id\":\"f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4\",\"kind\":1,\"pubkey\":\"884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6\",\"created_at\":1673131597,\"content\":\"Does anyone know a good crate rust to handle a Lightning node?\",\"tags\":[],\"sig\":\"53a629bae11dace9b487700cbe8e85058a3d7b6989e1e0bdd6eb4fb0201a3779742682f65ca37782c0cb93019a170e0a368bb033dfce1102df71420e24e2b784\"}]";
+///
+/// let events = extract_events(txt);
+/// assert_eq!(events.len(), 1);
+/// ```
+pub fn extract_events(message: &str) -> Vec<Event> {
+    let mut events = vec![];
+    let json = serde_json::from_str::<serde_json::Value>(message);
+
+    if json.is_err() {
+        return events;
+    }
+
+    let json = json.unwrap();
+
+    if !json.is_array() {
+        return events;
+    }
+
+    let json = json.as_array().unwrap();
+
+    for event in json {
+        if !event.is_object() {
+            continue;
+        }
+
+        let event = serde_json::from_value::<Event>(event.clone());
+
+        if event.is_err() {
+            continue;
+        }
+
+        events.push(event.unwrap());
+    }
+
+    events
+}
+
+#[cfg(feature = "net")]
+/// Extract events from a websocket message
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::events::extract_events_ws;
+/// use tungstenite::Message;
+///
+/// let txt = "[\"EVENT\",\"deb0ab5bd829d1642c926b7897b078d027ca41870d0a499c1fd76e4b5af5ccbd\",{\"id\":\"f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4\",\"kind\":1,\"pubkey\":\"884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6\",\"created_at\":1673131597,\"content\":\"Does anyone know a good crate rust to handle a Lightning node?\",\"tags\":[],\"sig\":\"53a629bae11dace9b487700cbe8e85058a3d7b6989e1e0bdd6eb4fb0201a3779742682f65ca37782c0cb93019a170e0a368bb033dfce1102df71420e24e2b784\"}]";
+///
+/// let events = extract_events_ws(&Message::Text(txt.to_string()));
+/// assert_eq!(events.len(), 1);
+/// ```
+pub fn extract_events_ws(message: &crate::Message) -> Vec<Event> {
+    if message.is_text() {
+        return extract_events(message.to_text().unwrap());
+    }
+
+    vec![]
+}
+
+/// Extract the events of an "EVENT" message with the id of the subscription they belong to
+///
+/// Unlike `extract_events`, only `["EVENT", <subscription id>, <event>]` messages are read
+/// # Example
+/// ```rust
+/// use nostr_rust::events::extract_events_with_sub;
+///
+/// let txt = "[\"EVENT\",\"deb0ab5bd829d1642c926b7897b078d027ca41870d0a499c1fd76e4b5af5ccbd\",{\"id\":\"f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4\",\"kind\":1,\"pubkey\":\"884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6\",\"created_at\":1673131597,\"content\":\"Does anyone know a good crate rust to handle a Lightning node?\",\"tags\":[],\"sig\":\"53a629bae11dace9b487700cbe8e85058a3d7b6989e1e0bdd6eb4fb0201a3779742682f65ca37782c0cb93019a170e0a368bb033dfce1102df71420e24e2b784\"}]";
+///
+/// let events = extract_events_with_sub(txt);
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].0, "deb0ab5bd829d1642c926b7897b078d027ca41870d0a499c1fd76e4b5af5ccbd");
+/// assert_eq!(events[0].1.kind, 1);
+///
+/// assert!(extract_events_with_sub("[\"EOSE\",\"deb0ab5bd829d1642c926b7897b078d027ca41870d0a499c1fd76e4b5af5ccbd\"]").is_empty());
+/// ```
+pub fn extract_events_with_sub(message: &str) -> Vec<(String, Event)> {
+    let json = match serde_json::from_str::<serde_json::Value>(message) {
+        Ok(json) => json,
+        Err(_) => return vec![],
+    };
+
+    match json.as_array().map(Vec::as_slice) {
+        Some([kind, subscription_id, event]) if kind == "EVENT" => {
+            match (
+                subscription_id.as_str(),
+                serde_json::from_value::<Event>(event.clone()),
+            ) {
+                (Some(subscription_id), Ok(event)) => vec![(subscription_id.to_string(), event)],
+                _ => vec![],
+            }
+        }
+        _ => vec![],
+    }
+}
+
+#[cfg(feature = "net")]
+/// Extract the events of an "EVENT" websocket message with the id of the subscription they belong to
+///
+/// See `extract_events_with_sub`
+pub fn extract_events_with_sub_ws(message: &crate::Message) -> Vec<(String, Event)> {
+    if message.is_text() {
+        return extract_events_with_sub(message.to_text().unwrap());
     }
 
     vec![]
 }
+
+/// Keep only the newest version of each replaceable event
+///
+/// Replaceable events (kinds 0, 3 and 10000 to 19999) are identified by their author and kind,
+/// parameterized replaceable events (kinds 30000 to 39999) by their author, kind and "d" tag.
+/// When two versions have the same `created_at`, the one with the lowest id is kept.
+/// The other events are kept untouched, in their original order.
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::events::{latest_replaceable, Event};
+///
+/// let article = |id: &str, created_at: u64, d: &str| Event {
+///   id: id.to_string(),
+///   pub_key: "pubkey".to_string(),
+///   created_at,
+///   kind: 30023,
+///   tags: vec![vec!["d".to_string(), d.to_string()]],
+///   content: String::new(),
+///   sig: String::new(),
+/// };
+///
+/// let events = latest_replaceable(vec![
+///   article("a1", 1, "first-article"),
+///   article("a2", 2, "first-article"),
+///   article("b2", 5, "second-article"),
+///   article("b1", 5, "second-article"),
+///   Event { kind: 1, ..article("note", 0, "") },
+/// ]);
+///
+/// let ids: Vec<&str> = events.iter().map(|event| event.id.as_str()).collect();
+/// assert_eq!(ids, vec!["a2", "b1", "note"]);
+/// ```
+pub fn latest_replaceable(events: Vec<Event>) -> Vec<Event> {
+    let mut latest: HashMap<(&str, u16, &str), usize> = HashMap::new();
+
+    for (index, event) in events.iter().enumerate() {
+        let key = match replaceable_key(event) {
+            Some(key) => key,
+            None => continue,
+        };
+
+        let newer = match latest.get(&key) {
+            Some(&kept) => {
+                let kept = &events[kept];
+                (event.created_at, std::cmp::Reverse(&event.id))
+                    > (kept.created_at, std::cmp::Reverse(&kept.id))
+            }
+            None => true,
+        };
+
+        if newer {
+            latest.insert(key, index);
+        }
+    }
+
+    let kept: HashSet<usize> = latest.into_values().collect();
+
+    events
+        .into_iter()
+        .enumerate()
+        .filter(|(index, event)| replaceable_key(event).is_none() || kept.contains(index))
+        .map(|(_, event)| event)
+        .collect()
+}
+
+/// Identifier of a replaceable event: author, kind and "d" tag (empty for non parameterized events)
+fn replaceable_key(event: &Event) -> Option<(&str, u16, &str)> {
+    match KindClass::of(event.kind) {
+        KindClass::Replaceable => Some((&event.pub_key, event.kind, "")),
+        KindClass::Addressable => Some((
+            &event.pub_key,
+            event.kind,
+            event.first_tag_value("d").unwrap_or(""),
+        )),
+        KindClass::Regular | KindClass::Ephemeral => None,
+    }
+}
+
+/// Category of an event kind (NIP-01), telling how the events are stored by the relays
+/// # Example
+/// ```rust
+/// use nostr_rust::events::KindClass;
+///
+/// assert_eq!(KindClass::of(1), KindClass::Regular);
+/// assert_eq!(KindClass::of(3), KindClass::Replaceable);
+/// assert_eq!(KindClass::of(10002), KindClass::Replaceable);
+/// assert_eq!(KindClass::of(22242), KindClass::Ephemeral);
+/// assert_eq!(KindClass::of(30023), KindClass::Addressable);
+/// assert_eq!(KindClass::of(40000), KindClass::Regular);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KindClass {
+    /// Every event is stored: 1, 2, 4-44, 1000-9999 and the kinds of no other class
+    Regular,
+    /// Only the latest event of an author is stored: 0, 3 and 10000-19999
+    Replaceable,
+    /// Not stored: 20000-29999
+    Ephemeral,
+    /// Only the latest event of an author per "d" tag is stored: 30000-39999
+    Addressable,
+}
+
+impl KindClass {
+    /// Get the category of a kind
+    pub fn of(kind: u16) -> Self {
+        match kind {
+            0 | 3 | 10000..=19999 => Self::Replaceable,
+            20000..=29999 => Self::Ephemeral,
+            30000..=39999 => Self::Addressable,
+            _ => Self::Regular,
+        }
+    }
+}
+
+/// Node of a reply tree, see `build_thread`
+#[derive(Debug, Clone, Default)]
+pub struct ThreadNode {
+    /// The event, None for the synthetic root
+    pub event: Option<Event>,
+    /// Direct replies, oldest first
+    pub replies: Vec<ThreadNode>,
+}
+
+/// Id of the event replied to (NIP-10), see `Event::thread_role`
+fn thread_parent(event: &Event) -> Option<&str> {
+    thread_ids(event).map(|(parent, _)| parent)
+}
+
+/// Check if one of the values is the hex or bech32 (npub, note) key
+fn references<'a>(values: impl IntoIterator<Item = &'a str>, key: &str) -> bool {
+    let key = auto_bech32_to_hex(key).unwrap_or_else(|_| key.to_string());
+
+    values
+        .into_iter()
+        .any(|value| value.eq_ignore_ascii_case(&key))
+}
+
+/// Ids of the parent and of the root of a reply (NIP-10)
+///
+/// With marked "e" tags, the parent is the "reply" one or else the "root" one. With the
+/// deprecated positional "e" tags, the tags referenced in the content (`#[index]`) are mentions,
+/// the first other tag is the root and the last one the parent.
+fn thread_ids(event: &Event) -> Option<(&str, &str)> {
+    let e_tags: Vec<(usize, &str, Option<&str>)> = event
+        .tags
+        .iter()
+        .enumerate()
+        .filter(|(_, tag)| tag_value(tag, 0) == Some("e"))
+        .filter_map(|(index, tag)| Some((index, tag_value(tag, 1)?, tag_value(tag, 3))))
+        .collect();
+    let marked = |marker: &str| {
+        e_tags
+            .iter()
+            .find(|(_, _, value)| *value == Some(marker))
+            .map(|(_, id, _)| *id)
+    };
+
+    if e_tags
+        .iter()
+        .any(|(_, _, marker)| marker.is_some_and(|marker| !marker.is_empty()))
+    {
+        let root = marked("root");
+        let parent = marked("reply").or(root)?;
+
+        Some((parent, root.unwrap_or(parent)))
+    } else {
+        let mut thread_tags = e_tags
+            .iter()
+            .filter(|(index, _, _)| !event.content.contains(&format!("#[{}]", index)))
+            .map(|(_, id, _)| *id);
+        let root = thread_tags.next()?;
+
+        Some((thread_tags.next_back().unwrap_or(root), root))
+    }
+}
+
+/// Place of an event in a thread (NIP-10), see `Event::thread_role`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThreadRole {
+    /// An event without "e" tag which other events reply to in its thread, see
+    /// `Event::thread_role_in`
+    Root,
+    /// A reply to `parent`, in the thread started by `root` (the same id for a direct reply)
+    Reply { parent: String, root: String },
+    /// The "e" tags are only mentions, the event doesn't reply to anything
+    Mention,
+    /// An event without "e" tag, no known event replies to it
+    Standalone,
+}
+
+fn sort_replies(replies: &mut [ThreadNode]) {
+    replies.sort_by_key(|node| {
+        node.event
+            .as_ref()
+            .map(|event| (event.created_at, event.id.clone()))
+    });
+}
+
+fn thread_node(
+    id: &str,
+    events: &mut HashMap<String, Event>,
+    children: &HashMap<String, Vec<String>>,
+) -> Option<ThreadNode> {
+    // Removing the event marks it as placed, so a reply cycle can't loop forever
+    let event = events.remove(id)?;
+
+    let mut replies: Vec<ThreadNode> = children
+        .get(id)
+        .into_iter()
+        .flatten()
+        .filter_map(|child| thread_node(child, events, children))
+        .collect();
+    sort_replies(&mut replies);
+
+    Some(ThreadNode {
+        event: Some(event),
+        replies,
+    })
+}
+
+/// Rebuild the reply tree of a set of events (NIP-10)
+///
+/// The returned node is a synthetic root (without event) holding the events which reply to
+/// nothing and the orphan replies, whose parent is not in the set. Duplicated events are kept once.
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::events::{build_thread, Event};
+///
+/// let event = |id: &str, created_at: u64, tags: Vec<Vec<&str>>| Event {
+///   id: id.to_string(),
+///   pub_key: String::new(),
+///   created_at,
+///   kind: 1,
+///   tags: tags.into_iter().map(|tag| tag.into_iter().map(str::to_string).collect()).collect(),
+///   content: String::new(),
+///   sig: String::new(),
+/// };
+///
+/// let thread = build_thread(vec![
+///   event("root", 0, vec![]),
+///   event("a", 1, vec![vec!["e", "root", "", "root"]]),
+///   event("b", 2, vec![vec!["e", "root", "", "root"], vec!["e", "a", "", "reply"]]),
+///   // Deprecated positional tags, the last one is the parent
+///   event("c", 3, vec![vec!["e", "root"], vec!["e", "a"]]),
+///   event("orphan", 4, vec![vec!["e", "missing", "", "reply"]]),
+/// ]);
+///
+/// assert!(thread.event.is_none());
+/// assert_eq!(thread.replies.len(), 2);
+///
+/// let root = &thread.replies[0];
+/// assert_eq!(root.event.as_ref().unwrap().id, "root");
+/// let a = &root.replies[0];
+/// let a_replies: Vec<&str> = a.replies.iter().map(|node| node.event.as_ref().unwrap().id.as_str()).collect();
+/// assert_eq!(a_replies, vec!["b", "c"]);
+///
+/// assert_eq!(thread.replies[1].event.as_ref().unwrap().id, "orphan");
+/// ```
+pub fn build_thread(events: Vec<Event>) -> ThreadNode {
+    let mut events_by_id: HashMap<String, Event> = HashMap::new();
+    for event in events {
+        events_by_id.entry(event.id.clone()).or_insert(event);
+    }
+
+    let mut children: HashMap<String, Vec<String>> = HashMap::new();
+    let mut top_level: Vec<String> = Vec::new();
+    for (id, event) in &events_by_id {
+        match thread_parent(event)
+            .filter(|parent| *parent != id && events_by_id.contains_key(*parent))
+        {
+            Some(parent) => children
+                .entry(parent.to_string())
+                .or_default()
+                .push(id.clone()),
+            None => top_level.push(id.clone()),
+        }
+    }
+
+    let mut root = ThreadNode::default();
+    for id in top_level {
+        root.replies
+            .extend(thread_node(&id, &mut events_by_id, &children));
+    }
+
+    // The events left are in a reply cycle, none of them is reachable from a top level event
+    while let Some(id) = events_by_id.keys().next().cloned() {
+        root.replies
+            .extend(thread_node(&id, &mut events_by_id, &children));
+    }
+
+    sort_replies(&mut root.replies);
+    root
+}
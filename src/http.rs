@@ -0,0 +1,105 @@
+use crate::DEFAULT_USER_AGENT;
+use std::time::Duration;
+
+/// Timeout of each attempt of an HTTP request, see `HttpConfig`
+pub const DEFAULT_HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Settings of the HTTP requests: the NIP-05 verifications and the NIP-11 relay information
+/// documents
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{http::{HttpConfig, DEFAULT_HTTP_TIMEOUT}, DEFAULT_USER_AGENT};
+/// use std::time::Duration;
+///
+/// let config = HttpConfig::default();
+/// assert_eq!(config.timeout, DEFAULT_HTTP_TIMEOUT);
+/// assert_eq!(config.retries, 1);
+/// assert_eq!(config.user_agent, DEFAULT_USER_AGENT);
+///
+/// let config = HttpConfig { timeout: Duration::from_secs(3), retries: 3, ..Default::default() };
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpConfig {
+    /// Timeout of each attempt
+    pub timeout: Duration,
+    /// Number of times a request is sent again after a connection error or a timeout
+    pub retries: u32,
+    /// User-Agent header, some domains block the requests without one
+    pub user_agent: String,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_HTTP_TIMEOUT,
+            retries: 1,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+        }
+    }
+}
+
+/// Errors after which a request is worth sending again
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
+}
+
+impl HttpConfig {
+    #[cfg(not(feature = "async"))]
+    /// HTTP client applying the timeout and the User-Agent
+    pub(crate) fn client(&self) -> reqwest::Result<reqwest::blocking::Client> {
+        reqwest::blocking::Client::builder()
+            .timeout(self.timeout)
+            .user_agent(&self.user_agent)
+            .build()
+    }
+
+    #[cfg(feature = "async")]
+    /// HTTP client applying the timeout and the User-Agent
+    pub(crate) fn client(&self) -> reqwest::Result<reqwest::Client> {
+        reqwest::Client::builder()
+            .timeout(self.timeout)
+            .user_agent(&self.user_agent)
+            .build()
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Send a request, again up to `retries` times after a connection error or a timeout
+    pub(crate) fn send(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+    ) -> reqwest::Result<reqwest::blocking::Response> {
+        for _ in 0..self.retries {
+            let Some(attempt) = request.try_clone() else {
+                break;
+            };
+
+            match attempt.send() {
+                Err(error) if is_transient(&error) => continue,
+                result => return result,
+            }
+        }
+
+        request.send()
+    }
+
+    #[cfg(feature = "async")]
+    /// Send a request, again up to `retries` times after a connection error or a timeout
+    pub(crate) async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> reqwest::Result<reqwest::Response> {
+        for _ in 0..self.retries {
+            let Some(attempt) = request.try_clone() else {
+                break;
+            };
+
+            match attempt.send().await {
+                Err(error) if is_transient(&error) => continue,
+                result => return result,
+            }
+        }
+
+        request.send().await
+    }
+}
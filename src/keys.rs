@@ -1,5 +1,28 @@
+use crate::bech32::{from_hb_to_hex, Bech32Error, ToBech32Kind};
 use rand::rngs::OsRng;
-use secp256k1::{PublicKey, SecretKey, SECP256K1};
+use rand::{CryptoRng, RngCore};
+use secp256k1::schnorr::Signature;
+use secp256k1::{PublicKey, SecretKey, XOnlyPublicKey, SECP256K1};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum KeyError {
+    #[error("Bech32 Error: {}", _0)]
+    Bech32Error(#[from] Bech32Error),
+
+    #[error("Invalid public key length: {} characters, 64 expected", _0)]
+    InvalidLength(usize),
+
+    #[error("Invalid parity byte \"{}\", 02 or 03 expected", _0)]
+    InvalidParity(String),
+
+    #[error("Invalid hex public key")]
+    InvalidHex,
+
+    #[error("The public key is not a point of the curve")]
+    NotOnCurve,
+}
 
 /// Get a random secret key
 /// # Example
@@ -8,7 +31,26 @@ use secp256k1::{PublicKey, SecretKey, SECP256K1};
 /// let (secret_key, public_key) = get_random_secret_key();
 /// ```
 pub fn get_random_secret_key() -> (SecretKey, PublicKey) {
-    SECP256K1.generate_keypair(&mut OsRng)
+    get_secret_key_with_rng(&mut OsRng)
+}
+
+/// Get a secret key generated with the given random number generator
+///
+/// Only a cryptographically secure generator must be used for real keys, a seeded one gives
+/// deterministic keys for tests
+/// # Example
+/// ```rust
+/// use nostr_rust::keys::get_secret_key_with_rng;
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let (secret_key, public_key) = get_secret_key_with_rng(&mut StdRng::seed_from_u64(42));
+/// let (same_secret_key, same_public_key) = get_secret_key_with_rng(&mut StdRng::seed_from_u64(42));
+///
+/// assert_eq!(secret_key, same_secret_key);
+/// assert_eq!(public_key, same_public_key);
+/// ```
+pub fn get_secret_key_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> (SecretKey, PublicKey) {
+    SECP256K1.generate_keypair(rng)
 }
 
 /// Get a secret key from a hex string
@@ -64,3 +106,83 @@ pub fn get_str_keys_from_secret(secret_key: &SecretKey) -> (String, String) {
 pub fn normalize_public_key(public_key: &str) -> String {
     public_key.to_string()[2..].to_string()
 }
+
+/// Verify a signature made by `Identity::sign_message`: a hex-encoded BIP-340 schnorr signature
+/// over the sha256 of `msg`, by the hex-encoded x-only `public_key`
+///
+/// An invalid public key or signature is reported as a failed verification
+/// # Example
+/// ```rust
+/// use nostr_rust::{keys::verify_message, Identity};
+/// use std::str::FromStr;
+///
+/// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+/// let signature = identity.sign_message(b"hello");
+///
+/// assert!(verify_message(&identity.public_key_str, b"hello", &signature));
+/// // Public key of the secret key 00..02
+/// assert!(!verify_message("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5", b"hello", &signature));
+/// assert!(!verify_message(&identity.public_key_str, b"goodbye", &signature));
+/// assert!(!verify_message(&identity.public_key_str, b"hello", "not a signature"));
+/// ```
+pub fn verify_message(public_key: &str, msg: &[u8], signature: &str) -> bool {
+    let (Ok(public_key), Ok(signature)) = (
+        XOnlyPublicKey::from_str(public_key),
+        Signature::from_str(signature),
+    ) else {
+        return false;
+    };
+    let message = secp256k1::Message::from_hashed_data::<secp256k1::hashes::sha256::Hash>(msg);
+
+    SECP256K1
+        .verify_schnorr(&signature, &message, &public_key)
+        .is_ok()
+}
+
+/// Validate a public key given as hex or `npub` and return it as an x-only key
+///
+/// 66 characters compressed keys are accepted when their parity byte is 02 or 03, the parity is
+/// then dropped
+/// # Example
+/// ```rust
+/// use nostr_rust::keys::{validate_pubkey, KeyError};
+///
+/// let hex = "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d";
+/// let npub = "npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6";
+///
+/// assert_eq!(validate_pubkey(hex).unwrap().to_string(), hex);
+/// assert_eq!(validate_pubkey(npub).unwrap().to_string(), hex);
+/// assert_eq!(validate_pubkey(&format!("02{}", hex)).unwrap().to_string(), hex);
+///
+/// assert_eq!(validate_pubkey("3bf0c6"), Err(KeyError::InvalidLength(6)));
+/// assert_eq!(validate_pubkey(&format!("04{}", hex)), Err(KeyError::InvalidParity("04".to_string())));
+/// assert_eq!(validate_pubkey(&"z".repeat(64)), Err(KeyError::InvalidHex));
+/// assert_eq!(validate_pubkey(&"f".repeat(64)), Err(KeyError::NotOnCurve));
+/// ```
+pub fn validate_pubkey(public_key: &str) -> Result<XOnlyPublicKey, KeyError> {
+    let hex_key = from_hb_to_hex(ToBech32Kind::PublicKey, public_key.trim())?;
+
+    let x_only = match hex_key.len() {
+        64 => hex_key.as_str(),
+        66 => match &hex_key[..2] {
+            "02" | "03" => &hex_key[2..],
+            parity => return Err(KeyError::InvalidParity(parity.to_string())),
+        },
+        length => return Err(KeyError::InvalidLength(length)),
+    };
+
+    let bytes = hex::decode(x_only).map_err(|_| KeyError::InvalidHex)?;
+    XOnlyPublicKey::from_slice(&bytes).map_err(|_| KeyError::NotOnCurve)
+}
+
+/// Check if a public key given as hex or `npub` is a valid x-only key, see `validate_pubkey`
+/// # Example
+/// ```rust
+/// use nostr_rust::keys::is_valid_pubkey;
+///
+/// assert!(is_valid_pubkey("npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6"));
+/// assert!(!is_valid_pubkey("nsec1"));
+/// ```
+pub fn is_valid_pubkey(public_key: &str) -> bool {
+    validate_pubkey(public_key).is_ok()
+}
@@ -3,19 +3,55 @@ use secp256k1::{PublicKey, SecretKey};
 use std::str::FromStr;
 use utils::get_timestamp;
 
+// The client is built for `sync` unless `async` is enabled, `net` alone has no HTTP client
+#[cfg(all(feature = "net", not(any(feature = "sync", feature = "async"))))]
+compile_error!("The `net` feature is internal, enable the `sync` or `async` feature instead");
+
+#[cfg(feature = "net")]
+pub mod archive;
 pub mod bech32;
+pub mod cache;
 pub mod events;
+#[cfg(feature = "net")]
+pub mod http;
 pub mod keys;
 pub mod nips;
+#[cfg(feature = "net")]
 pub mod nostr_client;
 pub mod req;
+pub mod signer;
 pub mod utils;
+#[cfg(feature = "net")]
 pub mod websocket;
 
 pub const DEFAULT_HASHTAG: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
 
+/// User-Agent of the HTTP requests (NIP-05 and NIP-11), see `http::HttpConfig`
+pub const DEFAULT_USER_AGENT: &str = concat!("nostr_rust/", env!("CARGO_PKG_VERSION"));
+
+#[cfg(feature = "net")]
 pub type Message = tungstenite::Message;
 
+/// NIPs implemented by the crate, in ascending order: the modules of `nips` and the NIPs
+/// implemented elsewhere (NIP-10 threads and NIP-40 expiration in `events`, NIP-19 in `bech32`,
+/// NIP-24 metadata in `nips::nip1`, NIP-70 protected events in `events`)
+const SUPPORTED_NIPS: &[u16] = &[
+    1, 2, 4, 5, 9, 10, 11, 13, 14, 16, 19, 21, 24, 25, 29, 30, 40, 44, 46, 52, 59, 65, 70, 72, 98,
+];
+
+/// List the NIPs implemented by the crate, in ascending order
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::supported_nips;
+///
+/// assert!(supported_nips().contains(&1));
+/// assert!(supported_nips().windows(2).all(|pair| pair[0] < pair[1]));
+/// ```
+pub fn supported_nips() -> &'static [u16] {
+    SUPPORTED_NIPS
+}
+
 /// Nostr Identity with secret and public keys
 pub struct Identity {
     pub secret_key: SecretKey,
@@ -77,6 +113,55 @@ impl Identity {
         }
         .to_event(self, difficulty_target)
     }
+
+    /// Sign an arbitrary message, for example the challenge of a login flow, and return the
+    /// hex-encoded signature
+    ///
+    /// The signature is a BIP-340 schnorr signature over the sha256 of `msg`, it can be checked
+    /// with `keys::verify_message`. It is not an event signature.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{keys::verify_message, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let signature = identity.sign_message(b"challenge-1234");
+    ///
+    /// assert_eq!(signature.len(), 128);
+    /// assert!(verify_message(&identity.public_key_str, b"challenge-1234", &signature));
+    /// assert!(!verify_message(&identity.public_key_str, b"challenge-1235", &signature));
+    /// ```
+    pub fn sign_message(&self, msg: &[u8]) -> String {
+        let message = secp256k1::Message::from_hashed_data::<secp256k1::hashes::sha256::Hash>(msg);
+
+        secp256k1::SECP256K1
+            .sign_schnorr(
+                &message,
+                &secp256k1::KeyPair::from_secret_key(secp256k1::SECP256K1, &self.secret_key),
+            )
+            .to_string()
+    }
+
+    /// Generate an identity with a random secret key, for example a throwaway key
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::Identity;
+    ///
+    /// let identity = Identity::generate();
+    /// assert_eq!(identity.public_key_str.len(), 64);
+    /// assert_ne!(identity.public_key_str, Identity::generate().public_key_str);
+    /// ```
+    pub fn generate() -> Self {
+        let (secret_key, public_key) = keys::get_random_secret_key();
+        let address = keys::get_str_keys_from_secret(&secret_key).1;
+
+        Self {
+            secret_key,
+            public_key,
+            public_key_str: address.clone(),
+            address,
+        }
+    }
 }
 
 impl FromStr for Identity {
@@ -116,3 +201,26 @@ impl FromStr for Identity {
         })
     }
 }
+
+impl std::fmt::Debug for Identity {
+    /// Format the public key and the address, the secret key is redacted to stay out of the logs
+    /// # Example
+    /// ```
+    /// use nostr_rust::Identity;
+    /// use std::str::FromStr;
+    ///
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let debug = format!("{:?}", identity);
+    ///
+    /// assert!(debug.contains(env!("PUBLIC_KEY")));
+    /// assert!(debug.contains("<redacted>"));
+    /// assert!(!debug.contains(&identity.secret_key.display_secret().to_string()));
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Identity")
+            .field("secret_key", &"<redacted>")
+            .field("public_key_str", &self.public_key_str)
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
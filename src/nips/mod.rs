@@ -1,9 +1,25 @@
+// Every module must be listed in `SUPPORTED_NIPS` (lib.rs)
+// The modules and methods talking to the relays or over HTTP require the `net` feature
 pub mod nip1;
 pub mod nip11;
 pub mod nip13;
+pub mod nip14;
+#[cfg(feature = "net")]
 pub mod nip16;
 pub mod nip2;
+pub mod nip21;
 pub mod nip25;
+pub mod nip29;
+pub mod nip30;
 pub mod nip4;
+pub mod nip44;
+#[cfg(feature = "net")]
+pub mod nip46;
+#[cfg(feature = "net")]
 pub mod nip5;
+pub mod nip52;
+pub mod nip59;
+pub mod nip65;
+pub mod nip72;
 pub mod nip9;
+pub mod nip98;
@@ -1,13 +1,23 @@
+use crate::events::Event;
+#[cfg(feature = "net")]
 use crate::{
-    events::{Event, EventPrepare},
+    bech32::auto_bech32_to_hex,
+    events::EventPrepare,
     nostr_client::{Client, ClientError},
+    req::ReqFilter,
+    signer::Signer,
     utils::get_timestamp,
-    Identity,
 };
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "net")]
 use serde_json::json;
+#[cfg(feature = "net")]
+use std::{cmp::Reverse, collections::HashSet};
 use thiserror::Error;
 
+#[cfg(feature = "net")]
 use super::nip5::NIP5Error;
+use super::nip65::{self, RelayListItem};
 
 // Implementation of the NIP1 protocol
 // https://github.com/nostr-protocol/nips/blob/master/01.md
@@ -17,28 +27,378 @@ pub enum NIP1Error {
     #[error("No metadata given")]
     NoMetadata,
 
+    #[cfg(feature = "net")]
     #[error("The client has an error")]
     ClientError(ClientError),
 
+    #[cfg(feature = "net")]
     #[error("NIP05 error")]
     NIP05Error(NIP5Error),
 
     #[error("Given NIP05 is invalid with the given pubkey")]
     BadNIP05,
+
+    #[error("Bech32 Error: {}", _0)]
+    Bech32Error(#[from] crate::bech32::Bech32Error),
+
+    #[error("Serde Error: {}", _0)]
+    SerdeError(#[from] serde_json::Error),
+}
+
+/// Metadata of a user (content of a kind 0 event), with the extra fields of NIP-24
+///
+/// The unknown and deprecated fields are kept in `extra`, so they are published again unchanged
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip1::Metadata;
+///
+/// let metadata: Metadata = serde_json::from_str(r#"{"name":"bot","bot":true,"lud16":"bot@example.com"}"#).unwrap();
+/// assert!(metadata.is_bot());
+/// assert_eq!(metadata.display_name, None);
+/// assert_eq!(metadata.extra["lud16"], "bot@example.com");
+///
+/// let content = serde_json::to_string(&metadata).unwrap();
+/// assert_eq!(content, r#"{"name":"bot","bot":true,"lud16":"bot@example.com"}"#);
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Metadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub about: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub picture: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nip05: Option<String>,
+    /// Richer name than `name`, which should be used when `display_name` is not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Web URL related to the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub website: Option<String>,
+    /// URL of a wide (~1024x768) picture displayed in the background of the profile
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner: Option<String>,
+    /// Whether the content is entirely or partially the result of an automation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bot: Option<bool>,
+    /// Other fields, kept as they are
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Metadata {
+    /// Check if the user is flagged as an automated account (NIP-24 `bot` field)
+    pub fn is_bot(&self) -> bool {
+        self.bot == Some(true)
+    }
+}
+
+/// Everything known about a public key: metadata, NIP05 verification and relay list
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// 32-bytes hex public key
+    pub pubkey: String,
+    /// Latest metadata, None if not found or invalid
+    pub metadata: Option<Metadata>,
+    /// None if the metadata has no nip05, otherwise the result of the verification
+    pub nip05_verified: Option<bool>,
+    /// Relays of the latest relay list metadata event (NIP65)
+    pub relays: Vec<RelayListItem>,
+}
+
+impl Profile {
+    /// Build a profile from the kind 0 and kind 10002 events of a public key, the newest event of each kind is used
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::Event, nips::nip1::Profile};
+    ///
+    /// let metadata = Event {
+    ///   id: String::new(),
+    ///   pub_key: "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///   created_at: 10,
+    ///   kind: 0,
+    ///   tags: vec![],
+    ///   content: "{\"name\":\"Thomas\"}".to_string(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// let profile = Profile::from_events("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", &[metadata]);
+    /// assert_eq!(profile.metadata.unwrap().name, Some("Thomas".to_string()));
+    /// assert_eq!(profile.nip05_verified, None);
+    /// assert!(profile.relays.is_empty());
+    /// ```
+    pub fn from_events(pubkey: &str, events: &[Event]) -> Self {
+        let latest_of_kind = |kind: u16| {
+            events
+                .iter()
+                .filter(|event| event.kind == kind && event.pub_key == pubkey)
+                .max_by_key(|event| event.created_at)
+        };
+
+        Self {
+            pubkey: pubkey.to_string(),
+            metadata: latest_of_kind(0)
+                .and_then(|event| serde_json::from_str::<Metadata>(&event.content).ok()),
+            nip05_verified: None,
+            relays: latest_of_kind(nip65::RELAY_LIST_KIND)
+                .map(nip65::parse_relay_list)
+                .unwrap_or_default(),
+        }
+    }
 }
 
+#[cfg(feature = "net")]
+/// Number of events requested per page by `Client::fetch_all_notes`
+const NOTES_PAGE_SIZE: usize = 100;
+
+#[cfg(feature = "net")]
+/// Pagination of the history of a public key, see `Client::fetch_all_notes`
+///
+/// Every relay is queried for each page (`Client::get_events_of_per_relay`) with an inclusive
+/// `until`. A relay returning a full page may have more events in the second of its oldest
+/// event, that second is then requested without limit before going further back in time
+pub(crate) struct NotesPager {
+    hex_pubkey: String,
+    kinds: Option<Vec<u16>>,
+    /// Maximum number of events left to return
+    remaining: usize,
+    until: Option<u64>,
+    /// Second requested without limit by the next page
+    boundary: Option<u64>,
+    seen: HashSet<String>,
+    done: bool,
+}
+
+#[cfg(feature = "net")]
+impl NotesPager {
+    pub(crate) fn new(hex_pubkey: String, kinds: &[u16], max: usize) -> Self {
+        Self {
+            hex_pubkey,
+            kinds: (!kinds.is_empty()).then(|| kinds.to_vec()),
+            remaining: max,
+            until: None,
+            boundary: None,
+            seen: HashSet::new(),
+            done: max == 0,
+        }
+    }
+
+    fn page_limit(&self) -> usize {
+        NOTES_PAGE_SIZE.min(self.remaining)
+    }
+
+    /// Filter of the next page, None when the history is exhausted
+    pub(crate) fn next_filter(&self) -> Option<ReqFilter> {
+        if self.done {
+            return None;
+        }
+
+        let mut filter = ReqFilter {
+            authors: Some(vec![self.hex_pubkey.clone()]),
+            kinds: self.kinds.clone(),
+            ..Default::default()
+        };
+
+        match self.boundary {
+            Some(second) => {
+                filter.since = Some(second);
+                filter.until = Some(second);
+            }
+            None => {
+                filter.until = self.until;
+                filter.limit = Some(self.page_limit() as u64);
+            }
+        }
+
+        Some(filter)
+    }
+
+    /// Add the events sent by each relay for the page of `next_filter`, return the events not
+    /// seen before, newest first
+    pub(crate) fn add_page(&mut self, pages: Vec<Vec<Event>>) -> Vec<Event> {
+        let (since, until) = match self.boundary {
+            Some(second) => (Some(second), Some(second)),
+            None => (None, self.until),
+        };
+        let limit = self.page_limit();
+
+        // Oldest second of each full page, the events of the previous seconds may be missing
+        let mut full_page_oldest: Option<u64> = None;
+        let mut ignores_filter = false;
+        let mut new_events: Vec<Event> = Vec::new();
+
+        for page in pages {
+            if self.boundary.is_none() && page.len() >= limit {
+                let oldest = page.iter().map(|event| event.created_at).min();
+                full_page_oldest = full_page_oldest.max(oldest);
+            }
+
+            for event in page {
+                if since.is_some_and(|since| event.created_at < since)
+                    || until.is_some_and(|until| event.created_at > until)
+                {
+                    ignores_filter = true;
+                    continue;
+                }
+
+                if self.seen.insert(event.id.clone()) {
+                    new_events.push(event);
+                }
+            }
+        }
+
+        new_events.sort_by_key(|event| (Reverse(event.created_at), event.id.clone()));
+        new_events.truncate(self.remaining);
+        self.remaining -= new_events.len();
+
+        match (self.boundary.take(), full_page_oldest) {
+            // A relay ignoring `since` or `until` would send the same page forever
+            _ if ignores_filter || self.remaining == 0 => self.done = true,
+            (Some(second), _) => match second.checked_sub(1) {
+                Some(until) => self.until = Some(until),
+                None => self.done = true,
+            },
+            // Every relay sent its whole history
+            (None, None) => self.done = true,
+            // The oldest second of a full page may have more events than the limit
+            (None, Some(oldest)) if self.until == Some(oldest) => self.boundary = Some(oldest),
+            (None, Some(oldest)) => self.until = Some(oldest),
+        }
+
+        new_events
+    }
+}
+
+#[cfg(feature = "net")]
+fn profile_filters(hex_pubkey: &str) -> Vec<ReqFilter> {
+    vec![
+        ReqFilter {
+            ids: None,
+            authors: Some(vec![hex_pubkey.to_string()]),
+            kinds: Some(vec![0]),
+            e: None,
+            p: None,
+            d: None,
+            h: None,
+            since: None,
+            until: None,
+            limit: Some(1),
+        },
+        ReqFilter {
+            ids: None,
+            authors: Some(vec![hex_pubkey.to_string()]),
+            kinds: Some(vec![nip65::RELAY_LIST_KIND]),
+            e: None,
+            p: None,
+            d: None,
+            h: None,
+            since: None,
+            until: None,
+            limit: Some(1),
+        },
+    ]
+}
+
+#[cfg(feature = "net")]
+/// Metadata event (kind 0), the nip05 must have been checked
+fn metadata_event(
+    pub_key: &str,
+    name: Option<&str>,
+    about: Option<&str>,
+    picture: Option<&str>,
+    nip05: Option<&str>,
+) -> Result<EventPrepare, NIP1Error> {
+    if name.is_none() && about.is_none() && picture.is_none() {
+        return Err(NIP1Error::NoMetadata);
+    }
+
+    let mut json_body = json!({});
+
+    if let Some(name) = name {
+        json_body["name"] = json!(name);
+    }
+
+    if let Some(about) = about {
+        json_body["about"] = json!(about);
+    }
+
+    if let Some(picture) = picture {
+        json_body["picture"] = json!(picture);
+    }
+
+    if let Some(nip05) = nip05 {
+        json_body["nip05"] = json!(nip05);
+    }
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: 0,
+        tags: vec![],
+        content: json_body.to_string(),
+    })
+}
+
+#[cfg(feature = "net")]
+fn full_metadata_event(pub_key: &str, metadata: &Metadata) -> Result<EventPrepare, NIP1Error> {
+    let content = serde_json::to_string(metadata)?;
+
+    if content == "{}" {
+        return Err(NIP1Error::NoMetadata);
+    }
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: 0,
+        tags: vec![],
+        content,
+    })
+}
+
+#[cfg(feature = "net")]
+fn text_note_event(
+    pub_key: &str,
+    content: &str,
+    tags: &[Vec<String>],
+) -> Result<EventPrepare, NIP1Error> {
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: 1,
+        tags: tags.to_vec(),
+        content: content.to_string(),
+    })
+}
+
+#[cfg(feature = "net")]
+fn recommended_relay_event(pub_key: &str, relay: &str) -> Result<EventPrepare, NIP1Error> {
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: 2,
+        tags: vec![],
+        content: relay.to_string(),
+    })
+}
+
+#[cfg(feature = "net")]
 impl From<ClientError> for NIP1Error {
     fn from(err: ClientError) -> Self {
         Self::ClientError(err)
     }
 }
 
+#[cfg(feature = "net")]
 impl From<NIP5Error> for NIP1Error {
     fn from(err: NIP5Error) -> Self {
         Self::NIP05Error(err)
     }
 }
 
+#[cfg(feature = "net")]
 impl Client {
     #[cfg(not(feature = "async"))]
     /// Set the metadata of the identity
@@ -55,53 +415,35 @@ impl Client {
     /// ```
     pub fn set_metadata(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         name: Option<&str>,
         about: Option<&str>,
         picture: Option<&str>,
         nip05: Option<&str>,
         difficulty_target: u16,
     ) -> Result<Event, NIP1Error> {
-        let mut json_body = json!({});
-
         if name.is_none() && about.is_none() && picture.is_none() {
             return Err(NIP1Error::NoMetadata);
         }
 
-        if let Some(name) = name {
-            json_body["name"] = json!(name);
-        }
-
-        if let Some(about) = about {
-            json_body["about"] = json!(about);
-        }
-
-        if let Some(picture) = picture {
-            json_body["picture"] = json!(picture);
-        }
-
         if let Some(nip05) = nip05 {
             // Check if the nip05 is valid
-            let validity = crate::nips::nip5::check_validity(nip05, &identity.public_key_str)?;
+            let validity = crate::nips::nip5::check_validity_with_config(
+                nip05,
+                &signer.public_key(),
+                self.http_config(),
+            )?;
 
             if !validity {
                 return Err(NIP1Error::BadNIP05);
             }
-
-            json_body["nip05"] = json!(nip05);
         }
 
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 0,
-            tags: vec![],
-            content: json_body.to_string(),
-        }
-        .to_event(identity, difficulty_target);
-
-        self.publish_event(&event)?;
-        Ok(event)
+        self.send_template(
+            signer,
+            |pub_key: &str| metadata_event(pub_key, name, about, picture, nip05),
+            difficulty_target,
+        )
     }
 
     #[cfg(feature = "async")]
@@ -121,41 +463,113 @@ impl Client {
     /// ```
     pub async fn set_metadata(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         name: Option<&str>,
         about: Option<&str>,
         picture: Option<&str>,
         difficulty_target: u16,
     ) -> Result<Event, NIP1Error> {
-        let mut json_body = json!({});
+        self.send_template(
+            signer,
+            |pub_key: &str| metadata_event(pub_key, name, about, picture, None),
+            difficulty_target,
+        )
+        .await
+    }
 
-        if name.is_none() && about.is_none() && picture.is_none() {
-            return Err(NIP1Error::NoMetadata);
-        }
+    #[cfg(not(feature = "async"))]
+    /// Publish every field of the metadata (NIP-24 fields and unknown fields included)
+    ///
+    /// The nip05 is verified first. To update the current metadata without losing fields, get it
+    /// with `Client::fetch_profile`, change it and publish it again.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nips::nip1::Metadata, nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let metadata = Metadata {
+    ///     name: Some("rust_nostr_client".to_string()),
+    ///     display_name: Some("Rust Nostr Client".to_string()),
+    ///     bot: Some(true),
+    ///     ..Default::default()
+    /// };
+    /// client.publish_metadata(&identity, &metadata, 0).unwrap();
+    /// ```
+    pub fn publish_metadata(
+        &mut self,
+        signer: &dyn Signer,
+        metadata: &Metadata,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP1Error> {
+        if let Some(nip05) = &metadata.nip05 {
+            let validity = crate::nips::nip5::check_validity_with_config(
+                nip05,
+                &signer.public_key(),
+                self.http_config(),
+            )?;
 
-        if let Some(name) = name {
-            json_body["name"] = json!(name);
+            if !validity {
+                return Err(NIP1Error::BadNIP05);
+            }
         }
 
-        if let Some(about) = about {
-            json_body["about"] = json!(about);
-        }
+        self.send_template(
+            signer,
+            |pub_key: &str| full_metadata_event(pub_key, metadata),
+            difficulty_target,
+        )
+    }
 
-        if let Some(picture) = picture {
-            json_body["picture"] = json!(picture);
-        }
+    #[cfg(feature = "async")]
+    /// Publish every field of the metadata (NIP-24 fields and unknown fields included)
+    ///
+    /// The nip05 is verified first. To update the current metadata without losing fields, get it
+    /// with `Client::fetch_profile`, change it and publish it again.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nips::nip1::Metadata, nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_publish_metadata() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     let metadata = Metadata {
+    ///         name: Some("rust_nostr_client".to_string()),
+    ///         display_name: Some("Rust Nostr Client".to_string()),
+    ///         bot: Some(true),
+    ///         ..Default::default()
+    ///     };
+    ///     client.publish_metadata(&identity, &metadata, 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn publish_metadata(
+        &mut self,
+        signer: &dyn Signer,
+        metadata: &Metadata,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP1Error> {
+        if let Some(nip05) = &metadata.nip05 {
+            let validity = crate::nips::nip5::check_validity_with_config(
+                nip05,
+                &signer.public_key(),
+                self.http_config(),
+            )
+            .await?;
 
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 0,
-            tags: vec![],
-            content: json_body.to_string(),
+            if !validity {
+                return Err(NIP1Error::BadNIP05);
+            }
         }
-        .to_event(identity, difficulty_target);
 
-        self.publish_event(&event).await?;
-        Ok(event)
+        self.send_template(
+            signer,
+            |pub_key: &str| full_metadata_event(pub_key, metadata),
+            difficulty_target,
+        )
+        .await
     }
 
     #[cfg(not(feature = "async"))]
@@ -171,22 +585,16 @@ impl Client {
     /// ```
     pub fn publish_text_note(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         content: &str,
         tags: &[Vec<String>],
         difficulty_target: u16,
     ) -> Result<Event, NIP1Error> {
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 1,
-            tags: tags.to_vec(),
-            content: content.to_string(),
-        }
-        .to_event(identity, difficulty_target);
-
-        self.publish_event(&event)?;
-        Ok(event)
+        self.send_template(
+            signer,
+            |pub_key: &str| text_note_event(pub_key, content, tags),
+            difficulty_target,
+        )
     }
 
     #[cfg(feature = "async")]
@@ -251,22 +659,17 @@ impl Client {
     /// ```
     pub async fn publish_text_note(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         content: &str,
         tags: &[Vec<String>],
         difficulty_target: u16,
     ) -> Result<Event, NIP1Error> {
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 1,
-            tags: tags.to_vec(),
-            content: content.to_string(),
-        }
-        .to_event(identity, difficulty_target);
-
-        self.publish_event(&event).await?;
-        Ok(event)
+        self.send_template(
+            signer,
+            |pub_key: &str| text_note_event(pub_key, content, tags),
+            difficulty_target,
+        )
+        .await
     }
 
     #[cfg(not(feature = "async"))]
@@ -283,21 +686,15 @@ impl Client {
     /// ```
     pub fn add_recommended_relay(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         relay: &str,
         difficulty_target: u16,
     ) -> Result<Event, NIP1Error> {
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 2,
-            tags: vec![],
-            content: relay.to_string(),
-        }
-        .to_event(identity, difficulty_target);
-
-        self.publish_event(&event)?;
-        Ok(event)
+        self.send_template(
+            signer,
+            |pub_key: &str| recommended_relay_event(pub_key, relay),
+            difficulty_target,
+        )
     }
 
     #[cfg(feature = "async")]
@@ -316,20 +713,162 @@ impl Client {
     /// ```
     pub async fn add_recommended_relay(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         relay: &str,
         difficulty_target: u16,
     ) -> Result<Event, NIP1Error> {
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 2,
-            tags: vec![],
-            content: relay.to_string(),
+        self.send_template(
+            signer,
+            |pub_key: &str| recommended_relay_event(pub_key, relay),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Fetch the profile of a public key: metadata, NIP05 verification and relay list
+    ///
+    /// The NIP05 verification is best-effort, a failed request is reported as not verified
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let profile = client.fetch_profile("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").unwrap();
+    /// ```
+    pub fn fetch_profile(&mut self, pubkey: &str) -> Result<Profile, NIP1Error> {
+        let hex_pubkey = auto_bech32_to_hex(pubkey)?;
+        let events = self.get_events_of(profile_filters(&hex_pubkey))?;
+
+        let mut profile = Profile::from_events(&hex_pubkey, &events);
+
+        if let Some(nip05) = profile.metadata.as_ref().and_then(|m| m.nip05.clone()) {
+            profile.nip05_verified = Some(
+                crate::nips::nip5::check_validity_with_config(
+                    &nip05,
+                    &hex_pubkey,
+                    self.http_config(),
+                )
+                .unwrap_or(false),
+            );
+        }
+
+        Ok(profile)
+    }
+
+    #[cfg(feature = "async")]
+    /// Fetch the profile of a public key: metadata, NIP05 verification and relay list
+    ///
+    /// The NIP05 verification is best-effort, a failed request is reported as not verified
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// #[tokio::test]
+    /// async fn test_fetch_profile() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let profile = client.fetch_profile("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").await.unwrap();
+    /// }
+    /// ```
+    pub async fn fetch_profile(&mut self, pubkey: &str) -> Result<Profile, NIP1Error> {
+        let hex_pubkey = auto_bech32_to_hex(pubkey)?;
+        let events = self.get_events_of(profile_filters(&hex_pubkey)).await?;
+
+        let mut profile = Profile::from_events(&hex_pubkey, &events);
+
+        if let Some(nip05) = profile.metadata.as_ref().and_then(|m| m.nip05.clone()) {
+            profile.nip05_verified = Some(
+                crate::nips::nip5::check_validity_with_config(
+                    &nip05,
+                    &hex_pubkey,
+                    self.http_config(),
+                )
+                .await
+                .unwrap_or(false),
+            );
+        }
+
+        Ok(profile)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Fetch the events of a public key, of the given kinds (every kind if empty), newest first
+    ///
+    /// The history is requested page by page until it's exhausted or until `max` events are fetched.
+    /// Every relay is waited for on each page (see `Client::get_events_of_per_relay`), a relay
+    /// which fails is skipped for that page
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let notes = client.fetch_all_notes("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", &[1], 1000).unwrap();
+    /// ```
+    pub fn fetch_all_notes(
+        &mut self,
+        pubkey: &str,
+        kinds: &[u16],
+        max: usize,
+    ) -> Result<Vec<Event>, NIP1Error> {
+        let mut pager = NotesPager::new(auto_bech32_to_hex(pubkey)?, kinds, max);
+        let mut events: Vec<Event> = Vec::new();
+
+        while let Some(filter) = pager.next_filter() {
+            let events_per_relay = self.get_events_of_per_relay(vec![filter])?;
+
+            // The failing relays are skipped, the others still cover the history
+            events.extend(
+                pager.add_page(
+                    events_per_relay
+                        .into_values()
+                        .filter_map(Result::ok)
+                        .collect(),
+                ),
+            );
+        }
+
+        events.sort_by_key(|event| (Reverse(event.created_at), event.id.clone()));
+        Ok(events)
+    }
+
+    #[cfg(feature = "async")]
+    /// Fetch the events of a public key, of the given kinds (every kind if empty), newest first
+    ///
+    /// The history is requested page by page until it's exhausted or until `max` events are fetched.
+    /// Every relay is waited for on each page (see `Client::get_events_of_per_relay`), a relay
+    /// which fails is skipped for that page
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// #[tokio::test]
+    /// async fn test_fetch_all_notes() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let notes = client.fetch_all_notes("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", &[1], 1000).await.unwrap();
+    /// }
+    /// ```
+    pub async fn fetch_all_notes(
+        &mut self,
+        pubkey: &str,
+        kinds: &[u16],
+        max: usize,
+    ) -> Result<Vec<Event>, NIP1Error> {
+        let mut pager = NotesPager::new(auto_bech32_to_hex(pubkey)?, kinds, max);
+        let mut events: Vec<Event> = Vec::new();
+
+        while let Some(filter) = pager.next_filter() {
+            let events_per_relay = self.get_events_of_per_relay(vec![filter]).await?;
+
+            // The failing relays are skipped, the others still cover the history
+            events.extend(
+                pager.add_page(
+                    events_per_relay
+                        .into_values()
+                        .filter_map(Result::ok)
+                        .collect(),
+                ),
+            );
         }
-        .to_event(identity, difficulty_target);
 
-        self.publish_event(&event).await?;
-        Ok(event)
+        events.sort_by_key(|event| (Reverse(event.created_at), event.id.clone()));
+        Ok(events)
     }
 }
@@ -1,3 +1,7 @@
+#[cfg(feature = "net")]
+use crate::{http::HttpConfig, nostr_client::Client};
+#[cfg(feature = "net")]
+use reqwest::{header, StatusCode};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -14,6 +18,20 @@ pub struct RelayInformationDocument {
     pub supported_nips: Option<Vec<u16>>,
     pub software: Option<String>,
     pub version: Option<String>,
+    pub limitation: Option<RelayLimitation>,
+}
+
+/// Limits of the relay, missing values mean no limit
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RelayLimitation {
+    /// Maximum number of open subscriptions per connection
+    pub max_subscriptions: Option<usize>,
+    /// Maximum number of filters of a subscription
+    pub max_filters: Option<usize>,
+    /// Maximum `limit` of a filter
+    pub max_limit: Option<u64>,
+    pub auth_required: Option<bool>,
+    pub payment_required: Option<bool>,
 }
 
 #[derive(Error, Debug, Eq, PartialEq)]
@@ -25,43 +43,198 @@ pub enum NIP11Error {
     RelayInformationDocumentNotAccessible,
 }
 
-#[cfg(not(feature = "async"))]
+/// Statuses after which the document is requested again without the NIP-11 Accept header
+#[cfg(feature = "net")]
+fn should_retry_without_accept(status: StatusCode) -> bool {
+    status == StatusCode::NOT_FOUND || status == StatusCode::NOT_ACCEPTABLE
+}
+
+#[cfg(all(feature = "net", not(feature = "async")))]
+/// Get the relay information document
+///
+/// The document is requested with the `Accept: application/nostr+json` header, then without it
+/// if the relay answers 404 or 406. Gzip encoded documents are supported. The default
+/// `HttpConfig` is used, see `get_relay_information_document_with_config` to change it.
 pub fn get_relay_information_document(
     relay_url: &str,
+) -> Result<RelayInformationDocument, NIP11Error> {
+    get_relay_information_document_with_config(relay_url, &HttpConfig::default())
+}
+
+#[cfg(all(feature = "net", not(feature = "async")))]
+/// Get the relay information document with the given timeout, retries and User-Agent
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{http::HttpConfig, nips::nip11::get_relay_information_document_with_config};
+/// use std::time::Duration;
+///
+/// let config = HttpConfig { timeout: Duration::from_secs(5), retries: 2, ..Default::default() };
+/// let document = get_relay_information_document_with_config(env!("RELAY_URL"), &config).unwrap();
+/// ```
+pub fn get_relay_information_document_with_config(
+    relay_url: &str,
+    config: &HttpConfig,
 ) -> Result<RelayInformationDocument, NIP11Error> {
     let relay_url = relay_url.replacen("ws", "http", 1);
-    let relay_response: RelayInformationDocument = match reqwest::blocking::Client::new()
-        .get(relay_url)
-        .header("Accept", "application/nostr+json")
-        .send()
-    {
-        Ok(response) => match response.json() {
-            Ok(json) => json,
-            Err(_) => return Err(NIP11Error::InvalidRelayInformationDocument),
-        },
-        Err(_) => return Err(NIP11Error::RelayInformationDocumentNotAccessible),
-    };
-
-    Ok(relay_response)
+    let client = config
+        .client()
+        .map_err(|_| NIP11Error::RelayInformationDocumentNotAccessible)?;
+
+    let mut response = config
+        .send(
+            client
+                .get(&relay_url)
+                .header(header::ACCEPT, "application/nostr+json"),
+        )
+        .map_err(|_| NIP11Error::RelayInformationDocumentNotAccessible)?;
+
+    if should_retry_without_accept(response.status()) {
+        response = config
+            .send(client.get(&relay_url))
+            .map_err(|_| NIP11Error::RelayInformationDocumentNotAccessible)?;
+    }
+
+    response
+        .json()
+        .map_err(|_| NIP11Error::InvalidRelayInformationDocument)
 }
 
 #[cfg(feature = "async")]
+/// Get the relay information document
+///
+/// The document is requested with the `Accept: application/nostr+json` header, then without it
+/// if the relay answers 404 or 406. Gzip encoded documents are supported. The default
+/// `HttpConfig` is used, see `get_relay_information_document_with_config` to change it.
 pub async fn get_relay_information_document(
     relay_url: &str,
+) -> Result<RelayInformationDocument, NIP11Error> {
+    get_relay_information_document_with_config(relay_url, &HttpConfig::default()).await
+}
+
+#[cfg(feature = "async")]
+/// Get the relay information document with the given timeout, retries and User-Agent
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{http::HttpConfig, nips::nip11::get_relay_information_document_with_config};
+/// use std::time::Duration;
+///
+/// #[tokio::test]
+/// async fn test_get_relay_information_document_with_config() {
+///     let config = HttpConfig { timeout: Duration::from_secs(5), retries: 2, ..Default::default() };
+///     let document = get_relay_information_document_with_config(env!("RELAY_URL"), &config).await.unwrap();
+/// }
+/// ```
+pub async fn get_relay_information_document_with_config(
+    relay_url: &str,
+    config: &HttpConfig,
 ) -> Result<RelayInformationDocument, NIP11Error> {
     let relay_url = relay_url.replacen("ws", "http", 1);
-    let relay_response: RelayInformationDocument = match reqwest::Client::new()
-        .get(relay_url)
-        .header("Accept", "application/nostr+json")
-        .send()
+    let client = config
+        .client()
+        .map_err(|_| NIP11Error::RelayInformationDocumentNotAccessible)?;
+
+    let mut response = config
+        .send(
+            client
+                .get(&relay_url)
+                .header(header::ACCEPT, "application/nostr+json"),
+        )
+        .await
+        .map_err(|_| NIP11Error::RelayInformationDocumentNotAccessible)?;
+
+    if should_retry_without_accept(response.status()) {
+        response = config
+            .send(client.get(&relay_url))
+            .await
+            .map_err(|_| NIP11Error::RelayInformationDocumentNotAccessible)?;
+    }
+
+    response
+        .json()
         .await
-    {
-        Ok(response) => match response.json().await {
-            Ok(json) => json,
-            Err(_) => return Err(NIP11Error::InvalidRelayInformationDocument),
-        },
-        Err(_) => return Err(NIP11Error::RelayInformationDocumentNotAccessible),
-    };
-
-    Ok(relay_response)
+        .map_err(|_| NIP11Error::InvalidRelayInformationDocument)
+}
+
+#[cfg(feature = "net")]
+impl Client {
+    #[cfg(not(feature = "async"))]
+    /// Set the maximum number of open subscriptions of every relay to the `max_subscriptions`
+    /// advertised in its relay information document (NIP-11)
+    ///
+    /// Return the result of each relay, the limit of a relay whose document is not accessible is
+    /// left unchanged
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    ///
+    /// for (relay, result) in client.load_max_subscriptions() {
+    ///     println!("{}: {:?}", relay, result);
+    /// }
+    /// ```
+    pub fn load_max_subscriptions(&mut self) -> Vec<(String, Result<Option<usize>, NIP11Error>)> {
+        let mut results = Vec::new();
+
+        for relay in self.relay_urls().to_vec() {
+            let result = get_relay_information_document_with_config(&relay, self.http_config())
+                .map(|document| {
+                    document
+                        .limitation
+                        .and_then(|limitation| limitation.max_subscriptions)
+                });
+
+            if let Ok(max_subscriptions) = result {
+                self.set_max_subscriptions(&relay, max_subscriptions);
+            }
+
+            results.push((relay, result));
+        }
+
+        results
+    }
+
+    #[cfg(feature = "async")]
+    /// Set the maximum number of open subscriptions of every relay to the `max_subscriptions`
+    /// advertised in its relay information document (NIP-11)
+    ///
+    /// Return the result of each relay, the limit of a relay whose document is not accessible is
+    /// left unchanged
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// #[tokio::test]
+    /// async fn test_load_max_subscriptions() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///
+    ///     for (relay, result) in client.load_max_subscriptions().await {
+    ///         println!("{}: {:?}", relay, result);
+    ///     }
+    /// }
+    /// ```
+    pub async fn load_max_subscriptions(
+        &mut self,
+    ) -> Vec<(String, Result<Option<usize>, NIP11Error>)> {
+        let mut results = Vec::new();
+
+        for relay in self.relay_urls().to_vec() {
+            let result = get_relay_information_document_with_config(&relay, self.http_config())
+                .await
+                .map(|document| {
+                    document
+                        .limitation
+                        .and_then(|limitation| limitation.max_subscriptions)
+                });
+
+            if let Ok(max_subscriptions) = result {
+                self.set_max_subscriptions(&relay, max_subscriptions);
+            }
+
+            results.push((relay, result));
+        }
+
+        results
+    }
 }
@@ -1,4 +1,6 @@
-use crate::{events::EventPrepare, nostr_client::ClientError, utils::get_timestamp};
+#[cfg(feature = "net")]
+use crate::nostr_client::ClientError;
+use crate::{events::EventPrepare, utils::get_timestamp};
 use hex::FromHexError;
 use rand::Rng;
 use thiserror::Error;
@@ -11,10 +13,12 @@ pub enum NIP13Error {
     #[error("Content Id is invalid")]
     InvalidContentId(FromHexError),
 
+    #[cfg(feature = "net")]
     #[error("The client has an error")]
     ClientError(ClientError),
 }
 
+#[cfg(feature = "net")]
 impl From<ClientError> for NIP13Error {
     fn from(err: ClientError) -> Self {
         Self::ClientError(err)
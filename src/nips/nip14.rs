@@ -0,0 +1,85 @@
+#[cfg(feature = "net")]
+use crate::{events::Event, nips::nip1::NIP1Error, nostr_client::Client, signer::Signer};
+
+// Implementation of the NIP14 protocol
+// https://github.com/nostr-protocol/nips/blob/master/14.md
+
+/// Build a subject tag
+pub fn subject_tag(subject: &str) -> Vec<String> {
+    vec!["subject".to_string(), subject.to_string()]
+}
+
+/// Get the subject of a reply from the subject of its parent, prefixed with "Re: "
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip14::reply_subject;
+///
+/// assert_eq!(reply_subject("Nostr meetup"), "Re: Nostr meetup");
+/// assert_eq!(reply_subject("Re: Nostr meetup"), "Re: Nostr meetup");
+/// ```
+pub fn reply_subject(parent_subject: &str) -> String {
+    if parent_subject.starts_with("Re: ") {
+        parent_subject.to_string()
+    } else {
+        format!("Re: {}", parent_subject)
+    }
+}
+
+#[cfg(feature = "net")]
+impl Client {
+    #[cfg(not(feature = "async"))]
+    /// Publish a text note (text_note) event with a subject
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = client.publish_text_note_with_subject(&identity, "Hello Nostr!", "Introduction", &[], 0).unwrap();
+    /// assert_eq!(event.subject(), Some("Introduction"));
+    /// ```
+    pub fn publish_text_note_with_subject(
+        &mut self,
+        signer: &dyn Signer,
+        content: &str,
+        subject: &str,
+        tags: &[Vec<String>],
+        difficulty_target: u16,
+    ) -> Result<Event, NIP1Error> {
+        let mut tags = tags.to_vec();
+        tags.push(subject_tag(subject));
+
+        self.publish_text_note(signer, content, &tags, difficulty_target)
+    }
+
+    #[cfg(feature = "async")]
+    /// Publish a text note (text_note) event with a subject
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_publish_text_note_with_subject() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     let event = client.publish_text_note_with_subject(&identity, "Hello Nostr!", "Introduction", &[], 0).await.unwrap();
+    ///     assert_eq!(event.subject(), Some("Introduction"));
+    /// }
+    /// ```
+    pub async fn publish_text_note_with_subject(
+        &mut self,
+        signer: &dyn Signer,
+        content: &str,
+        subject: &str,
+        tags: &[Vec<String>],
+        difficulty_target: u16,
+    ) -> Result<Event, NIP1Error> {
+        let mut tags = tags.to_vec();
+        tags.push(subject_tag(subject));
+
+        self.publish_text_note(signer, content, &tags, difficulty_target)
+            .await
+    }
+}
@@ -5,8 +5,9 @@ use crate::{
     events::{Event, EventPrepare},
     nips::nip11,
     nostr_client::{Client, ClientError},
+    signer::Signer,
     utils::get_timestamp,
-    Identity, Message,
+    Message,
 };
 use serde_json::json;
 use std::collections::HashMap;
@@ -39,6 +40,32 @@ impl From<websocket::SimplifiedWSError> for NIP16Error {
     }
 }
 
+/// Offset added to the kind of a replaceable event
+const REPLACEABLE_KIND_OFFSET: u16 = 10000;
+/// Offset added to the kind of an ephemeral event
+const EPHEMERAL_KIND_OFFSET: u16 = 20000;
+
+/// Event of kind `offset + kind`, `kind` must be less than 10000
+fn nip16_event(
+    pub_key: &str,
+    offset: u16,
+    kind: u16,
+    content: &str,
+    tags: &[Vec<String>],
+) -> Result<EventPrepare, NIP16Error> {
+    if kind > 9999 {
+        return Err(NIP16Error::EventKindOutOfRange);
+    }
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: offset + kind,
+        tags: tags.to_vec(),
+        content: content.to_string(),
+    })
+}
+
 impl Client {
     #[cfg(not(feature = "async"))]
     /// Publish a replaceable event.
@@ -51,7 +78,7 @@ impl Client {
     /// use nostr_rust::Identity;
     /// use nostr_rust::nips::nip16::NIP16Error;
     /// use std::str::FromStr;
-
+    ///
     /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
     /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
     /// let event = client.publish_replaceable_event(
@@ -72,26 +99,17 @@ impl Client {
     /// ```
     pub fn publish_replaceable_event(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         kind: u16,
         content: &str,
         tags: &[Vec<String>],
         difficulty_target: u16,
     ) -> Result<Event, NIP16Error> {
-        if kind > 9999 {
-            return Err(NIP16Error::EventKindOutOfRange);
-        }
-
-        let kind = kind + 10000;
-
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind,
-            tags: tags.to_vec(),
-            content: content.to_string(),
-        }
-        .to_event(identity, difficulty_target);
+        let event = self.sign_template(
+            signer,
+            |pub_key: &str| nip16_event(pub_key, REPLACEABLE_KIND_OFFSET, kind, content, tags),
+            difficulty_target,
+        )?;
 
         self.publish_nip16_event(&event)?;
         Ok(event)
@@ -132,26 +150,17 @@ impl Client {
     /// ```
     pub async fn publish_replaceable_event(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         kind: u16,
         content: &str,
         tags: &[Vec<String>],
         difficulty_target: u16,
     ) -> Result<Event, NIP16Error> {
-        if kind > 9999 {
-            return Err(NIP16Error::EventKindOutOfRange);
-        }
-
-        let kind = kind + 10000;
-
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind,
-            tags: tags.to_vec(),
-            content: content.to_string(),
-        }
-        .to_event(identity, difficulty_target);
+        let event = self.sign_template(
+            signer,
+            |pub_key: &str| nip16_event(pub_key, REPLACEABLE_KIND_OFFSET, kind, content, tags),
+            difficulty_target,
+        )?;
 
         self.publish_nip16_event(&event).await?;
         Ok(event)
@@ -168,7 +177,7 @@ impl Client {
     /// use nostr_rust::Identity;
     /// use nostr_rust::nips::nip16::NIP16Error;
     /// use std::str::FromStr;
-
+    ///
     /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
     /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
     /// let event = client.publish_ephemeral_event(
@@ -186,26 +195,17 @@ impl Client {
     /// ```
     pub fn publish_ephemeral_event(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         kind: u16,
         content: &str,
         tags: &[Vec<String>],
         difficulty_target: u16,
     ) -> Result<Event, NIP16Error> {
-        if kind > 9999 {
-            return Err(NIP16Error::EventKindOutOfRange);
-        }
-
-        let kind = kind + 20000;
-
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind,
-            tags: tags.to_vec(),
-            content: content.to_string(),
-        }
-        .to_event(identity, difficulty_target);
+        let event = self.sign_template(
+            signer,
+            |pub_key: &str| nip16_event(pub_key, EPHEMERAL_KIND_OFFSET, kind, content, tags),
+            difficulty_target,
+        )?;
 
         self.publish_nip16_event(&event)?;
         Ok(event)
@@ -226,15 +226,15 @@ impl Client {
     /// #[tokio::test]
     /// async fn test_publish_ephemeral_event() {
     ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
-    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
-    ///     let event = client.publish_ephemeral_event_async(
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let event = client.publish_ephemeral_event(
     ///         &identity,
     ///         10000,
     ///         "hello world",
     ///         &[],
     ///         0).await.unwrap_err();
     ///     assert_eq!(event, NIP16Error::EventKindOutOfRange);
-    ///     let event = client.publish_ephemeral_event_async(
+    ///     let event = client.publish_ephemeral_event(
     ///         &identity,
     ///         5,
     ///         "hello world",
@@ -243,40 +243,48 @@ impl Client {
     ///     assert_eq!(event.kind, 20005);
     /// }
     /// ```
-    pub async fn publish_ephemeral_event_async(
+    pub async fn publish_ephemeral_event(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         kind: u16,
         content: &str,
         tags: &[Vec<String>],
         difficulty_target: u16,
     ) -> Result<Event, NIP16Error> {
-        if kind > 9999 {
-            return Err(NIP16Error::EventKindOutOfRange);
-        }
-
-        let kind = kind + 20000;
-
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind,
-            tags: tags.to_vec(),
-            content: content.to_string(),
-        }
-        .to_event(identity, difficulty_target);
+        let event = self.sign_template(
+            signer,
+            |pub_key: &str| nip16_event(pub_key, EPHEMERAL_KIND_OFFSET, kind, content, tags),
+            difficulty_target,
+        )?;
 
         self.publish_nip16_event(&event).await?;
         Ok(event)
     }
 
+    #[cfg(feature = "async")]
+    #[deprecated(note = "use `Client::publish_ephemeral_event`")]
+    /// Publish an ephemeral event, see `Client::publish_ephemeral_event`
+    pub async fn publish_ephemeral_event_async(
+        &mut self,
+        signer: &dyn Signer,
+        kind: u16,
+        content: &str,
+        tags: &[Vec<String>],
+        difficulty_target: u16,
+    ) -> Result<Event, NIP16Error> {
+        self.publish_ephemeral_event(signer, kind, content, tags, difficulty_target)
+            .await
+    }
+
     #[cfg(not(feature = "async"))]
     pub fn publish_nip16_event(&mut self, event: &Event) -> Result<(), NIP16Error> {
         let supported_relays: HashMap<&String, &Arc<std::sync::Mutex<SimplifiedWS>>> = self
             .relays
             .iter()
             .filter_map(|(relay_url, ws)| {
-                if let Ok(relay_info) = nip11::get_relay_information_document(relay_url) {
+                if let Ok(relay_info) =
+                    nip11::get_relay_information_document_with_config(relay_url, self.http_config())
+                {
                     if let Some(supported_nips) = relay_info.supported_nips {
                         if supported_nips.contains(&16) {
                             return Some((relay_url, ws));
@@ -304,7 +312,9 @@ impl Client {
             HashMap::new();
 
         for relay in self.relays.iter() {
-            if let Ok(relay_info) = nip11::get_relay_information_document(relay.0).await {
+            if let Ok(relay_info) =
+                nip11::get_relay_information_document_with_config(relay.0, self.http_config()).await
+            {
                 if let Some(supported_nips) = relay_info.supported_nips {
                     if supported_nips.contains(&16) {
                         supported_relays.insert(relay.0, relay.1);
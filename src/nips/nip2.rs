@@ -1,11 +1,15 @@
-use crate::bech32::auto_bech32_to_hex;
+#[cfg(feature = "net")]
 use crate::{
+    bech32::auto_bech32_to_hex,
     events::EventPrepare,
-    nostr_client::{Client, ClientError},
+    nostr_client::{Client, ClientError, EventsPerRelay},
     req::ReqFilter,
+    signer::Signer,
     utils::get_timestamp,
-    Identity,
 };
+use crate::{events::Event, utils::tag_value};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use thiserror::Error;
 
 // Implementation of the NIP2 protocol
@@ -13,11 +17,18 @@ use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum NIP2Error {
+    #[cfg(feature = "net")]
     #[error("The client has an error")]
     ClientError(#[from] ClientError),
 
     #[error("Bech32 Error: {}", _0)]
     Bech32Error(#[from] crate::bech32::Bech32Error),
+
+    #[error("Key Error: {}", _0)]
+    KeyError(#[from] crate::keys::KeyError),
+
+    #[error("Serde Error: {}", _0)]
+    SerdeError(#[from] serde_json::Error),
 }
 
 #[derive(Debug, Clone)]
@@ -30,6 +41,90 @@ pub struct ContactListTag {
     pub surname: Option<String>,
 }
 
+/// Read/write preferences of a relay stored in the content of a contact list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayUsage {
+    #[serde(default)]
+    pub read: bool,
+    #[serde(default)]
+    pub write: bool,
+}
+
+/// A contact list: the contacts from the "p" tags and the relays from the content
+#[derive(Debug, Clone, Default)]
+pub struct ContactList {
+    pub contacts: Vec<ContactListTag>,
+    pub relays: HashMap<String, RelayUsage>,
+}
+
+impl ContactList {
+    /// Add a contact, or update the relay and petname of a followed contact when they are given,
+    /// return false if the contact list is unchanged
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nips::nip2::{ContactList, ContactListTag};
+    ///
+    /// let contact = |relay: Option<&str>, surname: Option<&str>| ContactListTag {
+    ///   key: "pubkey".to_string(),
+    ///   main_relay: relay.map(str::to_string),
+    ///   surname: surname.map(str::to_string),
+    /// };
+    ///
+    /// let mut contact_list = ContactList::default();
+    /// assert!(contact_list.follow(contact(Some("wss://relay.example"), None)));
+    /// assert!(contact_list.follow(contact(None, Some("alice"))));
+    /// assert!(!contact_list.follow(contact(Some("wss://relay.example"), None)));
+    ///
+    /// assert_eq!(contact_list.contacts.len(), 1);
+    /// assert_eq!(contact_list.contacts[0].main_relay.as_deref(), Some("wss://relay.example"));
+    /// assert_eq!(contact_list.contacts[0].surname.as_deref(), Some("alice"));
+    /// ```
+    pub fn follow(&mut self, contact: ContactListTag) -> bool {
+        match self.contacts.iter_mut().find(|c| c.key == contact.key) {
+            Some(followed) => {
+                let mut changed = false;
+
+                if contact.main_relay.is_some() && followed.main_relay != contact.main_relay {
+                    followed.main_relay = contact.main_relay;
+                    changed = true;
+                }
+
+                if contact.surname.is_some() && followed.surname != contact.surname {
+                    followed.surname = contact.surname;
+                    changed = true;
+                }
+
+                changed
+            }
+            None => {
+                self.contacts.push(contact);
+                true
+            }
+        }
+    }
+
+    /// Remove a contact, return false if it was not followed
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nips::nip2::{ContactList, ContactListTag};
+    ///
+    /// let mut contact_list = ContactList::default();
+    /// contact_list.follow(ContactListTag { key: "pubkey".to_string(), main_relay: None, surname: None });
+    ///
+    /// assert!(contact_list.unfollow("pubkey"));
+    /// assert!(!contact_list.unfollow("pubkey"));
+    /// assert!(contact_list.contacts.is_empty());
+    /// ```
+    pub fn unfollow(&mut self, key: &str) -> bool {
+        let count = self.contacts.len();
+        self.contacts.retain(|contact| contact.key != key);
+
+        self.contacts.len() != count
+    }
+}
+
 impl ContactListTag {
     pub fn to_tags(&self) -> Vec<String> {
         let mut tags: Vec<String> = vec![String::from("p"), self.key.clone()];
@@ -48,12 +143,161 @@ impl ContactListTag {
 
         tags
     }
+
+    /// Parse a "p" tag into a contact, None if the tag is not a valid "p" tag
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nips::nip2::ContactListTag;
+    ///
+    /// let contact = ContactListTag::from_tags(&["p".to_string(), "pubkey".to_string(), "wss://relay.example".to_string()]).unwrap();
+    /// assert_eq!(contact.key, "pubkey");
+    /// assert_eq!(contact.main_relay, Some("wss://relay.example".to_string()));
+    /// assert_eq!(contact.surname, None);
+    ///
+    /// assert!(ContactListTag::from_tags(&["p".to_string()]).is_none());
+    /// assert!(ContactListTag::from_tags(&[]).is_none());
+    /// ```
+    pub fn from_tags(tag: &[String]) -> Option<Self> {
+        if tag_value(tag, 0) != Some("p") {
+            return None;
+        }
+
+        Some(Self {
+            key: tag_value(tag, 1)?.to_string(),
+            main_relay: tag_value(tag, 2).map(str::to_string),
+            surname: tag_value(tag, 3).map(str::to_string),
+        })
+    }
+}
+
+/// Get the contacts of a contact list event, malformed "p" tags are skipped
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::Event, nips::nip2::parse_contact_list};
+///
+/// let event = Event {
+///   id: String::new(),
+///   pub_key: String::new(),
+///   created_at: 0,
+///   kind: 3,
+///   tags: vec![
+///     vec!["p".to_string()],
+///     vec!["p".to_string(), "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()],
+///   ],
+///   content: String::new(),
+///   sig: String::new(),
+/// };
+///
+/// let contact_list = parse_contact_list(&event);
+/// assert_eq!(contact_list.len(), 1);
+/// assert_eq!(contact_list[0].key, "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6");
+/// ```
+pub fn parse_contact_list(event: &Event) -> Vec<ContactListTag> {
+    event
+        .tags
+        .iter()
+        .filter_map(|tag| ContactListTag::from_tags(tag))
+        .collect()
 }
 
+/// Get the relays stored in the content of a contact list event, an empty or invalid content gives an empty map
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::Event, nips::nip2::{parse_contact_list_relays, RelayUsage}};
+///
+/// let mut event = Event {
+///   id: String::new(),
+///   pub_key: String::new(),
+///   created_at: 0,
+///   kind: 3,
+///   tags: vec![],
+///   content: r#"{"wss://relay.example":{"read":true,"write":false}}"#.to_string(),
+///   sig: String::new(),
+/// };
+///
+/// let relays = parse_contact_list_relays(&event);
+/// assert_eq!(relays["wss://relay.example"], RelayUsage { read: true, write: false });
+///
+/// event.content = String::new();
+/// assert!(parse_contact_list_relays(&event).is_empty());
+/// ```
+pub fn parse_contact_list_relays(event: &Event) -> HashMap<String, RelayUsage> {
+    if event.content.trim().is_empty() {
+        return HashMap::new();
+    }
+
+    serde_json::from_str(&event.content).unwrap_or_default()
+}
+
+#[cfg(feature = "net")]
+/// Contact list event (kind 3), the relays are stored as JSON in the content
+fn contact_list_event(
+    pub_key: &str,
+    contact_list: &[ContactListTag],
+    relays: Option<&HashMap<String, RelayUsage>>,
+) -> Result<EventPrepare, NIP2Error> {
+    let content = match relays {
+        Some(relays) => serde_json::to_string(relays)?,
+        None => String::new(),
+    };
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: 3,
+        tags: contact_list
+            .iter()
+            .map(|contact| contact.to_tags())
+            .collect(),
+        content,
+    })
+}
+
+#[cfg(feature = "net")]
+/// Newest contact list sent by the relays, each relay sends its latest version and only the
+/// newest one is the current list. The failing relays are ignored
+fn newest_contact_list(events_per_relay: &EventsPerRelay) -> Option<&Event> {
+    events_per_relay
+        .values()
+        .filter_map(|events| events.as_ref().ok())
+        .flatten()
+        .max_by_key(|event| event.created_at)
+}
+
+#[cfg(feature = "net")]
+/// Contact list of a contact list event
+fn to_contact_list(event: &Event) -> ContactList {
+    ContactList {
+        contacts: parse_contact_list(event),
+        relays: parse_contact_list_relays(event),
+    }
+}
+
+#[cfg(feature = "net")]
+/// Current contact list of a signer: an account without contact list on any relay starts from an
+/// empty one, but not when a relay failed since it may store the list and publishing an empty
+/// one would erase it
+fn own_contact_list_of(events_per_relay: EventsPerRelay) -> Result<ContactList, NIP2Error> {
+    if let Some(event) = newest_contact_list(&events_per_relay) {
+        return Ok(to_contact_list(event));
+    }
+
+    match events_per_relay.into_values().find_map(Result::err) {
+        Some(error) => Err(NIP2Error::ClientError(error)),
+        None => Ok(ContactList::default()),
+    }
+}
+
+#[cfg(feature = "net")]
 impl Client {
     #[cfg(not(feature = "async"))]
     /// Set the contact list of the identity
     ///
+    /// The relays are written in the content, pass the relays read by `Client::get_contact_list`
+    /// to keep them: None leaves the content empty and erases them.
     /// # Example
     /// ```rust
     /// use nostr_rust::{nostr_client::Client, Identity, nips::nip2::ContactListTag};
@@ -67,33 +311,33 @@ impl Client {
     ///   main_relay: Some(env!("RELAY_URL").to_string()),
     ///   surname: Some("Rust Nostr Client".to_string()),
     /// }],
+    /// None,
     /// 0).unwrap();
+    ///
+    /// // Update the contacts and keep the relays
+    /// let contact_list = client.get_contact_list(env!("PUBLIC_KEY")).unwrap();
+    /// client.set_contact_list(&identity, contact_list.contacts, Some(&contact_list.relays), 0).unwrap();
     /// ```
     pub fn set_contact_list(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         contact_list: Vec<ContactListTag>,
+        relays: Option<&HashMap<String, RelayUsage>>,
         difficulty_target: u16,
     ) -> Result<(), NIP2Error> {
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 3,
-            tags: contact_list
-                .iter()
-                .map(|contact| contact.to_tags())
-                .collect(),
-            content: String::new(),
-        }
-        .to_event(identity, difficulty_target);
-
-        self.publish_event(&event)?;
+        self.send_template(
+            signer,
+            |pub_key: &str| contact_list_event(pub_key, &contact_list, relays),
+            difficulty_target,
+        )?;
         Ok(())
     }
 
     #[cfg(feature = "async")]
     /// Set the contact list of the identity
     ///
+    /// The relays are written in the content, pass the relays read by `Client::get_contact_list`
+    /// to keep them: None leaves the content empty and erases them.
     /// # Example
     /// ```rust
     /// use nostr_rust::{nostr_client::Client, Identity, nips::nip2::ContactListTag};
@@ -108,33 +352,32 @@ impl Client {
     ///       main_relay: Some(env!("RELAY_URL").to_string()),
     ///       surname: Some("Rust Nostr Client".to_string()),
     ///     }],
-    ///     0).await;
+    ///     None,
+    ///     0).await.unwrap();
+    ///
+    ///     // Update the contacts and keep the relays
+    ///     let contact_list = client.get_contact_list(env!("PUBLIC_KEY")).await.unwrap();
+    ///     client.set_contact_list(&identity, contact_list.contacts, Some(&contact_list.relays), 0).await.unwrap();
     /// }
     /// ```
     pub async fn set_contact_list(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         contact_list: Vec<ContactListTag>,
+        relays: Option<&HashMap<String, RelayUsage>>,
         difficulty_target: u16,
     ) -> Result<(), NIP2Error> {
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 3,
-            tags: contact_list
-                .iter()
-                .map(|contact| contact.to_tags())
-                .collect(),
-            content: String::new(),
-        }
-        .to_event(identity, difficulty_target);
-
-        self.publish_event(&event).await?;
+        self.send_template(
+            signer,
+            |pub_key: &str| contact_list_event(pub_key, &contact_list, relays),
+            difficulty_target,
+        )
+        .await?;
         Ok(())
     }
 
     #[cfg(not(feature = "async"))]
-    /// Get the contact list of a pub key
+    /// Get the contact list of a pub key, with the relays stored in its content
     ///
     /// # Example
     /// ```rust
@@ -142,50 +385,38 @@ impl Client {
     /// use std::str::FromStr;
     /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
     /// let contact_list = client.get_contact_list("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").unwrap();
+    /// println!("{} contacts, {} relays", contact_list.contacts.len(), contact_list.relays.len());
     /// ```
-    pub fn get_contact_list(&mut self, pubkey: &str) -> Result<Vec<ContactListTag>, NIP2Error> {
+    pub fn get_contact_list(&mut self, pubkey: &str) -> Result<ContactList, NIP2Error> {
         let hex_id = auto_bech32_to_hex(pubkey)?;
+        let events_per_relay = self.contact_list_events(hex_id)?;
 
-        let mut contact_list: Vec<ContactListTag> = vec![];
+        Ok(newest_contact_list(&events_per_relay)
+            .map(to_contact_list)
+            .unwrap_or_default())
+    }
 
-        let events = self.get_events_of(vec![ReqFilter {
-            ids: None,
+    #[cfg(not(feature = "async"))]
+    /// Get the contact list events of a hex pub key, waiting for every relay to send EOSE
+    fn contact_list_events(&mut self, hex_id: String) -> Result<EventsPerRelay, NIP2Error> {
+        Ok(self.get_events_of_per_relay(vec![ReqFilter {
             authors: Some(vec![hex_id]),
             kinds: Some(vec![3]),
-            e: None,
-            p: None,
-            since: None,
-            until: None,
             limit: Some(1),
-        }])?;
-
-        for event in events {
-            for tag in event.tags {
-                if tag[0] == "p" {
-                    let mut contact = ContactListTag {
-                        key: tag[1].clone(),
-                        main_relay: None,
-                        surname: None,
-                    };
-
-                    if tag.len() > 2 {
-                        contact.main_relay = Some(tag[2].clone());
-
-                        if tag.len() > 3 {
-                            contact.surname = Some(tag[3].clone());
-                        }
-                    }
-
-                    contact_list.push(contact);
-                }
-            }
-        }
+            ..Default::default()
+        }])?)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get the current contact list of a signer, see `own_contact_list_of`
+    fn own_contact_list(&mut self, signer: &dyn Signer) -> Result<ContactList, NIP2Error> {
+        let events_per_relay = self.contact_list_events(signer.public_key())?;
 
-        Ok(contact_list)
+        own_contact_list_of(events_per_relay)
     }
 
     #[cfg(feature = "async")]
-    /// Get the contact list of a pub key
+    /// Get the contact list of a pub key, with the relays stored in its content
     ///
     /// # Example
     /// ```rust
@@ -198,48 +429,251 @@ impl Client {
     ///     client.get_contact_list("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").await.unwrap();
     /// }
     /// ```
-    pub async fn get_contact_list(
-        &mut self,
-        pubkey: &str,
-    ) -> Result<Vec<ContactListTag>, NIP2Error> {
+    pub async fn get_contact_list(&mut self, pubkey: &str) -> Result<ContactList, NIP2Error> {
         let hex_id = auto_bech32_to_hex(pubkey)?;
-        let mut contact_list: Vec<ContactListTag> = vec![];
+        let events_per_relay = self.contact_list_events(hex_id).await?;
 
-        let events = self
-            .get_events_of(vec![ReqFilter {
-                ids: None,
+        Ok(newest_contact_list(&events_per_relay)
+            .map(to_contact_list)
+            .unwrap_or_default())
+    }
+
+    #[cfg(feature = "async")]
+    /// Get the contact list events of a hex pub key, waiting for every relay to send EOSE
+    async fn contact_list_events(&mut self, hex_id: String) -> Result<EventsPerRelay, NIP2Error> {
+        Ok(self
+            .get_events_of_per_relay(vec![ReqFilter {
                 authors: Some(vec![hex_id]),
                 kinds: Some(vec![3]),
-                e: None,
-                p: None,
-                since: None,
-                until: None,
                 limit: Some(1),
+                ..Default::default()
             }])
+            .await?)
+    }
+
+    #[cfg(feature = "async")]
+    /// Get the current contact list of a signer, see `own_contact_list_of`
+    async fn own_contact_list(&mut self, signer: &dyn Signer) -> Result<ContactList, NIP2Error> {
+        let events_per_relay = self.contact_list_events(signer.public_key()).await?;
+
+        own_contact_list_of(events_per_relay)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Follow a public key (hex or npub): the newest contact list of the identity is fetched from
+    /// every relay right before adding the contact and republishing it, the other contacts and the
+    /// relays are kept
+    ///
+    /// A followed public key keeps its relay and petname unless new ones are given. Nothing is
+    /// published and None is returned when the contact list is unchanged. An identity without
+    /// contact list on any relay starts a new one, but gets the error of a relay which failed
+    /// instead of overwriting the list it may store
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// client.follow(&identity, "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", None, Some("0xtlt"), 0).unwrap();
+    /// ```
+    ///
+    /// With a mock relay storing no contact list (see `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, websocket::SimplifiedWS, Identity};
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, str::FromStr, thread};
+    /// use tungstenite::Message;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let url = format!("ws://{}", listener.local_addr().unwrap());
+    /// thread::spawn(move || {
+    ///     let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///
+    ///     while let Ok(message) = relay.read_message() {
+    ///         let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///
+    ///         if request[0] == "REQ" {
+    ///             relay.write_message(Message::text(json!(["EOSE", request[1]]).to_string())).unwrap();
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// let (socket, _) = tungstenite::connect(&url).unwrap();
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// client.add_connected_relay(&url, SimplifiedWS::from_socket(&url, socket).unwrap()).unwrap();
+    ///
+    /// let identity = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    /// let event = client.follow(&identity, "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", None, None, 0).unwrap().unwrap();
+    /// assert_eq!(event.tags, vec![vec!["p".to_string(), "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()]]);
+    /// ```
+    pub fn follow(
+        &mut self,
+        signer: &dyn Signer,
+        pubkey: &str,
+        relay_hint: Option<&str>,
+        petname: Option<&str>,
+        difficulty_target: u16,
+    ) -> Result<Option<Event>, NIP2Error> {
+        let key = crate::keys::validate_pubkey(pubkey)?.to_string();
+        let mut contact_list = self.own_contact_list(signer)?;
+
+        if !contact_list.follow(ContactListTag {
+            key,
+            main_relay: relay_hint.map(str::to_string),
+            surname: petname.map(str::to_string),
+        }) {
+            return Ok(None);
+        }
+
+        let event = self.send_template(
+            signer,
+            |pub_key: &str| {
+                contact_list_event(pub_key, &contact_list.contacts, Some(&contact_list.relays))
+            },
+            difficulty_target,
+        )?;
+
+        Ok(Some(event))
+    }
+
+    #[cfg(feature = "async")]
+    /// Follow a public key (hex or npub): the newest contact list of the identity is fetched from
+    /// every relay right before adding the contact and republishing it, the other contacts and the
+    /// relays are kept
+    ///
+    /// A followed public key keeps its relay and petname unless new ones are given. Nothing is
+    /// published and None is returned when the contact list is unchanged. An identity without
+    /// contact list on any relay starts a new one, but gets the error of a relay which failed
+    /// instead of overwriting the list it may store
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_follow() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     client.follow(&identity, "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", None, Some("0xtlt"), 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn follow(
+        &mut self,
+        signer: &dyn Signer,
+        pubkey: &str,
+        relay_hint: Option<&str>,
+        petname: Option<&str>,
+        difficulty_target: u16,
+    ) -> Result<Option<Event>, NIP2Error> {
+        let key = crate::keys::validate_pubkey(pubkey)?.to_string();
+        let mut contact_list = self.own_contact_list(signer).await?;
+
+        if !contact_list.follow(ContactListTag {
+            key,
+            main_relay: relay_hint.map(str::to_string),
+            surname: petname.map(str::to_string),
+        }) {
+            return Ok(None);
+        }
+
+        let event = self
+            .send_template(
+                signer,
+                |pub_key: &str| {
+                    contact_list_event(pub_key, &contact_list.contacts, Some(&contact_list.relays))
+                },
+                difficulty_target,
+            )
             .await?;
 
-        for event in events {
-            for tag in event.tags {
-                if tag[0] == "p" {
-                    let mut contact = ContactListTag {
-                        key: tag[1].clone(),
-                        main_relay: None,
-                        surname: None,
-                    };
+        Ok(Some(event))
+    }
 
-                    if tag.len() > 2 {
-                        contact.main_relay = Some(tag[2].clone());
+    #[cfg(not(feature = "async"))]
+    /// Unfollow a public key (hex or npub): the newest contact list of the identity is fetched
+    /// from every relay right before removing the contact and republishing it, the other contacts
+    /// and the relays are kept
+    ///
+    /// Nothing is published and None is returned when the public key was not followed, e.g. by an
+    /// identity without contact list. A relay which failed returns its error instead of an
+    /// overwrite of the list it may store
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// client.unfollow(&identity, "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 0).unwrap();
+    /// ```
+    pub fn unfollow(
+        &mut self,
+        signer: &dyn Signer,
+        pubkey: &str,
+        difficulty_target: u16,
+    ) -> Result<Option<Event>, NIP2Error> {
+        let key = crate::keys::validate_pubkey(pubkey)?.to_string();
+        let mut contact_list = self.own_contact_list(signer)?;
 
-                        if tag.len() > 3 {
-                            contact.surname = Some(tag[3].clone());
-                        }
-                    }
+        if !contact_list.unfollow(&key) {
+            return Ok(None);
+        }
 
-                    contact_list.push(contact);
-                }
-            }
+        let event = self.send_template(
+            signer,
+            |pub_key: &str| {
+                contact_list_event(pub_key, &contact_list.contacts, Some(&contact_list.relays))
+            },
+            difficulty_target,
+        )?;
+
+        Ok(Some(event))
+    }
+
+    #[cfg(feature = "async")]
+    /// Unfollow a public key (hex or npub): the newest contact list of the identity is fetched
+    /// from every relay right before removing the contact and republishing it, the other contacts
+    /// and the relays are kept
+    ///
+    /// Nothing is published and None is returned when the public key was not followed, e.g. by an
+    /// identity without contact list. A relay which failed returns its error instead of an
+    /// overwrite of the list it may store
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_unfollow() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     client.unfollow(&identity, "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn unfollow(
+        &mut self,
+        signer: &dyn Signer,
+        pubkey: &str,
+        difficulty_target: u16,
+    ) -> Result<Option<Event>, NIP2Error> {
+        let key = crate::keys::validate_pubkey(pubkey)?.to_string();
+        let mut contact_list = self.own_contact_list(signer).await?;
+
+        if !contact_list.unfollow(&key) {
+            return Ok(None);
         }
 
-        Ok(contact_list)
+        let event = self
+            .send_template(
+                signer,
+                |pub_key: &str| {
+                    contact_list_event(pub_key, &contact_list.contacts, Some(&contact_list.relays))
+                },
+                difficulty_target,
+            )
+            .await?;
+
+        Ok(Some(event))
     }
 }
@@ -0,0 +1,235 @@
+use crate::bech32::{decode_tlv, from_hb_to_hex, Bech32Error, ToBech32Kind};
+use crate::events::Address;
+use std::ops::Range;
+use std::str::FromStr;
+use thiserror::Error;
+
+// Implementation of the NIP21 protocol
+// https://github.com/nostr-protocol/nips/blob/master/21.md
+
+/// Scheme of the Nostr URIs
+pub const URI_SCHEME: &str = "nostr:";
+
+/// Characters of the data part of a bech32 string
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum NIP21Error {
+    #[error("The URI must start with \"nostr:\"")]
+    MissingScheme,
+
+    #[error(
+        "Unsupported entity \"{}\", the URI must be a npub, nprofile, note, nevent or naddr",
+        _0
+    )]
+    UnsupportedEntity(String),
+
+    #[error("Bech32 Error: {}", _0)]
+    Bech32Error(#[from] Bech32Error),
+}
+
+/// Entity of a Nostr URI (NIP-19), the ids and public keys are hex-encoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NostrUri {
+    /// npub
+    PublicKey(String),
+    /// nprofile
+    Profile {
+        pub_key: String,
+        relays: Vec<String>,
+    },
+    /// note
+    Note(String),
+    /// nevent
+    Event {
+        id: String,
+        relays: Vec<String>,
+        author: Option<String>,
+        kind: Option<u16>,
+    },
+    /// naddr
+    Address {
+        address: Address,
+        relays: Vec<String>,
+    },
+}
+
+impl NostrUri {
+    /// Public key of the entity: the profile, the author of the event or of the address, if known
+    pub fn pub_key(&self) -> Option<&str> {
+        match self {
+            Self::PublicKey(pub_key) | Self::Profile { pub_key, .. } => Some(pub_key),
+            Self::Event { author, .. } => author.as_deref(),
+            Self::Address { address, .. } => Some(&address.pub_key),
+            Self::Note(_) => None,
+        }
+    }
+
+    /// Id of the event of the entity, if any
+    pub fn event_id(&self) -> Option<&str> {
+        match self {
+            Self::Note(id) | Self::Event { id, .. } => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Decode a bech32 entity, without the "nostr:" prefix
+    fn from_entity(entity: &str) -> Result<Self, NIP21Error> {
+        let Some((hrp, _)) = entity.rsplit_once('1') else {
+            return Err(NIP21Error::UnsupportedEntity(entity.to_string()));
+        };
+
+        match hrp {
+            "npub" => Ok(Self::PublicKey(from_hb_to_hex(
+                ToBech32Kind::PublicKey,
+                entity,
+            )?)),
+            "note" => Ok(Self::Note(from_hb_to_hex(ToBech32Kind::Note, entity)?)),
+            "nprofile" => {
+                let pointer = decode_pointer(hrp, entity)?;
+
+                Ok(Self::Profile {
+                    pub_key: pointer.special.ok_or(Bech32Error::InvalidTlv)?,
+                    relays: pointer.relays,
+                })
+            }
+            "nevent" => {
+                let pointer = decode_pointer(hrp, entity)?;
+
+                Ok(Self::Event {
+                    id: pointer.special.ok_or(Bech32Error::InvalidTlv)?,
+                    relays: pointer.relays,
+                    author: pointer.author,
+                    kind: pointer.kind,
+                })
+            }
+            "naddr" => {
+                let (address, relays) = crate::bech32::decode_naddr(entity)?;
+
+                Ok(Self::Address { address, relays })
+            }
+            // nsec is not allowed in a URI
+            _ => Err(NIP21Error::UnsupportedEntity(hrp.to_string())),
+        }
+    }
+}
+
+/// TLV entries of a nprofile or a nevent
+#[derive(Default)]
+struct Pointer {
+    /// Public key of a nprofile, event id of a nevent
+    special: Option<String>,
+    relays: Vec<String>,
+    author: Option<String>,
+    kind: Option<u16>,
+}
+
+/// Decode the TLV entries of a nprofile or a nevent, unknown TLV types are ignored
+fn decode_pointer(hrp: &str, entity: &str) -> Result<Pointer, Bech32Error> {
+    let mut pointer = Pointer::default();
+
+    for (tlv_type, value) in decode_tlv(hrp, entity)? {
+        match tlv_type {
+            0 if value.len() == 32 => pointer.special = Some(hex::encode(value)),
+            1 => pointer
+                .relays
+                .push(String::from_utf8(value).map_err(|_| Bech32Error::InvalidTlv)?),
+            2 if value.len() == 32 => pointer.author = Some(hex::encode(value)),
+            3 => {
+                let bytes: [u8; 4] = value.try_into().map_err(|_| Bech32Error::InvalidTlv)?;
+                pointer.kind = Some(
+                    u16::try_from(u32::from_be_bytes(bytes))
+                        .map_err(|_| Bech32Error::InvalidTlv)?,
+                );
+            }
+            0 | 2 => return Err(Bech32Error::InvalidTlv),
+            _ => {}
+        }
+    }
+
+    Ok(pointer)
+}
+
+impl FromStr for NostrUri {
+    type Err = NIP21Error;
+
+    /// Parse a Nostr URI: "nostr:" followed by a npub, nprofile, note, nevent or naddr
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nips::nip21::{NIP21Error, NostrUri};
+    /// use std::str::FromStr;
+    ///
+    /// let uri = NostrUri::from_str("nostr:npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6").unwrap();
+    /// assert_eq!(uri, NostrUri::PublicKey("3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d".to_string()));
+    ///
+    /// let uri = NostrUri::from_str("nostr:nevent1qqs0qwpdjvkackrkhtfln30ahp8mfs400n80a0a5j8cnl0z8cw8c4eqpz3mhxue69uhhyetvv9ujuerpd46hxtnfdu4f8uep").unwrap();
+    /// assert_eq!(uri.event_id(), Some("f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4"));
+    ///
+    /// assert_eq!(NostrUri::from_str("npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6"), Err(NIP21Error::MissingScheme));
+    /// assert!(NostrUri::from_str("nostr:nsec1qqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqqsmhltgl").is_err());
+    /// ```
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        let entity = uri
+            .strip_prefix(URI_SCHEME)
+            .ok_or(NIP21Error::MissingScheme)?;
+
+        Self::from_entity(entity)
+    }
+}
+
+/// Find every valid Nostr URI of a text, with its byte range, to linkify them in place
+///
+/// The URIs don't need to be separated by spaces (e.g. in a markdown link), the invalid ones are
+/// skipped. See `utils::parse_content_tags` to compose a content instead
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip21::{extract_all, NostrUri};
+///
+/// let npub = "nostr:npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6";
+/// let text = format!("Follow [jack]({}), not nostr:npub1invalid or nostr:nsec1xyz.{}!", npub, npub);
+///
+/// let uris = extract_all(&text);
+/// assert_eq!(uris.len(), 2);
+/// assert_eq!(&text[uris[0].0.clone()], npub);
+/// assert_eq!(&text[uris[1].0.clone()], npub);
+/// assert_eq!(uris[0].1.pub_key(), Some("3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d"));
+/// ```
+pub fn extract_all(text: &str) -> Vec<(Range<usize>, NostrUri)> {
+    let mut uris: Vec<(Range<usize>, NostrUri)> = Vec::new();
+    let mut offset = 0;
+
+    while let Some(position) = text[offset..].find(URI_SCHEME) {
+        let start = offset + position;
+        let entity_start = start + URI_SCHEME.len();
+        let entity_len = entity_len(&text[entity_start..]);
+        let end = entity_start + entity_len;
+
+        match NostrUri::from_entity(&text[entity_start..end]) {
+            Ok(uri) => {
+                uris.push((start..end, uri));
+                offset = end;
+            }
+            Err(_) => offset = entity_start,
+        }
+    }
+
+    uris
+}
+
+/// Length of the bech32 string at the start of a text: a lowercase prefix, "1", then the data
+fn entity_len(text: &str) -> usize {
+    let hrp_len = text
+        .find(|c: char| !c.is_ascii_lowercase())
+        .unwrap_or(text.len());
+
+    if !text[hrp_len..].starts_with('1') {
+        return 0;
+    }
+
+    let data = &text[hrp_len + 1..];
+    let data_len = data
+        .find(|c: char| !BECH32_CHARSET.contains(c))
+        .unwrap_or(data.len());
+
+    hrp_len + 1 + data_len
+}
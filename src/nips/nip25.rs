@@ -1,27 +1,158 @@
-use crate::bech32::auto_bech32_to_hex;
+#[cfg(feature = "net")]
+use super::nip30;
+use super::nip30::NIP30Error;
+use crate::events::{Event, EventError};
+#[cfg(feature = "net")]
 use crate::{
-    events::{Event, EventPrepare},
+    bech32::auto_bech32_to_hex,
+    events::{EventId, EventPrepare},
     nostr_client::{Client, ClientError},
+    signer::Signer,
     utils::get_timestamp,
-    Identity,
 };
+#[cfg(feature = "net")]
+use secp256k1::XOnlyPublicKey;
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "net")]
+use std::str::FromStr;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum NIP25Error {
+    #[cfg(feature = "net")]
     #[error("The client has an error")]
     ClientError(ClientError),
 
     #[error("Bech32 Error: {}", _0)]
     Bech32Error(#[from] crate::bech32::Bech32Error),
+
+    #[error("Event Error: {}", _0)]
+    EventError(#[from] EventError),
+
+    #[error("Secp256k1 Error: {}", _0)]
+    Secp256k1Error(#[from] secp256k1::Error),
+
+    #[error("NIP30 Error: {}", _0)]
+    NIP30Error(#[from] NIP30Error),
 }
 
+#[cfg(feature = "net")]
 impl From<ClientError> for NIP25Error {
     fn from(err: ClientError) -> Self {
         Self::ClientError(err)
     }
 }
 
+/// Count the reactions (kind 7) to an event, by reaction
+///
+/// "+" and empty reactions are counted as "+" (like), "-" is a dislike, the other reactions are
+/// emojis. A reaction is for the target when its last "e" tag references it, and each author
+/// is counted once per reaction.
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::Event, nips::nip25::aggregate_reactions};
+///
+/// let reaction = |author: &str, content: &str| Event {
+///   id: String::new(),
+///   pub_key: author.to_string(),
+///   created_at: 0,
+///   kind: 7,
+///   tags: vec![vec!["e".to_string(), "target".to_string()]],
+///   content: content.to_string(),
+///   sig: String::new(),
+/// };
+///
+/// let reactions = aggregate_reactions(&[
+///   reaction("alice", "+"),
+///   reaction("alice", "+"),
+///   reaction("bob", ""),
+///   reaction("bob", "❤️"),
+///   reaction("carol", "-"),
+/// ], "target");
+///
+/// assert_eq!(reactions["+"], 2);
+/// assert_eq!(reactions["❤️"], 1);
+/// assert_eq!(reactions["-"], 1);
+/// ```
+pub fn aggregate_reactions(reactions: &[Event], target_id: &str) -> HashMap<String, u64> {
+    let mut reactors: HashMap<&str, HashSet<&str>> = HashMap::new();
+
+    for reaction in reactions {
+        if reaction.kind != 7 || reaction.referenced_event_ids().last() != Some(&target_id) {
+            continue;
+        }
+
+        let content = match reaction.content.as_str() {
+            "" => "+",
+            content => content,
+        };
+
+        reactors
+            .entry(content)
+            .or_default()
+            .insert(&reaction.pub_key);
+    }
+
+    reactors
+        .into_iter()
+        .map(|(content, authors)| (content.to_string(), authors.len() as u64))
+        .collect()
+}
+
+#[cfg(feature = "net")]
+/// Parse the event id and public key (hex or bech32) of a reaction
+fn parse_reaction_target(
+    event_id: &str,
+    event_pub_key: &str,
+) -> Result<(EventId, XOnlyPublicKey), NIP25Error> {
+    let event_id = EventId::from_hex(&auto_bech32_to_hex(event_id)?)?;
+    let event_pub_key = XOnlyPublicKey::from_str(&auto_bech32_to_hex(event_pub_key)?)?;
+
+    Ok((event_id, event_pub_key))
+}
+
+#[cfg(feature = "net")]
+fn reaction_event(
+    pub_key: &str,
+    event_id: EventId,
+    event_pub_key: XOnlyPublicKey,
+    reaction: &str,
+) -> Result<EventPrepare, NIP25Error> {
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: 7,
+        tags: vec![
+            vec!["e".to_string(), event_id.to_hex()],
+            vec!["p".to_string(), event_pub_key.to_string()],
+        ],
+        content: reaction.to_string(),
+    })
+}
+
+#[cfg(feature = "net")]
+/// Reaction with a custom emoji: the content is ":shortcode:" and an emoji tag gives its image
+fn custom_emoji_reaction_event(
+    pub_key: &str,
+    event_id: EventId,
+    event_pub_key: XOnlyPublicKey,
+    shortcode: &str,
+    image_url: &str,
+) -> Result<EventPrepare, NIP25Error> {
+    let emoji_tag = nip30::emoji_tag(shortcode, image_url)?;
+    let mut event = reaction_event(
+        pub_key,
+        event_id,
+        event_pub_key,
+        &format!(":{}:", shortcode),
+    )?;
+    event.tags.push(emoji_tag);
+
+    Ok(event)
+}
+
+#[cfg(feature = "net")]
 impl Client {
     #[cfg(not(feature = "async"))]
     /// React to an event
@@ -42,26 +173,45 @@ impl Client {
     /// ```
     pub fn react_to(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         event_pub_key: &str,
         reaction: &str,
         difficulty_target: u16,
     ) -> Result<Event, NIP25Error> {
-        let hex_id = auto_bech32_to_hex(event_id)?;
-        let hex_pk = auto_bech32_to_hex(event_pub_key)?;
+        let (event_id, event_pub_key) = parse_reaction_target(event_id, event_pub_key)?;
 
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 7,
-            tags: vec![vec!["e".to_string(), hex_id], vec!["p".to_string(), hex_pk]],
-            content: reaction.to_string(),
-        }
-        .to_event(identity, difficulty_target);
+        self.react_to_pk(signer, event_id, event_pub_key, reaction, difficulty_target)
+    }
 
-        self.publish_event(&event)?;
-        Ok(event)
+    #[cfg(not(feature = "async"))]
+    /// React to an event, with an already validated event id and public key
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventId, nostr_client::Client, Identity};
+    /// use secp256k1::XOnlyPublicKey;
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let event_id = EventId::from_hex("342060554ca30a9792f6e6959675ae734aed02c23e35037d2a0f72ac6316e83d").unwrap();
+    /// let pubkey = XOnlyPublicKey::from_str("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").unwrap();
+    /// client.react_to_pk(&identity, event_id, pubkey, "+", 0).unwrap();
+    /// ```
+    pub fn react_to_pk(
+        &mut self,
+        signer: &dyn Signer,
+        event_id: EventId,
+        event_pub_key: XOnlyPublicKey,
+        reaction: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP25Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| reaction_event(pub_key, event_id, event_pub_key, reaction),
+            difficulty_target,
+        )
     }
 
     #[cfg(feature = "async")]
@@ -87,26 +237,128 @@ impl Client {
     /// ```
     pub async fn react_to(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         event_pub_key: &str,
         reaction: &str,
         difficulty_target: u16,
     ) -> Result<Event, NIP25Error> {
-        let hex_id = auto_bech32_to_hex(event_id)?;
-        let hex_pk = auto_bech32_to_hex(event_pub_key)?;
+        let (event_id, event_pub_key) = parse_reaction_target(event_id, event_pub_key)?;
 
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 7,
-            tags: vec![vec!["e".to_string(), hex_id], vec!["p".to_string(), hex_pk]],
-            content: reaction.to_string(),
-        }
-        .to_event(identity, difficulty_target);
+        self.react_to_pk(signer, event_id, event_pub_key, reaction, difficulty_target)
+            .await
+    }
+
+    #[cfg(feature = "async")]
+    /// React to an event, with an already validated event id and public key
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventId, nostr_client::Client, Identity};
+    /// use secp256k1::XOnlyPublicKey;
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_react_to_pk() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let event_id = EventId::from_hex("342060554ca30a9792f6e6959675ae734aed02c23e35037d2a0f72ac6316e83d").unwrap();
+    ///     let pubkey = XOnlyPublicKey::from_str("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").unwrap();
+    ///     client.react_to_pk(&identity, event_id, pubkey, "+", 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn react_to_pk(
+        &mut self,
+        signer: &dyn Signer,
+        event_id: EventId,
+        event_pub_key: XOnlyPublicKey,
+        reaction: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP25Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| reaction_event(pub_key, event_id, event_pub_key, reaction),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// React to an event with a custom emoji (NIP-30)
+    ///
+    /// The shortcode must only have letters, digits and underscores, see `nip30::content_emojis`
+    /// to render the reactions
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let event = client.react_with_custom_emoji(&identity, "342060554ca30a9792f6e6959675ae734aed02c23e35037d2a0f72ac6316e83d", "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", "soapbox", "https://example.com/soapbox.png", 0).unwrap();
+    /// assert_eq!(event.content, ":soapbox:");
+    /// ```
+    pub fn react_with_custom_emoji(
+        &mut self,
+        signer: &dyn Signer,
+        event_id: &str,
+        event_pub_key: &str,
+        shortcode: &str,
+        image_url: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP25Error> {
+        let (event_id, event_pub_key) = parse_reaction_target(event_id, event_pub_key)?;
+
+        self.send_template(
+            signer,
+            |pub_key: &str| {
+                custom_emoji_reaction_event(pub_key, event_id, event_pub_key, shortcode, image_url)
+            },
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// React to an event with a custom emoji (NIP-30)
+    ///
+    /// The shortcode must only have letters, digits and underscores, see `nip30::content_emojis`
+    /// to render the reactions
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_react_with_custom_emoji() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let event = client.react_with_custom_emoji(&identity, "342060554ca30a9792f6e6959675ae734aed02c23e35037d2a0f72ac6316e83d", "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", "soapbox", "https://example.com/soapbox.png", 0).await.unwrap();
+    ///     assert_eq!(event.content, ":soapbox:");
+    /// }
+    /// ```
+    pub async fn react_with_custom_emoji(
+        &mut self,
+        signer: &dyn Signer,
+        event_id: &str,
+        event_pub_key: &str,
+        shortcode: &str,
+        image_url: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP25Error> {
+        let (event_id, event_pub_key) = parse_reaction_target(event_id, event_pub_key)?;
 
-        self.publish_event(&event).await?;
-        Ok(event)
+        self.send_template(
+            signer,
+            |pub_key: &str| {
+                custom_emoji_reaction_event(pub_key, event_id, event_pub_key, shortcode, image_url)
+            },
+            difficulty_target,
+        )
+        .await
     }
 
     #[cfg(not(feature = "async"))]
@@ -118,11 +370,11 @@ impl Client {
     /// use std::str::FromStr;
     /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
     /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
-    /// client.like(&identity, "342060554ca30a9792f6e6959675ae734aed0223e35037d2a0f72ac6316e83d", "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 0).unwrap();
+    /// client.like(&identity, "342060554ca30a9792f6e6959675ae734aed02c23e35037d2a0f72ac6316e83d", "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 0).unwrap();
     /// ```
     pub fn like(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         event_pub_key: &str,
         difficulty_target: u16,
@@ -130,7 +382,7 @@ impl Client {
         let hex_id = auto_bech32_to_hex(event_id)?;
         let hex_pk = auto_bech32_to_hex(event_pub_key)?;
 
-        self.react_to(identity, &hex_id, &hex_pk, "+", difficulty_target)
+        self.react_to(signer, &hex_id, &hex_pk, "+", difficulty_target)
     }
 
     #[cfg(feature = "async")]
@@ -145,12 +397,12 @@ impl Client {
     /// async fn test_like() {
     ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
     ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
-    ///     client.like(&identity, "342060554ca30a9792f6e6959675ae734aed0223e35037d2a0f72ac6316e83d", "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 0).await.unwrap();
+    ///     client.like(&identity, "342060554ca30a9792f6e6959675ae734aed02c23e35037d2a0f72ac6316e83d", "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 0).await.unwrap();
     /// }
     /// ```
     pub async fn like(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         event_pub_key: &str,
         difficulty_target: u16,
@@ -158,7 +410,7 @@ impl Client {
         let hex_id = auto_bech32_to_hex(event_id)?;
         let hex_pk = auto_bech32_to_hex(event_pub_key)?;
 
-        self.react_to(identity, &hex_id, &hex_pk, "+", difficulty_target)
+        self.react_to(signer, &hex_id, &hex_pk, "+", difficulty_target)
             .await
     }
 
@@ -175,7 +427,7 @@ impl Client {
     /// ```
     pub fn dislike(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         event_pub_key: &str,
         difficulty_target: u16,
@@ -183,7 +435,7 @@ impl Client {
         let hex_id = auto_bech32_to_hex(event_id)?;
         let hex_pk = auto_bech32_to_hex(event_pub_key)?;
 
-        self.react_to(identity, &hex_id, &hex_pk, "-", difficulty_target)
+        self.react_to(signer, &hex_id, &hex_pk, "-", difficulty_target)
     }
 
     #[cfg(feature = "async")]
@@ -203,7 +455,7 @@ impl Client {
     /// ```
     pub async fn dislike(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         event_pub_key: &str,
         difficulty_target: u16,
@@ -211,7 +463,7 @@ impl Client {
         let hex_id = auto_bech32_to_hex(event_id)?;
         let hex_pk = auto_bech32_to_hex(event_pub_key)?;
 
-        self.react_to(identity, &hex_id, &hex_pk, "-", difficulty_target)
+        self.react_to(signer, &hex_id, &hex_pk, "-", difficulty_target)
             .await
     }
 }
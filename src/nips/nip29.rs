@@ -0,0 +1,810 @@
+use crate::events::Event;
+#[cfg(feature = "net")]
+use crate::{
+    events::EventPrepare,
+    nips::nip11::{get_relay_information_document_with_config, NIP11Error},
+    nostr_client::{Client, ClientError},
+    req::ReqFilter,
+    signer::Signer,
+    utils::get_timestamp,
+    websocket::normalize_relay_url,
+};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+// Implementation of the NIP29 protocol
+// https://github.com/nostr-protocol/nips/blob/master/29.md
+
+/// Kind of a group chat message
+pub const GROUP_CHAT_MESSAGE_KIND: u16 = 9;
+/// Kind of a request to join a group
+pub const GROUP_JOIN_REQUEST_KIND: u16 = 9021;
+/// Kind of a request to leave a group
+pub const GROUP_LEAVE_REQUEST_KIND: u16 = 9022;
+/// Kind of the group metadata, signed by the relay
+pub const GROUP_METADATA_KIND: u16 = 39000;
+
+#[derive(Error, Debug)]
+pub enum NIP29Error {
+    #[cfg(feature = "net")]
+    #[error("The client has an error")]
+    ClientError(ClientError),
+
+    #[error(
+        "Invalid group id \"{}\", only the characters a-z, 0-9, - and _ are allowed",
+        _0
+    )]
+    InvalidGroupId(String),
+
+    #[error(
+        "Invalid group reference \"{}\", the format must be <relay>'<group-id>",
+        _0
+    )]
+    InvalidGroupReference(String),
+
+    #[cfg(feature = "net")]
+    #[error("The relay information document of the group relay has an error")]
+    RelayInformationError(NIP11Error),
+
+    #[cfg(feature = "net")]
+    #[error("The relay hosting the group has no public key in its relay information document")]
+    MissingRelayPubkey,
+}
+
+#[cfg(feature = "net")]
+impl From<ClientError> for NIP29Error {
+    fn from(err: ClientError) -> Self {
+        Self::ClientError(err)
+    }
+}
+
+#[cfg(feature = "net")]
+impl From<NIP11Error> for NIP29Error {
+    fn from(err: NIP11Error) -> Self {
+        Self::RelayInformationError(err)
+    }
+}
+
+/// Check a group id: not empty and made of the characters a-z, 0-9, - and _
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip29::validate_group_id;
+///
+/// assert!(validate_group_id("rust-lang_fr").is_ok());
+/// assert!(validate_group_id("").is_err());
+/// assert!(validate_group_id("Rust").is_err());
+/// assert!(validate_group_id("rust lang").is_err());
+/// ```
+pub fn validate_group_id(group_id: &str) -> Result<(), NIP29Error> {
+    if group_id.is_empty()
+        || !group_id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(NIP29Error::InvalidGroupId(group_id.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Group scoped to the relay hosting it, written `<relay>'<group-id>`
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip29::GroupReference;
+/// use std::str::FromStr;
+///
+/// let group = GroupReference::from_str("groups.nostr.com'rust").unwrap();
+/// assert_eq!(group.relay, "groups.nostr.com");
+/// assert_eq!(group.id, "rust");
+/// assert_eq!(group.to_string(), "groups.nostr.com'rust");
+///
+/// // Without a group id, the group is the "_" group of the relay
+/// assert_eq!(GroupReference::from_str("groups.nostr.com").unwrap().id, "_");
+///
+/// assert!(GroupReference::from_str("'rust").is_err());
+/// assert!(GroupReference::from_str("groups.nostr.com'Rust").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupReference {
+    /// Host of the relay, with or without the websocket scheme
+    pub relay: String,
+    /// Id of the group on the relay
+    pub id: String,
+}
+
+#[cfg(feature = "net")]
+impl GroupReference {
+    /// Normalized url of the relay hosting the group, `wss://` is added to a host without scheme
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nips::nip29::GroupReference;
+    /// use std::str::FromStr;
+    ///
+    /// let group = GroupReference::from_str("groups.nostr.com'rust").unwrap();
+    /// assert_eq!(group.relay_url().unwrap(), "wss://groups.nostr.com");
+    ///
+    /// let group = GroupReference::from_str("ws://localhost:7777'rust").unwrap();
+    /// assert_eq!(group.relay_url().unwrap(), "ws://localhost:7777");
+    ///
+    /// assert!(GroupReference::from_str("not a host'rust").unwrap().relay_url().is_err());
+    /// ```
+    pub fn relay_url(&self) -> Result<String, NIP29Error> {
+        let url = if self.relay.contains("://") {
+            self.relay.clone()
+        } else {
+            format!("wss://{}", self.relay)
+        };
+
+        normalize_relay_url(&url).map_err(|_| NIP29Error::InvalidGroupReference(self.to_string()))
+    }
+}
+
+impl FromStr for GroupReference {
+    type Err = NIP29Error;
+
+    fn from_str(reference: &str) -> Result<Self, Self::Err> {
+        let (relay, id) = reference.split_once('\'').unwrap_or((reference, "_"));
+
+        if relay.is_empty() {
+            return Err(NIP29Error::InvalidGroupReference(reference.to_string()));
+        }
+
+        validate_group_id(id)?;
+
+        Ok(Self {
+            relay: relay.to_string(),
+            id: id.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for GroupReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}'{}", self.relay, self.id)
+    }
+}
+
+/// Group metadata (kind 39000)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupMetadata {
+    /// Id of the group ("d" tag)
+    pub id: String,
+    pub name: Option<String>,
+    pub picture: Option<String>,
+    pub about: Option<String>,
+    /// Whether the messages can be read by anyone, false if the group has a "private" tag
+    pub public: bool,
+    /// Whether join requests are accepted automatically, false if the group has a "closed" tag
+    pub open: bool,
+}
+
+impl GroupMetadata {
+    /// Read the metadata of a group from a kind 39000 event, None for other kinds or without "d" tag
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::Event, nips::nip29::GroupMetadata};
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 39000,
+    ///   tags: vec![
+    ///     vec!["d".to_string(), "rust".to_string()],
+    ///     vec!["name".to_string(), "Rust".to_string()],
+    ///     vec!["closed".to_string()],
+    ///   ],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// let metadata = GroupMetadata::from_event(&event).unwrap();
+    /// assert_eq!(metadata.id, "rust");
+    /// assert_eq!(metadata.name.as_deref(), Some("Rust"));
+    /// assert!(metadata.public);
+    /// assert!(!metadata.open);
+    /// ```
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if event.kind != GROUP_METADATA_KIND {
+            return None;
+        }
+
+        let has_tag = |name: &str| event.tags_by_name(name).next().is_some();
+
+        Some(Self {
+            id: event.first_tag_value("d")?.to_string(),
+            name: event.first_tag_value("name").map(str::to_string),
+            picture: event.first_tag_value("picture").map(str::to_string),
+            about: event.first_tag_value("about").map(str::to_string),
+            public: !has_tag("private"),
+            open: !has_tag("closed"),
+        })
+    }
+}
+
+/// Get the chat messages (kind 9) of a group, sorted oldest first
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::Event, nips::nip29::group_messages};
+///
+/// let message = |group_id: &str, created_at: u64| Event {
+///   id: String::new(),
+///   pub_key: String::new(),
+///   created_at,
+///   kind: 9,
+///   tags: vec![vec!["h".to_string(), group_id.to_string()]],
+///   content: String::new(),
+///   sig: String::new(),
+/// };
+///
+/// let events = vec![message("rust", 2), message("other", 1), message("rust", 1)];
+/// let messages = group_messages(&events, "rust");
+/// assert_eq!(messages.len(), 2);
+/// assert_eq!(messages[0].created_at, 1);
+/// ```
+pub fn group_messages(events: &[Event], group_id: &str) -> Vec<Event> {
+    let mut messages: Vec<Event> = events
+        .iter()
+        .filter(|event| {
+            event.kind == GROUP_CHAT_MESSAGE_KIND && event.first_tag_value("h") == Some(group_id)
+        })
+        .cloned()
+        .collect();
+
+    messages.sort_by_key(|message| message.created_at);
+    messages
+}
+
+#[cfg(feature = "net")]
+fn group_tags(group_id: &str) -> Vec<Vec<String>> {
+    vec![vec!["h".to_string(), group_id.to_string()]]
+}
+
+#[cfg(feature = "net")]
+fn join_request_tags(group_id: &str, invite_code: Option<&str>) -> Vec<Vec<String>> {
+    let mut tags = group_tags(group_id);
+
+    if let Some(invite_code) = invite_code {
+        tags.push(vec!["code".to_string(), invite_code.to_string()]);
+    }
+
+    tags
+}
+
+#[cfg(feature = "net")]
+fn join_request_event(
+    pub_key: &str,
+    group_id: &str,
+    invite_code: Option<&str>,
+) -> Result<EventPrepare, NIP29Error> {
+    validate_group_id(group_id)?;
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: GROUP_JOIN_REQUEST_KIND,
+        tags: join_request_tags(group_id, invite_code),
+        content: String::new(),
+    })
+}
+
+#[cfg(feature = "net")]
+fn leave_request_event(pub_key: &str, group_id: &str) -> Result<EventPrepare, NIP29Error> {
+    validate_group_id(group_id)?;
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: GROUP_LEAVE_REQUEST_KIND,
+        tags: group_tags(group_id),
+        content: String::new(),
+    })
+}
+
+#[cfg(feature = "net")]
+fn group_message_event(
+    pub_key: &str,
+    group_id: &str,
+    content: &str,
+) -> Result<EventPrepare, NIP29Error> {
+    validate_group_id(group_id)?;
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: GROUP_CHAT_MESSAGE_KIND,
+        tags: group_tags(group_id),
+        content: content.to_string(),
+    })
+}
+
+#[cfg(feature = "net")]
+/// Messages of the group ("h" tag), `group_messages` still checks the events sent by the relays
+fn messages_filter(group_id: &str, since: Option<u64>) -> ReqFilter {
+    ReqFilter {
+        kinds: Some(vec![GROUP_CHAT_MESSAGE_KIND]),
+        h: Some(vec![group_id.to_string()]),
+        since,
+        ..Default::default()
+    }
+}
+
+#[cfg(feature = "net")]
+/// The metadata is signed by the relay, it is requested by its identifier ("d" tag) and by the
+/// public key of the relay
+fn metadata_filter(group_id: &str, relay_pubkey: &str) -> ReqFilter {
+    ReqFilter {
+        authors: Some(vec![relay_pubkey.to_string()]),
+        kinds: Some(vec![GROUP_METADATA_KIND]),
+        d: Some(vec![group_id.to_string()]),
+        ..Default::default()
+    }
+}
+
+#[cfg(feature = "net")]
+/// Newest metadata of the group among the events signed by the relay, the relay may ignore the
+/// authors of the filter
+fn find_metadata(
+    mut events: Vec<Event>,
+    group_id: &str,
+    relay_pubkey: &str,
+) -> Option<GroupMetadata> {
+    events.retain(|event| event.pub_key == relay_pubkey);
+    events.sort_by_key(|event| std::cmp::Reverse(event.created_at));
+
+    events
+        .iter()
+        .filter_map(GroupMetadata::from_event)
+        .find(|metadata| metadata.id == group_id)
+}
+
+#[cfg(feature = "net")]
+impl Client {
+    #[cfg(not(feature = "async"))]
+    /// Relay of the client hosting the group, added to the client if missing
+    fn group_relay(&mut self, group: &GroupReference) -> Result<String, NIP29Error> {
+        let url = group.relay_url()?;
+
+        match self.find_relay(&url) {
+            Some(relay) => Ok(relay),
+            None => {
+                self.add_relay(&url)?;
+                Ok(url)
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    /// Relay of the client hosting the group, added to the client if missing
+    async fn group_relay(&mut self, group: &GroupReference) -> Result<String, NIP29Error> {
+        let url = group.relay_url()?;
+
+        match self.find_relay(&url) {
+            Some(relay) => Ok(relay),
+            None => {
+                self.add_relay(&url).await?;
+                Ok(url)
+            }
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Public key of the relay hosting the group, from its relay information document (NIP-11)
+    fn group_relay_pubkey(&self, group: &GroupReference) -> Result<String, NIP29Error> {
+        let document =
+            get_relay_information_document_with_config(&group.relay_url()?, self.http_config())?;
+
+        document.pubkey.ok_or(NIP29Error::MissingRelayPubkey)
+    }
+
+    #[cfg(feature = "async")]
+    /// Public key of the relay hosting the group, from its relay information document (NIP-11)
+    async fn group_relay_pubkey(&self, group: &GroupReference) -> Result<String, NIP29Error> {
+        let document =
+            get_relay_information_document_with_config(&group.relay_url()?, self.http_config())
+                .await?;
+
+        document.pubkey.ok_or(NIP29Error::MissingRelayPubkey)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Sign the event of a template and send it to the relay hosting the group only, the other
+    /// relays would reject or leak it
+    fn send_to_group<T: FnOnce(&str) -> Result<EventPrepare, NIP29Error>>(
+        &mut self,
+        signer: &dyn Signer,
+        group: &GroupReference,
+        template: T,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP29Error> {
+        let relay = self.group_relay(group)?;
+        let event = self.sign_template(signer, template, difficulty_target)?;
+
+        self.publish_event_to(&[relay], &event)?;
+        Ok(event)
+    }
+
+    #[cfg(feature = "async")]
+    /// Sign the event of a template and send it to the relay hosting the group only, the other
+    /// relays would reject or leak it
+    async fn send_to_group<T: FnOnce(&str) -> Result<EventPrepare, NIP29Error>>(
+        &mut self,
+        signer: &dyn Signer,
+        group: &GroupReference,
+        template: T,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP29Error> {
+        let relay = self.group_relay(group).await?;
+        let event = self.sign_template(signer, template, difficulty_target)?;
+
+        self.publish_event_to(&[relay], &event).await?;
+        Ok(event)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get the events of the relay hosting the group
+    fn get_group_events(
+        &mut self,
+        group: &GroupReference,
+        filter: ReqFilter,
+    ) -> Result<Vec<Event>, NIP29Error> {
+        let relay = self.group_relay(group)?;
+        let mut events_per_relay = self.get_events_of_relays(vec![relay.clone()], vec![filter])?;
+
+        Ok(events_per_relay.remove(&relay).unwrap_or(Ok(Vec::new()))?)
+    }
+
+    #[cfg(feature = "async")]
+    /// Get the events of the relay hosting the group
+    async fn get_group_events(
+        &mut self,
+        group: &GroupReference,
+        filter: ReqFilter,
+    ) -> Result<Vec<Event>, NIP29Error> {
+        let relay = self.group_relay(group).await?;
+        let mut events_per_relay = self
+            .get_events_of_relays(vec![relay.clone()], vec![filter])
+            .await?;
+
+        Ok(events_per_relay.remove(&relay).unwrap_or(Ok(Vec::new()))?)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Ask to join a group (kind 9021), with the invite code of a closed group
+    ///
+    /// The request is sent to the relay hosting the group only, added to the client if missing
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    /// client.join_group(&identity, &group, None, 0).unwrap();
+    /// ```
+    ///
+    /// With two mock relays, the other relay doesn't receive the request (see
+    /// `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference, websocket::SimplifiedWS, Identity};
+    /// use std::{net::TcpListener, str::FromStr, sync::mpsc, thread, time::Duration};
+    ///
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// let (sender, receiver) = mpsc::channel();
+    /// let mut urls = Vec::new();
+    /// for _ in 0..2 {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let url = format!("ws://{}", listener.local_addr().unwrap());
+    ///     let (relay_url, sender) = (url.clone(), sender.clone());
+    ///     thread::spawn(move || {
+    ///         let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///         while let Ok(message) = relay.read_message() {
+    ///             sender.send((relay_url.clone(), message.into_text().unwrap())).unwrap();
+    ///         }
+    ///     });
+    ///
+    ///     let (socket, _) = tungstenite::connect(&url).unwrap();
+    ///     client.add_connected_relay(&url, SimplifiedWS::from_socket(&url, socket).unwrap()).unwrap();
+    ///     urls.push(url);
+    /// }
+    ///
+    /// let identity = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    /// let group = GroupReference::from_str(&format!("{}'rust", urls[0])).unwrap();
+    /// client.join_group(&identity, &group, None, 0).unwrap();
+    ///
+    /// let (relay, message) = receiver.recv_timeout(Duration::from_secs(1)).unwrap();
+    /// assert_eq!(relay, urls[0]);
+    /// assert!(message.starts_with("[\"EVENT\""));
+    /// assert!(receiver.recv_timeout(Duration::from_millis(200)).is_err());
+    /// ```
+    pub fn join_group(
+        &mut self,
+        signer: &dyn Signer,
+        group: &GroupReference,
+        invite_code: Option<&str>,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP29Error> {
+        self.send_to_group(
+            signer,
+            group,
+            |pub_key: &str| join_request_event(pub_key, &group.id, invite_code),
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Ask to join a group (kind 9021), with the invite code of a closed group
+    ///
+    /// The request is sent to the relay hosting the group only, added to the client if missing
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_join_group() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    ///     client.join_group(&identity, &group, None, 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn join_group(
+        &mut self,
+        signer: &dyn Signer,
+        group: &GroupReference,
+        invite_code: Option<&str>,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP29Error> {
+        self.send_to_group(
+            signer,
+            group,
+            |pub_key: &str| join_request_event(pub_key, &group.id, invite_code),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Ask to leave a group (kind 9022)
+    ///
+    /// The request is sent to the relay hosting the group only, added to the client if missing
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    /// client.leave_group(&identity, &group, 0).unwrap();
+    /// ```
+    pub fn leave_group(
+        &mut self,
+        signer: &dyn Signer,
+        group: &GroupReference,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP29Error> {
+        self.send_to_group(
+            signer,
+            group,
+            |pub_key: &str| leave_request_event(pub_key, &group.id),
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Ask to leave a group (kind 9022)
+    ///
+    /// The request is sent to the relay hosting the group only, added to the client if missing
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_leave_group() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    ///     client.leave_group(&identity, &group, 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn leave_group(
+        &mut self,
+        signer: &dyn Signer,
+        group: &GroupReference,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP29Error> {
+        self.send_to_group(
+            signer,
+            group,
+            |pub_key: &str| leave_request_event(pub_key, &group.id),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Send a chat message (kind 9) to a group
+    ///
+    /// The message is sent to the relay hosting the group only, added to the client if missing
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    /// client.send_group_message(&identity, &group, "Hello Rustaceans!", 0).unwrap();
+    /// ```
+    pub fn send_group_message(
+        &mut self,
+        signer: &dyn Signer,
+        group: &GroupReference,
+        content: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP29Error> {
+        self.send_to_group(
+            signer,
+            group,
+            |pub_key: &str| group_message_event(pub_key, &group.id, content),
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Send a chat message (kind 9) to a group
+    ///
+    /// The message is sent to the relay hosting the group only, added to the client if missing
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_send_group_message() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    ///     client.send_group_message(&identity, &group, "Hello Rustaceans!", 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn send_group_message(
+        &mut self,
+        signer: &dyn Signer,
+        group: &GroupReference,
+        content: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP29Error> {
+        self.send_to_group(
+            signer,
+            group,
+            |pub_key: &str| group_message_event(pub_key, &group.id, content),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get the chat messages of a group published since the timestamp, sorted oldest first
+    ///
+    /// The messages are read from the relay hosting the group only, added to the client if missing
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference, utils::get_timestamp};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    /// let messages = client.get_group_messages(&group, Some(get_timestamp() - 3600)).unwrap();
+    /// ```
+    pub fn get_group_messages(
+        &mut self,
+        group: &GroupReference,
+        since: Option<u64>,
+    ) -> Result<Vec<Event>, NIP29Error> {
+        validate_group_id(&group.id)?;
+        let events = self.get_group_events(group, messages_filter(&group.id, since))?;
+
+        Ok(group_messages(&events, &group.id))
+    }
+
+    #[cfg(feature = "async")]
+    /// Get the chat messages of a group published since the timestamp, sorted oldest first
+    ///
+    /// The messages are read from the relay hosting the group only, added to the client if missing
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference, utils::get_timestamp};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_get_group_messages() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    ///     let messages = client.get_group_messages(&group, Some(get_timestamp() - 3600)).await.unwrap();
+    /// }
+    /// ```
+    pub async fn get_group_messages(
+        &mut self,
+        group: &GroupReference,
+        since: Option<u64>,
+    ) -> Result<Vec<Event>, NIP29Error> {
+        validate_group_id(&group.id)?;
+        let events = self
+            .get_group_events(group, messages_filter(&group.id, since))
+            .await?;
+
+        Ok(group_messages(&events, &group.id))
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get the metadata of a group, None if the relay hosting the group doesn't know it
+    ///
+    /// Only the metadata signed by the relay hosting the group is read: its public key is taken
+    /// from its relay information document (NIP-11), `NIP29Error::MissingRelayPubkey` if it has
+    /// none
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    /// let metadata = client.get_group_metadata(&group).unwrap();
+    /// ```
+    pub fn get_group_metadata(
+        &mut self,
+        group: &GroupReference,
+    ) -> Result<Option<GroupMetadata>, NIP29Error> {
+        validate_group_id(&group.id)?;
+        let relay_pubkey = self.group_relay_pubkey(group)?;
+        let events = self.get_group_events(group, metadata_filter(&group.id, &relay_pubkey))?;
+
+        Ok(find_metadata(events, &group.id, &relay_pubkey))
+    }
+
+    #[cfg(feature = "async")]
+    /// Get the metadata of a group, None if the relay hosting the group doesn't know it
+    ///
+    /// Only the metadata signed by the relay hosting the group is read: its public key is taken
+    /// from its relay information document (NIP-11), `NIP29Error::MissingRelayPubkey` if it has
+    /// none
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, nips::nip29::GroupReference};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_get_group_metadata() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let group = GroupReference::from_str(&format!("{}'rust", env!("RELAY_URL"))).unwrap();
+    ///
+    ///     let metadata = client.get_group_metadata(&group).await.unwrap();
+    /// }
+    /// ```
+    pub async fn get_group_metadata(
+        &mut self,
+        group: &GroupReference,
+    ) -> Result<Option<GroupMetadata>, NIP29Error> {
+        validate_group_id(&group.id)?;
+        let relay_pubkey = self.group_relay_pubkey(group).await?;
+        let events = self
+            .get_group_events(group, metadata_filter(&group.id, &relay_pubkey))
+            .await?;
+
+        Ok(find_metadata(events, &group.id, &relay_pubkey))
+    }
+}
@@ -0,0 +1,135 @@
+use crate::events::Event;
+use crate::utils::tag_value;
+use thiserror::Error;
+
+// Implementation of the NIP30 protocol
+// https://github.com/nostr-protocol/nips/blob/master/30.md
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum NIP30Error {
+    #[error(
+        "Invalid emoji shortcode \"{}\", only letters, digits and underscores are allowed",
+        _0
+    )]
+    InvalidShortcode(String),
+}
+
+/// Check that a shortcode only has alphanumeric characters and underscores
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip30::{validate_shortcode, NIP30Error};
+///
+/// assert!(validate_shortcode("soapbox_42").is_ok());
+/// assert_eq!(validate_shortcode("soap box"), Err(NIP30Error::InvalidShortcode("soap box".to_string())));
+/// assert!(validate_shortcode("").is_err());
+/// assert!(validate_shortcode("émoji").is_err());
+/// ```
+pub fn validate_shortcode(shortcode: &str) -> Result<(), NIP30Error> {
+    if shortcode.is_empty()
+        || !shortcode
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(NIP30Error::InvalidShortcode(shortcode.to_string()));
+    }
+
+    Ok(())
+}
+
+/// Build an emoji tag: ["emoji", shortcode, image url]
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip30::emoji_tag;
+///
+/// assert_eq!(
+///     emoji_tag("soapbox", "https://example.com/soapbox.png").unwrap(),
+///     vec!["emoji", "soapbox", "https://example.com/soapbox.png"]
+/// );
+/// assert!(emoji_tag(":soapbox:", "https://example.com/soapbox.png").is_err());
+/// ```
+pub fn emoji_tag(shortcode: &str, image_url: &str) -> Result<Vec<String>, NIP30Error> {
+    validate_shortcode(shortcode)?;
+
+    Ok(vec![
+        "emoji".to_string(),
+        shortcode.to_string(),
+        image_url.to_string(),
+    ])
+}
+
+/// Get the image url of a shortcode from the emoji tags of an event
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::Event, nips::nip30::emoji_url};
+///
+/// let event = Event {
+///   id: String::new(),
+///   pub_key: String::new(),
+///   created_at: 0,
+///   kind: 7,
+///   tags: vec![vec!["emoji".to_string(), "soapbox".to_string(), "https://example.com/soapbox.png".to_string()]],
+///   content: ":soapbox:".to_string(),
+///   sig: String::new(),
+/// };
+///
+/// assert_eq!(emoji_url(&event, "soapbox"), Some("https://example.com/soapbox.png"));
+/// assert_eq!(emoji_url(&event, "ditto"), None);
+/// ```
+pub fn emoji_url<'a>(event: &'a Event, shortcode: &str) -> Option<&'a str> {
+    event
+        .tags_by_name("emoji")
+        .find(|tag| tag_value(tag, 1) == Some(shortcode))
+        .and_then(|tag| tag_value(tag, 2))
+}
+
+/// Get the custom emojis of the content of an event, in order of appearance: each `:shortcode:`
+/// with an emoji tag, along with its image url
+///
+/// The shortcodes without emoji tag are left as text
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::Event, nips::nip30::content_emojis};
+///
+/// let event = Event {
+///   id: String::new(),
+///   pub_key: String::new(),
+///   created_at: 0,
+///   kind: 1,
+///   tags: vec![
+///     vec!["emoji".to_string(), "soapbox".to_string(), "https://example.com/soapbox.png".to_string()],
+///     vec!["emoji".to_string(), "ditto".to_string(), "https://example.com/ditto.png".to_string()],
+///   ],
+///   content: "Hello :ditto: and :unknown: :soapbox::ditto:".to_string(),
+///   sig: String::new(),
+/// };
+///
+/// assert_eq!(content_emojis(&event), vec![
+///     ("ditto", "https://example.com/ditto.png"),
+///     ("soapbox", "https://example.com/soapbox.png"),
+///     ("ditto", "https://example.com/ditto.png"),
+/// ]);
+/// ```
+pub fn content_emojis(event: &Event) -> Vec<(&str, &str)> {
+    let mut emojis: Vec<(&str, &str)> = Vec::new();
+    let mut rest = event.content.as_str();
+
+    while let Some(start) = rest.find(':') {
+        let after = &rest[start + 1..];
+
+        let Some(end) = after.find(':') else {
+            break;
+        };
+
+        let shortcode = &after[..end];
+        match emoji_url(event, shortcode) {
+            Some(url) if validate_shortcode(shortcode).is_ok() => {
+                emojis.push((shortcode, url));
+                rest = &after[end + 1..];
+            }
+            // The closing colon may open the next shortcode
+            _ => rest = after,
+        }
+    }
+
+    emojis
+}
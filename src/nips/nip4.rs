@@ -4,27 +4,43 @@
 // Thanks to Yuki Kishimoto for the inspiration with his module
 // https://gitlab.com/p2kishimoto/nostr-rs-sdk/-/tree/master/crates/nostr-sdk-base
 
-use crate::bech32::auto_bech32_to_hex;
-use crate::events::{Event, EventPrepare};
-use crate::nostr_client::Client;
-use crate::req::ReqFilter;
-use crate::utils::get_timestamp;
-use crate::Identity;
+#[cfg(feature = "net")]
+use crate::{
+    bech32::auto_bech32_to_hex,
+    events::{Event, EventPrepare},
+    nostr_client::{Client, ClientError},
+    req::ReqFilter,
+    utils::get_timestamp,
+    Identity,
+};
 use aes::{
-    cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit},
+    cipher::{
+        block_padding::Pkcs7, generic_array::GenericArray, BlockDecryptMut, BlockEncryptMut,
+        KeyIvInit,
+    },
     Aes256,
 };
-use base64::Engine;
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
 use cbc::{Decryptor, Encryptor};
 use secp256k1::{ecdh, rand::random, PublicKey, SecretKey, XOnlyPublicKey};
 use serde::{Deserialize, Serialize};
 use std::convert::From;
+use std::io::Write;
 use std::str::FromStr;
 use thiserror::Error;
 
 type Aes256CbcEnc = Encryptor<Aes256>;
 type Aes256CbcDec = Decryptor<Aes256>;
 
+/// Size of the AES blocks
+const BLOCK_LEN: usize = 16;
+/// Number of base64 characters decoded at once by `decrypt_to_writer`, 6144 bytes (384 blocks)
+const STREAM_CHUNK_LEN: usize = 8192;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PrivateMessage {
     pub author: String,
@@ -32,7 +48,7 @@ pub struct PrivateMessage {
     pub timestamp: u64,
 }
 
-#[derive(Error, Debug, Eq, PartialEq)]
+#[derive(Error, Debug)]
 pub enum Error {
     #[error(
         r#"Invalid content format. Expected format "<encrypted_text>?iv=<initialization_vec>""#
@@ -56,6 +72,28 @@ pub enum Error {
 
     #[error("Error when decrypting the message")]
     DecryptionError,
+
+    #[error("Error while writing the decrypted message: {}", _0)]
+    WriteError(String),
+
+    #[cfg(feature = "net")]
+    #[error("The client has an error: {}", _0)]
+    ClientError(#[from] ClientError),
+}
+
+/// Decode base64 with the standard alphabet, then without padding, then with the url-safe alphabet,
+/// since other clients don't all encode the same way
+fn decode_base64(input: &str) -> Result<Vec<u8>, Error> {
+    use base64::prelude::{
+        BASE64_STANDARD, BASE64_STANDARD_NO_PAD, BASE64_URL_SAFE, BASE64_URL_SAFE_NO_PAD,
+    };
+
+    BASE64_STANDARD
+        .decode(input)
+        .or_else(|_| BASE64_STANDARD_NO_PAD.decode(input))
+        .or_else(|_| BASE64_URL_SAFE.decode(input))
+        .or_else(|_| BASE64_URL_SAFE_NO_PAD.decode(input))
+        .map_err(|_| Error::Base64DecodeError)
 }
 
 /// Decrypt a private message
@@ -79,47 +117,177 @@ pub enum Error {
 ///
 /// let message = nip4::encrypt(&system_sec_key, &sender_pub_key, event_content).unwrap();
 /// let message = nip4::decrypt(&system_sec_key, &sender_pub_key, &message).unwrap();
+///
+/// // Errors are reported without panicking
+/// assert!(matches!(nip4::decrypt(&system_sec_key, &sender_pub_key, "hello"), Err(nip4::Error::InvalidContentFormat)));
+/// assert!(matches!(nip4::decrypt(&system_sec_key, &sender_pub_key, "!!!?iv=!!!"), Err(nip4::Error::Base64DecodeError)));
+/// assert!(matches!(nip4::decrypt(&system_sec_key, &sender_pub_key, "AAAA?iv=AAECAwQFBgcICQoLDA0ODw=="), Err(nip4::Error::WrongBlockMode)));
+///
+/// // Url-safe and unpadded base64 from other clients are accepted
+/// let message = nip4::encrypt(&system_sec_key, &sender_pub_key, event_content).unwrap();
+/// let to_url_safe = |part: &str| part.replace('+', "-").replace('/', "_").replace('=', "");
+/// let (content, iv) = message.split_once("?iv=").unwrap();
+/// let url_safe = format!("{}?iv={}", to_url_safe(content), to_url_safe(iv));
+/// assert_eq!(nip4::decrypt(&system_sec_key, &sender_pub_key, &url_safe).unwrap(), event_content);
+///
+/// // Wrong key
+/// let sk = secp256k1::SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000003").unwrap();
+/// let pk = XOnlyPublicKey::from_str("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5").unwrap();
+/// assert!(matches!(nip4::decrypt(&sk, &pk, "HXYghI1ehpkYm1rAuLRzbw==?iv=AAECAwQFBgcICQoLDA0ODw=="), Err(nip4::Error::DecryptionError)));
 /// ```
 pub fn decrypt(
     sk: &SecretKey,
     pk: &XOnlyPublicKey,
     encrypted_content: &str,
 ) -> Result<String, Error> {
+    let mut plaintext: Vec<u8> = Vec::new();
+    decrypt_to_writer(sk, pk, encrypted_content, &mut plaintext)?;
+
+    String::from_utf8(plaintext).map_err(|_| Error::Utf8EncodeError)
+}
+
+/// Decrypt a private message into a writer, chunk by chunk, and return the number of bytes written
+///
+/// The decoded ciphertext is never held in memory at once, which matters for the large messages
+/// (e.g. embedding base64 media). The plaintext is written as is, without checking that it is
+/// UTF-8. The padding is only checked at the end: after an error, the writer may have received
+/// a part of a message decrypted with a wrong key.
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip4;
+/// use secp256k1::{SecretKey, XOnlyPublicKey};
+/// use std::str::FromStr;
+///
+/// let sk = SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+/// let pk = XOnlyPublicKey::from_str("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5").unwrap();
+///
+/// let attachment = "a".repeat(100_000);
+/// let message = nip4::encrypt(&sk, &pk, &attachment).unwrap();
+///
+/// let mut file: Vec<u8> = Vec::new();
+/// assert_eq!(nip4::decrypt_to_writer(&sk, &pk, &message, &mut file).unwrap(), 100_000);
+/// assert_eq!(file, attachment.as_bytes());
+///
+/// let mut file: Vec<u8> = Vec::new();
+/// assert!(matches!(nip4::decrypt_to_writer(&sk, &pk, "AAAA?iv=AAECAwQFBgcICQoLDA0ODw==", &mut file), Err(nip4::Error::WrongBlockMode)));
+/// ```
+pub fn decrypt_to_writer<W: Write>(
+    sk: &SecretKey,
+    pk: &XOnlyPublicKey,
+    encrypted_content: &str,
+    mut writer: W,
+) -> Result<usize, Error> {
     let parsed_content: Vec<&str> = encrypted_content.split("?iv=").collect();
-    if parsed_content.len() != 2 {
+    let [content, iv] = parsed_content[..] else {
         return Err(Error::InvalidContentFormat);
+    };
+
+    let iv: Vec<u8> = decode_base64(iv)?;
+    let key: Vec<u8> = generate_shared_key(sk, pk)?;
+
+    if key.len() != 32 || iv.len() != BLOCK_LEN {
+        return Err(Error::Base64DecodeError);
     }
 
-    let mut encrypted_content: Vec<u8> = base64::prelude::BASE64_STANDARD
-        .decode(parsed_content[0])
-        .unwrap()
-        .to_vec();
+    let engine = chunk_engine(content);
+    let mut cipher = Aes256CbcDec::new(key.as_slice().into(), iv.as_slice().into());
+    let write = |writer: &mut W, bytes: &[u8]| {
+        writer
+            .write_all(bytes)
+            .map_err(|error| Error::WriteError(error.to_string()))
+    };
+
+    // The last block is held back until the end to remove the padding
+    let mut last_block: Option<[u8; BLOCK_LEN]> = None;
+    let mut written = 0;
+
+    for chunk in content.as_bytes().chunks(STREAM_CHUNK_LEN) {
+        let mut chunk = engine.decode(chunk).map_err(|_| Error::Base64DecodeError)?;
+
+        // CBC mode only works on whole blocks, only the last chunk may not be a whole number of
+        // blocks, hence an invalid message
+        if chunk.len() % BLOCK_LEN != 0 {
+            return Err(Error::WrongBlockMode);
+        }
 
-    let iv: Vec<u8> = base64::prelude::BASE64_STANDARD
-        .decode(parsed_content[1])
-        .unwrap()
-        .to_vec();
-    let key: Vec<u8> = generate_shared_key(sk, pk)?;
+        for block in chunk.chunks_exact_mut(BLOCK_LEN) {
+            cipher.decrypt_block_mut(GenericArray::from_mut_slice(block));
 
-    let key = key.as_slice().try_into();
-    let iv = iv.as_slice().try_into();
+            if let Some(previous) = last_block {
+                write(&mut writer, &previous)?;
+                written += BLOCK_LEN;
+            }
+            last_block = block.try_into().ok();
+        }
+    }
 
-    if key.is_err() || iv.is_err() {
-        return Err(Error::Base64DecodeError);
+    let Some(last_block) = last_block else {
+        return Err(Error::WrongBlockMode);
+    };
+
+    // With whole blocks, an invalid PKCS#7 padding means a wrong key or a corrupted message
+    let padding = last_block[BLOCK_LEN - 1] as usize;
+    if !(1..=BLOCK_LEN).contains(&padding)
+        || last_block[BLOCK_LEN - padding..]
+            .iter()
+            .any(|byte| *byte as usize != padding)
+    {
+        return Err(Error::DecryptionError);
     }
 
-    let cipher = Aes256CbcDec::new(key.unwrap(), iv.unwrap());
+    write(&mut writer, &last_block[..BLOCK_LEN - padding])?;
+    writer
+        .flush()
+        .map_err(|error| Error::WriteError(error.to_string()))?;
 
-    let result = cipher
-        .decrypt_padded_mut::<Pkcs7>(&mut encrypted_content)
-        .map_err(|_| Error::WrongBlockMode)?;
+    Ok(written + BLOCK_LEN - padding)
+}
 
-    String::from_utf8(result.try_into().unwrap()).map_err(|_| Error::Utf8EncodeError)
+/// Base64 engine decoding the chunks of `decrypt_to_writer`, with or without padding, with the
+/// url-safe alphabet if the content uses it (see `decode_base64`)
+fn chunk_engine(content: &str) -> GeneralPurpose {
+    let alphabet = if content.contains(['-', '_']) {
+        &alphabet::URL_SAFE
+    } else {
+        &alphabet::STANDARD
+    };
+
+    GeneralPurpose::new(
+        alphabet,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    )
 }
 
+/// Encrypt a private message with a random initialization vector
 pub fn encrypt(sk: &SecretKey, pk: &XOnlyPublicKey, text: &str) -> Result<String, Error> {
+    encrypt_with_iv(sk, pk, text, random())
+}
+
+/// Encrypt a private message with the given initialization vector
+///
+/// The IV must be random and never reused, use `encrypt` unless you need a deterministic output (tests)
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip4;
+/// use secp256k1::{SecretKey, XOnlyPublicKey};
+/// use std::str::FromStr;
+///
+/// let sk = SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+/// let pk = XOnlyPublicKey::from_str("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5").unwrap();
+/// let iv = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+///
+/// let message = nip4::encrypt_with_iv(&sk, &pk, "hello world!", iv).unwrap();
+/// assert_eq!(message, "HXYghI1ehpkYm1rAuLRzbw==?iv=AAECAwQFBgcICQoLDA0ODw==");
+/// assert_eq!(nip4::decrypt(&sk, &pk, &message).unwrap(), "hello world!");
+/// ```
+pub fn encrypt_with_iv(
+    sk: &SecretKey,
+    pk: &XOnlyPublicKey,
+    text: &str,
+    iv: [u8; 16],
+) -> Result<String, Error> {
     let key: Vec<u8> = generate_shared_key(sk, pk)?;
-    let iv: [u8; 16] = random();
 
     let cipher = Aes256CbcEnc::new(key.as_slice().into(), &iv.into());
     let result: Vec<u8> = cipher.encrypt_padded_vec_mut::<Pkcs7>(text.as_bytes());
@@ -147,6 +315,24 @@ fn from_schnorr_pk(schnorr_pk: &XOnlyPublicKey) -> Result<PublicKey, Error> {
     Ok(PublicKey::from_str(&pk)?)
 }
 
+/// Encrypted direct message (kind 4) to `pubkey`
+#[cfg(feature = "net")]
+fn private_message_event(
+    pub_key: &str,
+    secret_key: &SecretKey,
+    pubkey: &XOnlyPublicKey,
+    message: &str,
+) -> Result<EventPrepare, Error> {
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: 4,
+        tags: vec![vec!["p".to_string(), pubkey.to_string()]],
+        content: encrypt(secret_key, pubkey, message)?,
+    })
+}
+
+#[cfg(feature = "net")]
 impl Client {
     #[cfg(not(feature = "async"))]
     /// Send private message to a public key
@@ -170,19 +356,36 @@ impl Client {
     ) -> Result<Event, Error> {
         let hex_pubkey = auto_bech32_to_hex(pubkey)?;
         let x_pub_key = secp256k1::XOnlyPublicKey::from_str(&hex_pubkey)?;
-        let encrypted_message = encrypt(&identity.secret_key, &x_pub_key, message)?;
-
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 4,
-            tags: vec![vec!["p".to_string(), hex_pubkey]],
-            content: encrypted_message,
-        }
-        .to_event(identity, difficulty_target);
 
-        self.publish_event(&event).unwrap();
-        Ok(event)
+        self.send_private_message_pk(identity, &x_pub_key, message, difficulty_target)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Send private message to an already validated public key
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use secp256k1::XOnlyPublicKey;
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let pubkey = XOnlyPublicKey::from_str("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").unwrap();
+    ///
+    /// client.send_private_message_pk(&identity, &pubkey, "Hello from Rust Nostr Client!", 0).unwrap();
+    /// ```
+    pub fn send_private_message_pk(
+        &mut self,
+        identity: &Identity,
+        pubkey: &XOnlyPublicKey,
+        message: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, Error> {
+        self.send_template(
+            identity,
+            |pub_key: &str| private_message_event(pub_key, &identity.secret_key, pubkey, message),
+            difficulty_target,
+        )
     }
 
     #[cfg(feature = "async")]
@@ -210,19 +413,41 @@ impl Client {
     ) -> Result<Event, Error> {
         let hex_pubkey = auto_bech32_to_hex(pubkey)?;
         let x_pub_key = secp256k1::XOnlyPublicKey::from_str(&hex_pubkey)?;
-        let encrypted_message = encrypt(&identity.secret_key, &x_pub_key, message)?;
-
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 4,
-            tags: vec![vec!["p".to_string(), hex_pubkey.to_string()]],
-            content: encrypted_message,
-        }
-        .to_event(identity, difficulty_target);
 
-        self.publish_event(&event).await.unwrap();
-        Ok(event)
+        self.send_private_message_pk(identity, &x_pub_key, message, difficulty_target)
+            .await
+    }
+
+    #[cfg(feature = "async")]
+    /// Send private message to an already validated public key
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use secp256k1::XOnlyPublicKey;
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_send_private_message_pk() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     let pubkey = XOnlyPublicKey::from_str("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").unwrap();
+    ///     client.send_private_message_pk(&identity, &pubkey, "Hello from Rust Nostr Client!", 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn send_private_message_pk(
+        &mut self,
+        identity: &Identity,
+        pubkey: &XOnlyPublicKey,
+        message: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, Error> {
+        self.send_template(
+            identity,
+            |pub_key: &str| private_message_event(pub_key, &identity.secret_key, pubkey, message),
+            difficulty_target,
+        )
+        .await
     }
 
     #[cfg(not(feature = "async"))]
@@ -253,6 +478,8 @@ impl Client {
                     kinds: Some(vec![4]),
                     e: None,
                     p: Some(vec![hex_pubkey.to_string()]),
+                    d: None,
+                    h: None,
                     since: None,
                     until: None,
                     limit: Some(limit),
@@ -263,6 +490,8 @@ impl Client {
                     kinds: Some(vec![4]),
                     e: None,
                     p: Some(vec![identity.public_key_str.clone()]),
+                    d: None,
+                    h: None,
                     since: None,
                     until: None,
                     limit: Some(limit),
@@ -305,6 +534,8 @@ impl Client {
                     kinds: Some(vec![4]),
                     e: None,
                     p: Some(vec![hex_pubkey.to_string()]),
+                    d: None,
+                    h: None,
                     since: None,
                     until: None,
                     limit: Some(limit),
@@ -315,6 +546,8 @@ impl Client {
                     kinds: Some(vec![4]),
                     e: None,
                     p: Some(vec![identity.public_key_str.clone()]),
+                    d: None,
+                    h: None,
                     since: None,
                     until: None,
                     limit: Some(limit),
@@ -368,7 +601,7 @@ impl Client {
         }
 
         // Sort messages by timestamp
-        messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        messages.sort_by_key(|message| message.timestamp);
 
         // Reverse order
         messages.reverse();
@@ -423,7 +656,7 @@ impl Client {
         }
 
         // Sort messages by timestamp
-        messages.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        messages.sort_by_key(|message| message.timestamp);
 
         // Reverse order
         messages.reverse();
@@ -431,5 +664,95 @@ impl Client {
         Ok(messages)
     }
 
+    #[cfg(not(feature = "async"))]
+    /// Get the private messages received by the identity from every author, newest first
+    ///
+    /// The messages which can't be decrypted (not meant for the identity or malformed) are skipped
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let messages = client.get_received_dms(&identity, 10).unwrap();
+    /// ```
+    pub fn get_received_dms(
+        &mut self,
+        identity: &Identity,
+        limit: u64,
+    ) -> Result<Vec<PrivateMessage>, Error> {
+        let events = self.get_events_of(vec![received_dms_filter(identity, limit)])?;
+
+        Ok(received_messages(identity, events))
+    }
+
+    #[cfg(feature = "async")]
+    /// Get the private messages received by the identity from every author, newest first
+    ///
+    /// The messages which can't be decrypted (not meant for the identity or malformed) are skipped
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_get_received_dms() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     let messages = client.get_received_dms(&identity, 10).await.unwrap();
+    /// }
+    /// ```
+    pub async fn get_received_dms(
+        &mut self,
+        identity: &Identity,
+        limit: u64,
+    ) -> Result<Vec<PrivateMessage>, Error> {
+        let events = self
+            .get_events_of(vec![received_dms_filter(identity, limit)])
+            .await?;
+
+        Ok(received_messages(identity, events))
+    }
+
     // TODO: get a list of private messages with a list of public keys
 }
+
+/// Private messages of every author tagging the identity
+#[cfg(feature = "net")]
+fn received_dms_filter(identity: &Identity, limit: u64) -> ReqFilter {
+    ReqFilter {
+        ids: None,
+        authors: None,
+        kinds: Some(vec![4]),
+        e: None,
+        p: Some(vec![identity.public_key_str.clone()]),
+        d: None,
+        h: None,
+        since: None,
+        until: None,
+        limit: Some(limit),
+    }
+}
+
+/// Decrypt the private messages received by the identity, newest first
+#[cfg(feature = "net")]
+fn received_messages(identity: &Identity, events: Vec<Event>) -> Vec<PrivateMessage> {
+    let mut messages: Vec<PrivateMessage> = events
+        .into_iter()
+        .filter(|event| event.kind == 4)
+        .filter_map(|event| {
+            let author = XOnlyPublicKey::from_str(&event.pub_key).ok()?;
+            let content = decrypt(&identity.secret_key, &author, &event.content).ok()?;
+
+            Some(PrivateMessage {
+                author: event.pub_key,
+                content,
+                timestamp: event.created_at,
+            })
+        })
+        .collect();
+
+    messages.sort_by_key(|message| std::cmp::Reverse(message.timestamp));
+
+    messages
+}
@@ -0,0 +1,245 @@
+// Implementation of the NIP44 protocol (version 2)
+// https://github.com/nostr-protocol/nips/blob/master/44.md
+
+use base64::Engine;
+use chacha20::{
+    cipher::{KeyIvInit, StreamCipher},
+    ChaCha20,
+};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use secp256k1::{ecdh, rand::random, Parity, SecretKey, XOnlyPublicKey};
+use sha2::Sha256;
+use thiserror::Error;
+
+const VERSION: u8 = 2;
+const SALT: &[u8] = b"nip44-v2";
+const MIN_PLAINTEXT_LEN: usize = 1;
+const MAX_PLAINTEXT_LEN: usize = 65535;
+/// Bounds of a base64 payload and of the decoded payload (version, nonce, ciphertext and mac)
+const MIN_PAYLOAD_LEN: usize = 132;
+const MAX_PAYLOAD_LEN: usize = 87472;
+const MIN_DECODED_LEN: usize = 99;
+const MAX_DECODED_LEN: usize = 65603;
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum NIP44Error {
+    #[error("The plaintext must be between 1 and 65535 bytes")]
+    InvalidPlaintextLength,
+
+    #[error("Unknown encryption version")]
+    UnknownVersion,
+
+    #[error("The payload is invalid")]
+    InvalidPayload,
+
+    #[error("The message authentication code is invalid")]
+    InvalidMac,
+
+    #[error("The padding is invalid")]
+    InvalidPadding,
+
+    #[error("The decrypted message is not UTF-8")]
+    Utf8DecodeError,
+}
+
+/// Keys of a message, derived from the conversation key and the nonce
+struct MessageKeys {
+    chacha_key: [u8; 32],
+    chacha_nonce: [u8; 12],
+    hmac_key: [u8; 32],
+}
+
+impl MessageKeys {
+    fn new(conversation_key: &[u8; 32], nonce: &[u8; 32]) -> Self {
+        let mut keys = [0u8; 76];
+        Hkdf::<Sha256>::from_prk(conversation_key)
+            .expect("the conversation key is a sha256 output")
+            .expand(nonce, &mut keys)
+            .expect("76 bytes is a valid length for HKDF-SHA256");
+
+        let mut message_keys = Self {
+            chacha_key: [0u8; 32],
+            chacha_nonce: [0u8; 12],
+            hmac_key: [0u8; 32],
+        };
+        message_keys.chacha_key.copy_from_slice(&keys[..32]);
+        message_keys.chacha_nonce.copy_from_slice(&keys[32..44]);
+        message_keys.hmac_key.copy_from_slice(&keys[44..]);
+
+        message_keys
+    }
+
+    fn apply_keystream(&self, data: &mut [u8]) {
+        ChaCha20::new(&self.chacha_key.into(), &self.chacha_nonce.into()).apply_keystream(data);
+    }
+
+    fn mac(&self, nonce: &[u8; 32], ciphertext: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.hmac_key).expect("HMAC accepts keys of any size");
+        mac.update(nonce);
+        mac.update(ciphertext);
+
+        mac
+    }
+}
+
+/// Get the conversation key of two users, the same from both sides
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip44::get_conversation_key;
+/// use secp256k1::{SecretKey, XOnlyPublicKey};
+/// use std::str::FromStr;
+///
+/// let sk = SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+/// let pk = XOnlyPublicKey::from_str("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5").unwrap();
+///
+/// let conversation_key = get_conversation_key(&sk, &pk);
+/// assert_eq!(hex::encode(conversation_key), "c41c775356fd92eadc63ff5a0dc1da211b268cbea22316767095b2871ea1412d");
+/// ```
+pub fn get_conversation_key(sk: &SecretKey, pk: &XOnlyPublicKey) -> [u8; 32] {
+    let shared_point = ecdh::shared_secret_point(&pk.public_key(Parity::Even), sk);
+    let (conversation_key, _) = Hkdf::<Sha256>::extract(Some(SALT), &shared_point[..32]);
+
+    conversation_key.into()
+}
+
+/// Length of the padded plaintext: 32 bytes minimum, then chunks growing with the length
+fn padded_len(len: usize) -> usize {
+    if len <= 32 {
+        return 32;
+    }
+
+    let next_power = 1 << (usize::BITS - (len - 1).leading_zeros());
+    let chunk = if next_power <= 256 {
+        32
+    } else {
+        next_power / 8
+    };
+
+    chunk * ((len - 1) / chunk + 1)
+}
+
+/// Encrypt a message for a public key
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip44;
+/// use secp256k1::{SecretKey, XOnlyPublicKey};
+/// use std::str::FromStr;
+///
+/// let sk1 = SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+/// let pk1 = XOnlyPublicKey::from_str("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798").unwrap();
+/// let sk2 = SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000002").unwrap();
+/// let pk2 = XOnlyPublicKey::from_str("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5").unwrap();
+///
+/// let payload = nip44::encrypt(&sk1, &pk2, "Hello Nostr!").unwrap();
+/// assert_eq!(nip44::decrypt(&sk2, &pk1, &payload), Ok("Hello Nostr!".to_string()));
+/// assert_eq!(nip44::encrypt(&sk1, &pk2, ""), Err(nip44::NIP44Error::InvalidPlaintextLength));
+/// ```
+pub fn encrypt(sk: &SecretKey, pk: &XOnlyPublicKey, text: &str) -> Result<String, NIP44Error> {
+    encrypt_with_nonce(sk, pk, text, &random())
+}
+
+/// Encrypt a message for a public key with the given nonce, which must be random and never reused
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip44;
+/// use secp256k1::{SecretKey, XOnlyPublicKey};
+/// use std::str::FromStr;
+///
+/// let sk = SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+/// let pk = XOnlyPublicKey::from_str("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5").unwrap();
+/// let mut nonce = [0u8; 32];
+/// nonce[31] = 1;
+///
+/// assert_eq!(
+///     nip44::encrypt_with_nonce(&sk, &pk, "a", &nonce).unwrap(),
+///     "AgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABee0G5VSK0/9YypIObAtDKfYEAjD35uVkHyB0F4DwrcNaCXlCWZKaArsGrY6M9wnuTMxWfp1RTN9Xga8no+kF5Vsb"
+/// );
+/// ```
+pub fn encrypt_with_nonce(
+    sk: &SecretKey,
+    pk: &XOnlyPublicKey,
+    text: &str,
+    nonce: &[u8; 32],
+) -> Result<String, NIP44Error> {
+    let plaintext = text.as_bytes();
+
+    if !(MIN_PLAINTEXT_LEN..=MAX_PLAINTEXT_LEN).contains(&plaintext.len()) {
+        return Err(NIP44Error::InvalidPlaintextLength);
+    }
+
+    let keys = MessageKeys::new(&get_conversation_key(sk, pk), nonce);
+
+    // Length prefix (u16 big-endian), plaintext, then zeros
+    let mut ciphertext = vec![0u8; 2 + padded_len(plaintext.len())];
+    ciphertext[..2].copy_from_slice(&(plaintext.len() as u16).to_be_bytes());
+    ciphertext[2..2 + plaintext.len()].copy_from_slice(plaintext);
+    keys.apply_keystream(&mut ciphertext);
+
+    let mac = keys.mac(nonce, &ciphertext).finalize().into_bytes();
+
+    let mut payload = Vec::with_capacity(1 + nonce.len() + ciphertext.len() + mac.len());
+    payload.push(VERSION);
+    payload.extend_from_slice(nonce);
+    payload.extend_from_slice(&ciphertext);
+    payload.extend_from_slice(&mac);
+
+    Ok(base64::engine::general_purpose::STANDARD.encode(payload))
+}
+
+/// Decrypt a message of a public key
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip44;
+/// use secp256k1::{SecretKey, XOnlyPublicKey};
+/// use std::str::FromStr;
+///
+/// let sk = SecretKey::from_str("0000000000000000000000000000000000000000000000000000000000000002").unwrap();
+/// let pk = XOnlyPublicKey::from_str("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798").unwrap();
+/// let payload = "AgAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABee0G5VSK0/9YypIObAtDKfYEAjD35uVkHyB0F4DwrcNaCXlCWZKaArsGrY6M9wnuTMxWfp1RTN9Xga8no+kF5Vsb";
+///
+/// assert_eq!(nip44::decrypt(&sk, &pk, payload), Ok("a".to_string()));
+/// assert_eq!(nip44::decrypt(&sk, &pk, "#invalid"), Err(nip44::NIP44Error::UnknownVersion));
+/// assert_eq!(nip44::decrypt(&sk, &pk, &payload.replace("Vsb", "Vsc")), Err(nip44::NIP44Error::InvalidMac));
+/// ```
+pub fn decrypt(sk: &SecretKey, pk: &XOnlyPublicKey, payload: &str) -> Result<String, NIP44Error> {
+    if payload.starts_with('#') {
+        return Err(NIP44Error::UnknownVersion);
+    }
+
+    if !(MIN_PAYLOAD_LEN..=MAX_PAYLOAD_LEN).contains(&payload.len()) {
+        return Err(NIP44Error::InvalidPayload);
+    }
+
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(payload)
+        .map_err(|_| NIP44Error::InvalidPayload)?;
+
+    if !(MIN_DECODED_LEN..=MAX_DECODED_LEN).contains(&data.len()) {
+        return Err(NIP44Error::InvalidPayload);
+    }
+
+    if data[0] != VERSION {
+        return Err(NIP44Error::UnknownVersion);
+    }
+
+    let mut nonce = [0u8; 32];
+    nonce.copy_from_slice(&data[1..33]);
+    let (ciphertext, mac) = data[33..].split_at(data.len() - 33 - 32);
+
+    let keys = MessageKeys::new(&get_conversation_key(sk, pk), &nonce);
+    keys.mac(&nonce, ciphertext)
+        .verify_slice(mac)
+        .map_err(|_| NIP44Error::InvalidMac)?;
+
+    let mut padded = ciphertext.to_vec();
+    keys.apply_keystream(&mut padded);
+
+    let len = u16::from_be_bytes([padded[0], padded[1]]) as usize;
+    if len < MIN_PLAINTEXT_LEN || padded.len() != 2 + padded_len(len) {
+        return Err(NIP44Error::InvalidPadding);
+    }
+
+    String::from_utf8(padded[2..2 + len].to_vec()).map_err(|_| NIP44Error::Utf8DecodeError)
+}
@@ -0,0 +1,481 @@
+use crate::{
+    events::{Event, EventPrepare},
+    keys,
+    nips::nip4,
+    nostr_client::{Client, ClientError},
+    req::ReqFilter,
+    signer::SignerError,
+    utils::{get_timestamp, random_hash},
+    Identity, Message,
+};
+use secp256k1::XOnlyPublicKey;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use url::Url;
+
+#[cfg(not(feature = "async"))]
+use crate::signer::Signer;
+
+// Implementation of the NIP46 protocol (client side)
+// https://github.com/nostr-protocol/nips/blob/master/46.md
+
+/// Kind of the requests and responses exchanged with a remote signer
+pub const NOSTR_CONNECT_KIND: u16 = 24133;
+
+/// Delay between two reads of the relays while waiting for a response
+const RESPONSE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Error, Debug)]
+pub enum NIP46Error {
+    #[error("The client has an error")]
+    ClientError(ClientError),
+
+    #[error(
+        "Invalid bunker URI \"{}\", the format must be bunker://<pubkey>?relay=<url>",
+        _0
+    )]
+    InvalidBunkerUri(String),
+
+    #[error("Encryption Error: {}", _0)]
+    EncryptionError(#[from] nip4::Error),
+
+    #[error("The remote signer did not answer in time")]
+    Timeout,
+
+    #[error("The remote signer rejected the request: {}", _0)]
+    Rejected(String),
+
+    #[error("The remote signer requires an authentication at {}", _0)]
+    AuthRequired(String),
+
+    #[error("The remote signer sent an invalid response")]
+    InvalidResponse,
+
+    #[error("The event public key {} is not the remote signer public key", _0)]
+    WrongPublicKey(String),
+}
+
+impl From<ClientError> for NIP46Error {
+    fn from(err: ClientError) -> Self {
+        Self::ClientError(err)
+    }
+}
+
+impl From<NIP46Error> for SignerError {
+    fn from(err: NIP46Error) -> Self {
+        match err {
+            NIP46Error::Timeout => Self::Timeout,
+            NIP46Error::Rejected(reason) => Self::Rejected(reason),
+            NIP46Error::WrongPublicKey(pub_key) => Self::WrongPublicKey(pub_key),
+            err => Self::Failed(err.to_string()),
+        }
+    }
+}
+
+/// Connection string of a remote signer: `bunker://<pubkey>?relay=<url>&secret=<secret>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BunkerUri {
+    /// Public key of the remote signer (hex), not necessarily the one signing the events
+    pub remote_pub_key: String,
+    pub relays: Vec<String>,
+    pub secret: Option<String>,
+}
+
+impl FromStr for BunkerUri {
+    type Err = NIP46Error;
+
+    /// Parse a bunker URI
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nips::nip46::BunkerUri;
+    /// use std::str::FromStr;
+    ///
+    /// let uri = BunkerUri::from_str("bunker://884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6?relay=wss://relay.example&secret=abcd").unwrap();
+    /// assert_eq!(uri.remote_pub_key, "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6");
+    /// assert_eq!(uri.relays, vec!["wss://relay.example".to_string()]);
+    /// assert_eq!(uri.secret, Some("abcd".to_string()));
+    ///
+    /// assert!(BunkerUri::from_str("bunker://884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").is_err());
+    /// assert!(BunkerUri::from_str("nostrconnect://884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6?relay=wss://relay.example").is_err());
+    /// ```
+    fn from_str(uri: &str) -> Result<Self, Self::Err> {
+        let invalid = || NIP46Error::InvalidBunkerUri(uri.to_string());
+        let url = Url::parse(uri).map_err(|_| invalid())?;
+
+        if url.scheme() != "bunker" {
+            return Err(invalid());
+        }
+
+        let remote_pub_key = url.host_str().ok_or_else(invalid)?.to_string();
+        XOnlyPublicKey::from_str(&remote_pub_key).map_err(|_| invalid())?;
+
+        let mut relays: Vec<String> = Vec::new();
+        let mut secret: Option<String> = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "relay" => relays.push(value.to_string()),
+                "secret" => secret = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        if relays.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            remote_pub_key,
+            relays,
+            secret,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    id: String,
+    #[serde(default)]
+    result: String,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Client of a remote signer (NIP-46), the requests are sent through the relays of the bunker URI
+///
+/// The secret key of the user stays in the remote signer, a random local key is used to
+/// encrypt the requests (NIP-04). Without the async feature it implements `Signer`, so it can be
+/// given to the client methods in place of an `Identity`.
+///
+/// With the async feature it does not implement `Signer`, whose methods are blocking: sign the
+/// event with `RemoteSigner::sign_event` and send it with `Client::publish_event`.
+pub struct RemoteSigner {
+    #[cfg(not(feature = "async"))]
+    client: std::sync::Mutex<Client>,
+    #[cfg(feature = "async")]
+    client: tokio::sync::Mutex<Client>,
+    local: Identity,
+    remote_pub_key: XOnlyPublicKey,
+    user_pub_key: String,
+    timeout: Duration,
+}
+
+impl RemoteSigner {
+    fn new(client: Client, remote_pub_key: XOnlyPublicKey, timeout: Duration) -> Self {
+        let (secret_key, _) = keys::get_random_secret_key();
+        let public_key_str = keys::get_str_keys_from_secret(&secret_key).1;
+
+        Self {
+            client: client.into(),
+            local: Identity {
+                secret_key,
+                public_key: keys::get_public_key_from_secret(&secret_key),
+                public_key_str: public_key_str.clone(),
+                address: public_key_str,
+            },
+            remote_pub_key,
+            user_pub_key: String::new(),
+            timeout,
+        }
+    }
+
+    /// Public key of the user (hex), which signs the events
+    pub fn user_public_key(&self) -> &str {
+        &self.user_pub_key
+    }
+
+    fn connect_params(uri: &BunkerUri) -> Vec<String> {
+        let mut params = vec![uri.remote_pub_key.clone()];
+        params.extend(uri.secret.clone());
+        params
+    }
+
+    fn request_event(
+        &self,
+        method: &str,
+        params: &[String],
+    ) -> Result<(String, Event), NIP46Error> {
+        let request_id = random_hash();
+        let request = json!({ "id": request_id, "method": method, "params": params }).to_string();
+        let content = nip4::encrypt(&self.local.secret_key, &self.remote_pub_key, &request)?;
+
+        let event = self.local.make_event(
+            NOSTR_CONNECT_KIND,
+            &content,
+            &[vec!["p".to_string(), self.remote_pub_key.to_string()]],
+            0,
+        );
+
+        Ok((request_id, event))
+    }
+
+    fn response_filter(&self) -> ReqFilter {
+        ReqFilter {
+            authors: Some(vec![self.remote_pub_key.to_string()]),
+            kinds: Some(vec![NOSTR_CONNECT_KIND]),
+            p: Some(vec![self.local.public_key_str.clone()]),
+            since: Some(get_timestamp()),
+            ..Default::default()
+        }
+    }
+
+    /// The response to the request if the message is one
+    fn parse_response(
+        &self,
+        subscription_id: &str,
+        request_id: &str,
+        message: &Message,
+    ) -> Option<Result<String, NIP46Error>> {
+        let value: Value = serde_json::from_str(&message.to_string()).ok()?;
+        if value[0] != "EVENT" || value[1].as_str() != Some(subscription_id) {
+            return None;
+        }
+
+        let event: Event = serde_json::from_value(value[2].clone()).ok()?;
+        if event.pub_key != self.remote_pub_key.to_string() || event.verify().is_err() {
+            return None;
+        }
+
+        let content =
+            nip4::decrypt(&self.local.secret_key, &self.remote_pub_key, &event.content).ok()?;
+        let response: Response = serde_json::from_str(&content).ok()?;
+        if response.id != request_id {
+            return None;
+        }
+
+        Some(match response.error.filter(|error| !error.is_empty()) {
+            Some(url) if response.result == "auth_url" => Err(NIP46Error::AuthRequired(url)),
+            Some(error) => Err(NIP46Error::Rejected(error)),
+            None => Ok(response.result),
+        })
+    }
+
+    fn unsigned_event(event: &EventPrepare) -> String {
+        json!({
+            "kind": event.kind,
+            "content": event.content,
+            "tags": event.tags,
+            "created_at": event.created_at,
+        })
+        .to_string()
+    }
+
+    /// Check that the signed event is the requested one, signed by the user
+    fn check_signed_event(&self, event: &EventPrepare, result: &str) -> Result<Event, NIP46Error> {
+        let signed: Event =
+            serde_json::from_str(result).map_err(|_| NIP46Error::InvalidResponse)?;
+
+        if signed.pub_key != self.user_pub_key
+            || signed.kind != event.kind
+            || signed.content != event.content
+            || signed.tags != event.tags
+            || signed.id != signed.get_content_id()
+            || signed.verify().is_err()
+        {
+            return Err(NIP46Error::InvalidResponse);
+        }
+
+        Ok(signed)
+    }
+
+    fn set_user_public_key(&mut self, pub_key: String) -> Result<(), NIP46Error> {
+        XOnlyPublicKey::from_str(&pub_key).map_err(|_| NIP46Error::InvalidResponse)?;
+        self.user_pub_key = pub_key;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Connect to the remote signer of a bunker URI and get the public key of the user
+    ///
+    /// Every request fails with `NIP46Error::Timeout` if the signer doesn't answer in `timeout`
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use nostr_rust::{nips::nip46::RemoteSigner, nostr_client::Client};
+    /// use std::time::Duration;
+    ///
+    /// let uri = format!("bunker://{}?relay={}", env!("PUBLIC_KEY"), env!("RELAY_URL"));
+    /// let signer = RemoteSigner::connect(&uri, Duration::from_secs(30)).unwrap();
+    ///
+    /// // The remote signer is used like an Identity
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// client.publish(&signer, 1, "Signed by my bunker", &[], 0).unwrap();
+    /// ```
+    pub fn connect(uri: &str, timeout: Duration) -> Result<Self, NIP46Error> {
+        let uri = BunkerUri::from_str(uri)?;
+        let remote_pub_key = XOnlyPublicKey::from_str(&uri.remote_pub_key)
+            .map_err(|_| NIP46Error::InvalidBunkerUri(uri.remote_pub_key.clone()))?;
+        let client = Client::new(uri.relays.iter().map(String::as_str).collect())?;
+
+        let mut signer = Self::new(client, remote_pub_key, timeout);
+        signer.request("connect", &Self::connect_params(&uri))?;
+        let user_pub_key = signer.request("get_public_key", &[])?;
+        signer.set_user_public_key(user_pub_key)?;
+
+        Ok(signer)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Send a request to the remote signer and wait for its result
+    pub fn request(&self, method: &str, params: &[String]) -> Result<String, NIP46Error> {
+        let (request_id, event) = self.request_event(method, params)?;
+        let mut client = self.client.lock().unwrap();
+
+        // Subscribe before publishing to not miss a quick response
+        let subscription_id = client.subscribe(vec![self.response_filter()])?;
+        let response = client
+            .publish_event(&event)
+            .map_err(NIP46Error::from)
+            .and_then(|_| self.wait_response(&mut client, &subscription_id, &request_id));
+        client.unsubscribe(&subscription_id)?;
+
+        response
+    }
+
+    #[cfg(not(feature = "async"))]
+    fn wait_response(
+        &self,
+        client: &mut Client,
+        subscription_id: &str,
+        request_id: &str,
+    ) -> Result<String, NIP46Error> {
+        let deadline = Instant::now() + self.timeout;
+
+        while Instant::now() < deadline {
+            let data = client.next_data_all()?;
+
+            if data.is_empty() {
+                std::thread::sleep(RESPONSE_POLL_INTERVAL);
+                continue;
+            }
+
+            for (_, message) in data {
+                if let Some(response) = self.parse_response(subscription_id, request_id, &message) {
+                    return response;
+                }
+            }
+        }
+
+        Err(NIP46Error::Timeout)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Ask the remote signer to sign an event prepared for the user public key
+    pub fn sign_event(&self, event: EventPrepare) -> Result<Event, NIP46Error> {
+        if event.pub_key != self.user_pub_key {
+            return Err(NIP46Error::WrongPublicKey(event.pub_key));
+        }
+
+        let result = self.request("sign_event", &[Self::unsigned_event(&event)])?;
+        self.check_signed_event(&event, &result)
+    }
+
+    #[cfg(feature = "async")]
+    /// Connect to the remote signer of a bunker URI and get the public key of the user
+    ///
+    /// Every request fails with `NIP46Error::Timeout` if the signer doesn't answer in `timeout`
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nips::nip46::RemoteSigner, utils::get_timestamp, events::EventPrepare};
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::test]
+    /// async fn test_connect() {
+    ///     let uri = format!("bunker://{}?relay={}", env!("PUBLIC_KEY"), env!("RELAY_URL"));
+    ///     let signer = RemoteSigner::connect(&uri, Duration::from_secs(30)).await.unwrap();
+    ///
+    ///     let event = EventPrepare::builder(signer.user_public_key()).content("hi").build().unwrap();
+    ///     let event = signer.sign_event(event).await.unwrap();
+    /// }
+    /// ```
+    pub async fn connect(uri: &str, timeout: Duration) -> Result<Self, NIP46Error> {
+        let uri = BunkerUri::from_str(uri)?;
+        let remote_pub_key = XOnlyPublicKey::from_str(&uri.remote_pub_key)
+            .map_err(|_| NIP46Error::InvalidBunkerUri(uri.remote_pub_key.clone()))?;
+        let client = Client::new(uri.relays.iter().map(String::as_str).collect()).await?;
+
+        let mut signer = Self::new(client, remote_pub_key, timeout);
+        signer
+            .request("connect", &Self::connect_params(&uri))
+            .await?;
+        let user_pub_key = signer.request("get_public_key", &[]).await?;
+        signer.set_user_public_key(user_pub_key)?;
+
+        Ok(signer)
+    }
+
+    #[cfg(feature = "async")]
+    /// Send a request to the remote signer and wait for its result
+    pub async fn request(&self, method: &str, params: &[String]) -> Result<String, NIP46Error> {
+        let (request_id, event) = self.request_event(method, params)?;
+        let mut client = self.client.lock().await;
+
+        // Subscribe before publishing to not miss a quick response
+        let subscription_id = client.subscribe(vec![self.response_filter()]).await?;
+        let response = match client.publish_event(&event).await {
+            Ok(()) => {
+                self.wait_response(&mut client, &subscription_id, &request_id)
+                    .await
+            }
+            Err(err) => Err(err.into()),
+        };
+        client.unsubscribe(&subscription_id).await?;
+
+        response
+    }
+
+    #[cfg(feature = "async")]
+    async fn wait_response(
+        &self,
+        client: &mut Client,
+        subscription_id: &str,
+        request_id: &str,
+    ) -> Result<String, NIP46Error> {
+        let deadline = Instant::now() + self.timeout;
+
+        while Instant::now() < deadline {
+            let data = client.next_data_all().await?;
+
+            if data.is_empty() {
+                tokio::time::sleep(RESPONSE_POLL_INTERVAL).await;
+                continue;
+            }
+
+            for (_, message) in data {
+                if let Some(response) = self.parse_response(subscription_id, request_id, &message) {
+                    return response;
+                }
+            }
+        }
+
+        Err(NIP46Error::Timeout)
+    }
+
+    #[cfg(feature = "async")]
+    /// Ask the remote signer to sign an event prepared for the user public key
+    pub async fn sign_event(&self, event: EventPrepare) -> Result<Event, NIP46Error> {
+        if event.pub_key != self.user_pub_key {
+            return Err(NIP46Error::WrongPublicKey(event.pub_key));
+        }
+
+        let result = self
+            .request("sign_event", &[Self::unsigned_event(&event)])
+            .await?;
+        self.check_signed_event(&event, &result)
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> String {
+        self.user_pub_key.clone()
+    }
+
+    fn sign(&self, event: EventPrepare) -> Result<Event, SignerError> {
+        Ok(self.sign_event(event)?)
+    }
+}
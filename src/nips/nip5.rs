@@ -1,4 +1,6 @@
 use crate::bech32::auto_bech32_to_hex;
+use crate::http::HttpConfig;
+use reqwest::header;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
@@ -42,8 +44,18 @@ pub struct NostrWellKnown {
 /// assert_eq!(check_validity("_@", "3235036bd0957dfb27ccda02d452d7c763be40c91a1ac082ba6983b25238388c"), Err(NIP5Error::RequestFailed));
 /// ```
 pub fn check_validity(nip05: &str, pubkey: &str) -> Result<bool, NIP5Error> {
+    check_validity_with_config(nip05, pubkey, &HttpConfig::default())
+}
+
+#[cfg(not(feature = "async"))]
+/// Check validity of a NIP05 identifier with the given timeout, retries and User-Agent
+pub fn check_validity_with_config(
+    nip05: &str,
+    pubkey: &str,
+    config: &HttpConfig,
+) -> Result<bool, NIP5Error> {
     let hex_pubkey = auto_bech32_to_hex(pubkey)?;
-    let pubkey_found = get_nip05(nip05)?;
+    let pubkey_found = get_nip05_with_config(nip05, config)?;
 
     Ok(pubkey_found == hex_pubkey)
 }
@@ -63,8 +75,18 @@ pub fn check_validity(nip05: &str, pubkey: &str) -> Result<bool, NIP5Error> {
 /// }
 /// ```
 pub async fn check_validity(nip05: &str, pubkey: &str) -> Result<bool, NIP5Error> {
+    check_validity_with_config(nip05, pubkey, &HttpConfig::default()).await
+}
+
+#[cfg(feature = "async")]
+/// Check validity of a NIP05 identifier with the given timeout, retries and User-Agent
+pub async fn check_validity_with_config(
+    nip05: &str,
+    pubkey: &str,
+    config: &HttpConfig,
+) -> Result<bool, NIP5Error> {
     let hex_pubkey = auto_bech32_to_hex(pubkey)?;
-    let pubkey_found = get_nip05(nip05).await?;
+    let pubkey_found = get_nip05_with_config(nip05, config).await?;
 
     Ok(pubkey_found == hex_pubkey)
 }
@@ -80,11 +102,22 @@ pub async fn check_validity(nip05: &str, pubkey: &str) -> Result<bool, NIP5Error
 /// assert_eq!(get_nips05("nostr.0xtlt.dev").unwrap().names.get("_").unwrap(), "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6");
 /// ```
 pub fn get_nips05(domain: &str) -> Result<NostrWellKnown, NIP5Error> {
+    get_nips05_with_config(domain, &HttpConfig::default())
+}
+
+#[cfg(not(feature = "async"))]
+/// Get NIP05 Nostr Well Known of a domain with the given timeout, retries and User-Agent
+pub fn get_nips05_with_config(
+    domain: &str,
+    config: &HttpConfig,
+) -> Result<NostrWellKnown, NIP5Error> {
     // Check the domain
-    let relay_response: NostrWellKnown = match reqwest::blocking::Client::new()
+    let client = config.client().map_err(|_| NIP5Error::RequestFailed)?;
+    let request = client
         .get(format!("https://{domain}/.well-known/nostr.json"))
-        .send()
-    {
+        .header(header::ACCEPT, "application/json");
+
+    let relay_response: NostrWellKnown = match config.send(request) {
         Ok(response) => match response.json() {
             Ok(json) => json,
             Err(_) => return Err(NIP5Error::InvalidResponseFormat),
@@ -109,12 +142,22 @@ pub fn get_nips05(domain: &str) -> Result<NostrWellKnown, NIP5Error> {
 /// }
 /// ```
 pub async fn get_nips05(domain: &str) -> Result<NostrWellKnown, NIP5Error> {
+    get_nips05_with_config(domain, &HttpConfig::default()).await
+}
+
+#[cfg(feature = "async")]
+/// Get NIP05 Nostr Well Known of a domain with the given timeout, retries and User-Agent
+pub async fn get_nips05_with_config(
+    domain: &str,
+    config: &HttpConfig,
+) -> Result<NostrWellKnown, NIP5Error> {
     // Check the domain
-    let relay_response: NostrWellKnown = match reqwest::Client::new()
+    let client = config.client().map_err(|_| NIP5Error::RequestFailed)?;
+    let request = client
         .get(format!("https://{domain}/.well-known/nostr.json"))
-        .send()
-        .await
-    {
+        .header(header::ACCEPT, "application/json");
+
+    let relay_response: NostrWellKnown = match config.send(request).await {
         Ok(response) => match response.json().await {
             Ok(json) => json,
             Err(_) => return Err(NIP5Error::InvalidResponseFormat),
@@ -135,16 +178,30 @@ pub async fn get_nips05(domain: &str) -> Result<NostrWellKnown, NIP5Error> {
 /// assert_eq!(get_nip05("_@nostr.0xtlt.dev"), Ok("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()));
 /// ```
 pub fn get_nip05(nip05: &str) -> Result<String, NIP5Error> {
+    get_nip05_with_config(nip05, &HttpConfig::default())
+}
+
+#[cfg(not(feature = "async"))]
+/// Get the public key of a NIP05 identifier with the given timeout, retries and User-Agent
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{http::HttpConfig, nips::nip5::get_nip05_with_config};
+///
+/// let config = HttpConfig { retries: 3, user_agent: "my-app/1.0".to_string(), ..Default::default() };
+/// assert_eq!(get_nip05_with_config("_@nostr.0xtlt.dev", &config), Ok("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()));
+/// ```
+pub fn get_nip05_with_config(nip05: &str, config: &HttpConfig) -> Result<String, NIP5Error> {
     let parts: Vec<&str> = nip05.split('@').collect();
 
     // Check ["username", "domain"] length = 2
-    if parts.len() != 2 {
+    let [username, domain] = parts[..] else {
         return Err(NIP5Error::InvalidFormat);
-    }
+    };
 
-    let list = get_nips05(parts[1])?;
+    let list = get_nips05_with_config(domain, config)?;
 
-    let pubkey = list.names.get(parts[0]);
+    let pubkey = list.names.get(username);
 
     if let Some(pubkey) = pubkey {
         Ok(pubkey.clone())
@@ -166,16 +223,33 @@ pub fn get_nip05(nip05: &str) -> Result<String, NIP5Error> {
 /// }
 /// ```
 pub async fn get_nip05(nip05: &str) -> Result<String, NIP5Error> {
+    get_nip05_with_config(nip05, &HttpConfig::default()).await
+}
+
+#[cfg(feature = "async")]
+/// Get the public key of a NIP05 identifier with the given timeout, retries and User-Agent
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{http::HttpConfig, nips::nip5::get_nip05_with_config};
+///
+/// #[tokio::test]
+/// async fn test_get_nip05_with_config() {
+///     let config = HttpConfig { retries: 3, user_agent: "my-app/1.0".to_string(), ..Default::default() };
+///     assert_eq!(get_nip05_with_config("_@nostr.0xtlt.dev", &config).await, Ok("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()));
+/// }
+/// ```
+pub async fn get_nip05_with_config(nip05: &str, config: &HttpConfig) -> Result<String, NIP5Error> {
     let parts: Vec<&str> = nip05.split('@').collect();
 
     // Check ["username", "domain"] length = 2
-    if parts.len() != 2 {
+    let [username, domain] = parts[..] else {
         return Err(NIP5Error::InvalidFormat);
-    }
+    };
 
-    let list = get_nips05(parts[1]).await?;
+    let list = get_nips05_with_config(domain, config).await?;
 
-    let pubkey = list.names.get(parts[0]);
+    let pubkey = list.names.get(username);
 
     if let Some(pubkey) = pubkey {
         Ok(pubkey.clone())
@@ -0,0 +1,562 @@
+use crate::events::{Address, Event};
+#[cfg(feature = "net")]
+use crate::{
+    events::EventPrepare,
+    nostr_client::{Client, ClientError},
+    signer::Signer,
+    utils::get_timestamp,
+};
+use thiserror::Error;
+
+// Implementation of the NIP52 protocol
+// https://github.com/nostr-protocol/nips/blob/master/52.md
+
+/// Kind of a date-based calendar event
+pub const DATE_CALENDAR_EVENT_KIND: u16 = 31922;
+/// Kind of a time-based calendar event
+pub const TIME_CALENDAR_EVENT_KIND: u16 = 31923;
+/// Kind of a calendar
+pub const CALENDAR_KIND: u16 = 31924;
+/// Kind of a calendar event RSVP
+pub const CALENDAR_RSVP_KIND: u16 = 31925;
+
+#[derive(Error, Debug)]
+pub enum NIP52Error {
+    #[cfg(feature = "net")]
+    #[error("The client has an error")]
+    ClientError(ClientError),
+
+    #[error("The event kind {} is not a calendar event kind", _0)]
+    WrongKind(u16),
+
+    #[error("The calendar event has no \"{}\" tag", _0)]
+    MissingTag(&'static str),
+
+    #[error("Invalid date \"{}\", the format must be YYYY-MM-DD", _0)]
+    InvalidDate(String),
+
+    #[error("Invalid unix timestamp \"{}\"", _0)]
+    InvalidTimestamp(String),
+
+    #[error("The start and the end of the calendar event must be both dates or both timestamps")]
+    MixedTimes,
+
+    #[error("The start of the calendar event is after its end")]
+    StartAfterEnd,
+}
+
+#[cfg(feature = "net")]
+impl From<ClientError> for NIP52Error {
+    fn from(err: ClientError) -> Self {
+        Self::ClientError(err)
+    }
+}
+
+/// Start or end of a calendar event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CalendarTime {
+    /// Date in the YYYY-MM-DD format, used by date-based calendar events
+    Date(String),
+    /// Unix timestamp in seconds, used by time-based calendar events
+    Timestamp(u64),
+}
+
+impl CalendarTime {
+    fn to_tag_value(&self) -> String {
+        match self {
+            Self::Date(date) => date.clone(),
+            Self::Timestamp(timestamp) => timestamp.to_string(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), NIP52Error> {
+        match self {
+            Self::Date(date) if !is_valid_date(date) => Err(NIP52Error::InvalidDate(date.clone())),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Date-based (kind 31922) or time-based (kind 31923) calendar event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarEvent {
+    /// Identifier of the event ("d" tag)
+    pub identifier: String,
+    pub title: String,
+    pub start: CalendarTime,
+    pub end: Option<CalendarTime>,
+    pub location: Option<String>,
+    /// Public keys of the participants (hex)
+    pub participants: Vec<String>,
+    /// Description of the event, stored in the content
+    pub description: String,
+}
+
+impl CalendarEvent {
+    /// Kind of the calendar event, deduced from its start
+    pub fn kind(&self) -> u16 {
+        match self.start {
+            CalendarTime::Date(_) => DATE_CALENDAR_EVENT_KIND,
+            CalendarTime::Timestamp(_) => TIME_CALENDAR_EVENT_KIND,
+        }
+    }
+
+    /// Check the dates format and that the start is not after the end
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nips::nip52::{CalendarEvent, CalendarTime};
+    ///
+    /// let mut event = CalendarEvent {
+    ///   identifier: "meetup".to_string(),
+    ///   title: "Nostr meetup".to_string(),
+    ///   start: CalendarTime::Date("2023-05-02".to_string()),
+    ///   end: Some(CalendarTime::Date("2023-05-03".to_string())),
+    ///   location: None,
+    ///   participants: vec![],
+    ///   description: String::new(),
+    /// };
+    /// assert!(event.validate().is_ok());
+    ///
+    /// event.end = Some(CalendarTime::Date("2023-05-01".to_string()));
+    /// assert!(event.validate().is_err());
+    ///
+    /// event.start = CalendarTime::Date("02/05/2023".to_string());
+    /// assert!(event.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), NIP52Error> {
+        self.start.validate()?;
+
+        let end = match &self.end {
+            Some(end) => end,
+            None => return Ok(()),
+        };
+        end.validate()?;
+
+        let start_after_end = match (&self.start, end) {
+            (CalendarTime::Date(start), CalendarTime::Date(end)) => start > end,
+            (CalendarTime::Timestamp(start), CalendarTime::Timestamp(end)) => start > end,
+            _ => return Err(NIP52Error::MixedTimes),
+        };
+
+        if start_after_end {
+            return Err(NIP52Error::StartAfterEnd);
+        }
+
+        Ok(())
+    }
+
+    /// Tags of the calendar event
+    pub fn to_tags(&self) -> Vec<Vec<String>> {
+        let mut tags = vec![
+            vec!["d".to_string(), self.identifier.clone()],
+            vec!["title".to_string(), self.title.clone()],
+            vec!["start".to_string(), self.start.to_tag_value()],
+        ];
+
+        if let Some(end) = &self.end {
+            tags.push(vec!["end".to_string(), end.to_tag_value()]);
+        }
+
+        if let Some(location) = &self.location {
+            tags.push(vec!["location".to_string(), location.clone()]);
+        }
+
+        for participant in &self.participants {
+            tags.push(vec!["p".to_string(), participant.clone()]);
+        }
+
+        tags
+    }
+
+    /// Parse a calendar event (kind 31922 or 31923)
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::Event, nips::nip52::{CalendarEvent, CalendarTime}};
+    ///
+    /// let event = Event {
+    ///   id: String::new(),
+    ///   pub_key: String::new(),
+    ///   created_at: 0,
+    ///   kind: 31923,
+    ///   tags: vec![
+    ///     vec!["d".to_string(), "meetup".to_string()],
+    ///     vec!["title".to_string(), "Nostr meetup".to_string()],
+    ///     vec!["start".to_string(), "1683021600".to_string()],
+    ///     vec!["end".to_string(), "1683028800".to_string()],
+    ///     vec!["location".to_string(), "Paris".to_string()],
+    ///   ],
+    ///   content: "Let's talk about Nostr".to_string(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// let calendar_event = CalendarEvent::from_event(&event).unwrap();
+    /// assert_eq!(calendar_event.start, CalendarTime::Timestamp(1683021600));
+    /// assert_eq!(calendar_event.location, Some("Paris".to_string()));
+    /// ```
+    pub fn from_event(event: &Event) -> Result<Self, NIP52Error> {
+        let parse_time = |value: &str| match event.kind {
+            DATE_CALENDAR_EVENT_KIND => Ok(CalendarTime::Date(value.to_string())),
+            _ => value
+                .parse::<u64>()
+                .map(CalendarTime::Timestamp)
+                .map_err(|_| NIP52Error::InvalidTimestamp(value.to_string())),
+        };
+
+        if event.kind != DATE_CALENDAR_EVENT_KIND && event.kind != TIME_CALENDAR_EVENT_KIND {
+            return Err(NIP52Error::WrongKind(event.kind));
+        }
+
+        let calendar_event = Self {
+            identifier: event
+                .first_tag_value("d")
+                .ok_or(NIP52Error::MissingTag("d"))?
+                .to_string(),
+            title: event
+                .first_tag_value("title")
+                .ok_or(NIP52Error::MissingTag("title"))?
+                .to_string(),
+            start: parse_time(
+                event
+                    .first_tag_value("start")
+                    .ok_or(NIP52Error::MissingTag("start"))?,
+            )?,
+            end: event.first_tag_value("end").map(parse_time).transpose()?,
+            location: event.first_tag_value("location").map(str::to_string),
+            participants: event
+                .referenced_pubkeys()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            description: event.content.clone(),
+        };
+
+        calendar_event.validate()?;
+        Ok(calendar_event)
+    }
+}
+
+/// Answer to a calendar event invitation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RsvpStatus {
+    Accepted,
+    Declined,
+    Tentative,
+}
+
+impl RsvpStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::Declined => "declined",
+            Self::Tentative => "tentative",
+        }
+    }
+}
+
+/// Coordinate (`<kind>:<pubkey>:<identifier>`) of a parameterized replaceable event, used by "a" tags
+///
+/// See `events::Address` to parse a coordinate
+pub fn coordinate(kind: u16, pub_key: &str, identifier: &str) -> String {
+    Address {
+        kind,
+        pub_key: pub_key.to_string(),
+        identifier: identifier.to_string(),
+    }
+    .to_string()
+}
+
+fn is_valid_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return false;
+    };
+
+    if year.len() != 4
+        || month.len() != 2
+        || day.len() != 2
+        || !parts
+            .iter()
+            .all(|part| part.chars().all(|c| c.is_ascii_digit()))
+    {
+        return false;
+    }
+
+    let month: u8 = month.parse().unwrap_or(0);
+    let day: u8 = day.parse().unwrap_or(0);
+
+    (1..=12).contains(&month) && (1..=31).contains(&day)
+}
+
+#[cfg(feature = "net")]
+fn calendar_tags(identifier: &str, title: &str, events: &[String]) -> Vec<Vec<String>> {
+    let mut tags = vec![
+        vec!["d".to_string(), identifier.to_string()],
+        vec!["title".to_string(), title.to_string()],
+    ];
+
+    for event in events {
+        tags.push(vec!["a".to_string(), event.clone()]);
+    }
+
+    tags
+}
+
+#[cfg(feature = "net")]
+fn rsvp_tags(event_coordinate: &str, status: RsvpStatus) -> Vec<Vec<String>> {
+    vec![
+        // One RSVP per calendar event, a new answer replaces the previous one
+        vec!["d".to_string(), event_coordinate.to_string()],
+        vec!["a".to_string(), event_coordinate.to_string()],
+        vec!["status".to_string(), status.as_str().to_string()],
+    ]
+}
+
+#[cfg(feature = "net")]
+fn calendar_entry_event(
+    pub_key: &str,
+    calendar_event: &CalendarEvent,
+) -> Result<EventPrepare, NIP52Error> {
+    calendar_event.validate()?;
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: calendar_event.kind(),
+        tags: calendar_event.to_tags(),
+        content: calendar_event.description.clone(),
+    })
+}
+
+#[cfg(feature = "net")]
+fn calendar_event(
+    pub_key: &str,
+    identifier: &str,
+    title: &str,
+    description: &str,
+    events: &[String],
+) -> Result<EventPrepare, NIP52Error> {
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: CALENDAR_KIND,
+        tags: calendar_tags(identifier, title, events),
+        content: description.to_string(),
+    })
+}
+
+#[cfg(feature = "net")]
+fn rsvp_event(
+    pub_key: &str,
+    event_coordinate: &str,
+    status: RsvpStatus,
+) -> Result<EventPrepare, NIP52Error> {
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: CALENDAR_RSVP_KIND,
+        tags: rsvp_tags(event_coordinate, status),
+        content: String::new(),
+    })
+}
+
+#[cfg(feature = "net")]
+impl Client {
+    #[cfg(not(feature = "async"))]
+    /// Publish a calendar event, the kind (31922 or 31923) depends on the start
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity, nips::nip52::{CalendarEvent, CalendarTime}};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let calendar_event = CalendarEvent {
+    ///   identifier: "meetup".to_string(),
+    ///   title: "Nostr meetup".to_string(),
+    ///   start: CalendarTime::Date("2023-05-02".to_string()),
+    ///   end: None,
+    ///   location: Some("Paris".to_string()),
+    ///   participants: vec![env!("PUBLIC_KEY").to_string()],
+    ///   description: "Let's talk about Nostr".to_string(),
+    /// };
+    ///
+    /// client.publish_calendar_event(&identity, &calendar_event, 0).unwrap();
+    /// ```
+    pub fn publish_calendar_event(
+        &mut self,
+        signer: &dyn Signer,
+        calendar_event: &CalendarEvent,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP52Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| calendar_entry_event(pub_key, calendar_event),
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Publish a calendar event, the kind (31922 or 31923) depends on the start
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity, nips::nip52::{CalendarEvent, CalendarTime}};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_publish_calendar_event() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let calendar_event = CalendarEvent {
+    ///       identifier: "meetup".to_string(),
+    ///       title: "Nostr meetup".to_string(),
+    ///       start: CalendarTime::Date("2023-05-02".to_string()),
+    ///       end: None,
+    ///       location: Some("Paris".to_string()),
+    ///       participants: vec![env!("PUBLIC_KEY").to_string()],
+    ///       description: "Let's talk about Nostr".to_string(),
+    ///     };
+    ///
+    ///     client.publish_calendar_event(&identity, &calendar_event, 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn publish_calendar_event(
+        &mut self,
+        signer: &dyn Signer,
+        calendar_event: &CalendarEvent,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP52Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| calendar_entry_event(pub_key, calendar_event),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Publish a calendar (kind 31924), a collection of calendar events given by their coordinates
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity, nips::nip52::{coordinate, TIME_CALENDAR_EVENT_KIND}};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let events = vec![coordinate(TIME_CALENDAR_EVENT_KIND, env!("PUBLIC_KEY"), "meetup")];
+    /// client.publish_calendar(&identity, "nostr", "Nostr events", "", &events, 0).unwrap();
+    /// ```
+    pub fn publish_calendar(
+        &mut self,
+        signer: &dyn Signer,
+        identifier: &str,
+        title: &str,
+        description: &str,
+        events: &[String],
+        difficulty_target: u16,
+    ) -> Result<Event, NIP52Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| calendar_event(pub_key, identifier, title, description, events),
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Publish a calendar (kind 31924), a collection of calendar events given by their coordinates
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity, nips::nip52::{coordinate, TIME_CALENDAR_EVENT_KIND}};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_publish_calendar() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let events = vec![coordinate(TIME_CALENDAR_EVENT_KIND, env!("PUBLIC_KEY"), "meetup")];
+    ///     client.publish_calendar(&identity, "nostr", "Nostr events", "", &events, 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn publish_calendar(
+        &mut self,
+        signer: &dyn Signer,
+        identifier: &str,
+        title: &str,
+        description: &str,
+        events: &[String],
+        difficulty_target: u16,
+    ) -> Result<Event, NIP52Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| calendar_event(pub_key, identifier, title, description, events),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Answer to a calendar event (kind 31925), given by its coordinate
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity, nips::nip52::{coordinate, RsvpStatus, TIME_CALENDAR_EVENT_KIND}};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let event = coordinate(TIME_CALENDAR_EVENT_KIND, env!("PUBLIC_KEY"), "meetup");
+    /// client.rsvp_calendar_event(&identity, &event, RsvpStatus::Accepted, 0).unwrap();
+    /// ```
+    pub fn rsvp_calendar_event(
+        &mut self,
+        signer: &dyn Signer,
+        event_coordinate: &str,
+        status: RsvpStatus,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP52Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| rsvp_event(pub_key, event_coordinate, status),
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Answer to a calendar event (kind 31925), given by its coordinate
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity, nips::nip52::{coordinate, RsvpStatus, TIME_CALENDAR_EVENT_KIND}};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_rsvp_calendar_event() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let event = coordinate(TIME_CALENDAR_EVENT_KIND, env!("PUBLIC_KEY"), "meetup");
+    ///     client.rsvp_calendar_event(&identity, &event, RsvpStatus::Accepted, 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn rsvp_calendar_event(
+        &mut self,
+        signer: &dyn Signer,
+        event_coordinate: &str,
+        status: RsvpStatus,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP52Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| rsvp_event(pub_key, event_coordinate, status),
+            difficulty_target,
+        )
+        .await
+    }
+}
@@ -0,0 +1,183 @@
+// Implementation of the NIP59 protocol
+// https://github.com/nostr-protocol/nips/blob/master/59.md
+
+use crate::{
+    events::{Event, EventError, EventPrepare},
+    keys::{validate_pubkey, KeyError},
+    utils::jittered_timestamp,
+    Identity,
+};
+use secp256k1::XOnlyPublicKey;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+
+use super::nip44::{self, NIP44Error};
+
+pub const SEAL_KIND: u16 = 13;
+pub const GIFT_WRAP_KIND: u16 = 1059;
+
+/// The seal and the gift wrap are dated up to 2 days in the past, to hide when the rumor was sent
+const MAX_CREATED_AT_TWEAK: Duration = Duration::from_secs(2 * 24 * 60 * 60);
+
+#[derive(Error, Debug)]
+pub enum NIP59Error {
+    #[error("NIP44 Error: {}", _0)]
+    NIP44Error(#[from] NIP44Error),
+
+    #[error("Key Error: {}", _0)]
+    KeyError(#[from] KeyError),
+
+    #[error("Event Error: {}", _0)]
+    EventError(#[from] EventError),
+
+    #[error("Serde Error: {}", _0)]
+    SerdeError(#[from] serde_json::Error),
+
+    #[error("The event is not a gift wrap (kind 1059)")]
+    NotGiftWrap,
+
+    #[error("The gift wrap doesn't contain a seal (kind 13)")]
+    NotSeal,
+
+    #[error("The rumor is not authored by the signer of the seal")]
+    AuthorMismatch,
+
+    #[error("The id of the rumor doesn't match its content")]
+    InvalidRumorId,
+}
+
+/// Unsigned event, as sealed in a gift wrap
+#[derive(Serialize, Deserialize)]
+struct Rumor {
+    id: String,
+    pubkey: String,
+    created_at: u64,
+    kind: u16,
+    tags: Vec<Vec<String>>,
+    content: String,
+}
+
+/// Encrypt an event for the recipient in an event of the given kind signed by the identity
+fn encrypted_event(
+    identity: &Identity,
+    recipient: &XOnlyPublicKey,
+    kind: u16,
+    tags: Vec<Vec<String>>,
+    content: &str,
+) -> Result<Event, NIP59Error> {
+    Ok(EventPrepare {
+        pub_key: identity.public_key_str.clone(),
+        created_at: jittered_timestamp(MAX_CREATED_AT_TWEAK),
+        kind,
+        tags,
+        content: nip44::encrypt(&identity.secret_key, recipient, content)?,
+    }
+    .to_event(identity, 0))
+}
+
+/// Gift wrap a rumor (an unsigned event) for the recipient (hex or npub)
+///
+/// The rumor is authored by the sender (its `pub_key` is replaced) and NIP-44 encrypted in a
+/// seal (kind 13) signed by the sender, which is encrypted in a gift wrap (kind 1059) signed by
+/// a throwaway key. The seal and the gift wrap have a random `created_at`.
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::EventPrepare, nips::nip59::{gift_wrap, unwrap_gift, GIFT_WRAP_KIND}, Identity};
+/// use std::str::FromStr;
+///
+/// let sender = Identity::from_str(env!("SECRET_KEY")).unwrap();
+/// let recipient = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000002").unwrap();
+///
+/// let rumor = EventPrepare::builder(&sender.public_key_str).kind(14).content("Hello Nostr!").build().unwrap();
+/// let wrap = gift_wrap(&sender, &recipient.public_key_str, rumor).unwrap();
+/// assert_eq!(wrap.kind, GIFT_WRAP_KIND);
+/// assert_ne!(wrap.pub_key, sender.public_key_str);
+/// assert_eq!(wrap.referenced_pubkeys(), vec![recipient.public_key_str.as_str()]);
+///
+/// let rumor = unwrap_gift(&recipient, &wrap).unwrap();
+/// assert_eq!(rumor.pub_key, sender.public_key_str);
+/// assert_eq!(rumor.content, "Hello Nostr!");
+/// assert!(rumor.sig.is_empty());
+///
+/// assert!(unwrap_gift(&sender, &wrap).is_err());
+/// ```
+pub fn gift_wrap(
+    sender: &Identity,
+    recipient_pubkey: &str,
+    rumor: EventPrepare,
+) -> Result<Event, NIP59Error> {
+    let recipient = validate_pubkey(recipient_pubkey)?;
+    let rumor = EventPrepare {
+        pub_key: sender.public_key_str.clone(),
+        ..rumor
+    };
+
+    let rumor = serde_json::to_string(&Rumor {
+        id: rumor.get_content_id(),
+        pubkey: rumor.pub_key,
+        created_at: rumor.created_at,
+        kind: rumor.kind,
+        tags: rumor.tags,
+        content: rumor.content,
+    })?;
+    let seal = encrypted_event(sender, &recipient, SEAL_KIND, vec![], &rumor)?;
+
+    encrypted_event(
+        &Identity::generate(),
+        &recipient,
+        GIFT_WRAP_KIND,
+        vec![vec!["p".to_string(), recipient.to_string()]],
+        &serde_json::to_string(&seal)?,
+    )
+}
+
+/// Get the rumor of a gift wrap received by the recipient, the rumor has no signature
+///
+/// The signature of the seal is checked and the rumor must be authored by the signer of the seal
+pub fn unwrap_gift(recipient: &Identity, wrap: &Event) -> Result<Event, NIP59Error> {
+    if wrap.kind != GIFT_WRAP_KIND {
+        return Err(NIP59Error::NotGiftWrap);
+    }
+
+    let wrap_key = XOnlyPublicKey::from_str(&wrap.pub_key).map_err(EventError::from)?;
+    let seal: Event = serde_json::from_str(&nip44::decrypt(
+        &recipient.secret_key,
+        &wrap_key,
+        &wrap.content,
+    )?)?;
+
+    if seal.kind != SEAL_KIND {
+        return Err(NIP59Error::NotSeal);
+    }
+
+    seal.verify()?;
+
+    let seal_key = XOnlyPublicKey::from_str(&seal.pub_key).map_err(EventError::from)?;
+    let rumor: Rumor = serde_json::from_str(&nip44::decrypt(
+        &recipient.secret_key,
+        &seal_key,
+        &seal.content,
+    )?)?;
+
+    if rumor.pubkey != seal.pub_key {
+        return Err(NIP59Error::AuthorMismatch);
+    }
+
+    let rumor = Event {
+        id: rumor.id,
+        pub_key: rumor.pubkey,
+        created_at: rumor.created_at,
+        kind: rumor.kind,
+        tags: rumor.tags,
+        content: rumor.content,
+        sig: String::new(),
+    };
+
+    if rumor.id != rumor.get_content_id() {
+        return Err(NIP59Error::InvalidRumorId);
+    }
+
+    Ok(rumor)
+}
@@ -0,0 +1,318 @@
+use crate::events::Event;
+use crate::utils::tag_value;
+#[cfg(feature = "net")]
+use crate::{
+    events::latest_replaceable,
+    nostr_client::{Client, ClientError, RelayResult},
+    req::ReqFilter,
+    signer::{Signer, SignerError},
+    websocket::normalize_relay_url,
+    Message,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "net")]
+use serde_json::json;
+
+// Implementation of the NIP65 protocol
+// https://github.com/nostr-protocol/nips/blob/master/65.md
+
+/// Kind of the relay list metadata event
+pub const RELAY_LIST_KIND: u16 = 10002;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelayListItem {
+    /// Relay URL
+    pub url: String,
+    /// The user reads from this relay
+    pub read: bool,
+    /// The user writes to this relay
+    pub write: bool,
+}
+
+/// Get the relays of a relay list metadata event (kind 10002)
+///
+/// A "r" tag without marker means the relay is used for both read and write
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::Event, nips::nip65::parse_relay_list};
+///
+/// let event = Event {
+///   id: String::new(),
+///   pub_key: String::new(),
+///   created_at: 0,
+///   kind: 10002,
+///   tags: vec![
+///     vec!["r".to_string(), "wss://alicerelay.example.com".to_string()],
+///     vec!["r".to_string(), "wss://brando-relay.com".to_string(), "read".to_string()],
+///     vec!["r".to_string()],
+///   ],
+///   content: String::new(),
+///   sig: String::new(),
+/// };
+///
+/// let relays = parse_relay_list(&event);
+/// assert_eq!(relays.len(), 2);
+/// assert!(relays[0].read && relays[0].write);
+/// assert!(relays[1].read && !relays[1].write);
+/// ```
+pub fn parse_relay_list(event: &Event) -> Vec<RelayListItem> {
+    event
+        .tags_by_name("r")
+        .filter_map(|tag| {
+            let url = tag_value(tag, 1)?;
+
+            let (read, write) = match tag_value(tag, 2) {
+                Some("read") => (true, false),
+                Some("write") => (false, true),
+                _ => (true, true),
+            };
+
+            Some(RelayListItem {
+                url: url.to_string(),
+                read,
+                write,
+            })
+        })
+        .collect()
+}
+
+#[cfg(feature = "net")]
+/// Relays of `Client::publish_to_recipient`, normalized: the write relays of the author and the
+/// read relays of the recipient, the relays of the client replace a missing relay list
+fn outbox_relays(
+    relay_lists: Vec<Event>,
+    author: &str,
+    recipient: &str,
+    client_relays: &[String],
+) -> Vec<String> {
+    let relay_lists = latest_replaceable(relay_lists);
+    let relays_of = |pubkey: &str, usage: fn(&RelayListItem) -> bool| -> Vec<String> {
+        let relays: Vec<String> = relay_lists
+            .iter()
+            .filter(|event| event.pub_key == pubkey && event.kind == RELAY_LIST_KIND)
+            .flat_map(parse_relay_list)
+            .filter(usage)
+            .map(|relay| relay.url)
+            .collect();
+
+        if relays.is_empty() {
+            client_relays.to_vec()
+        } else {
+            relays
+        }
+    };
+
+    let mut outbox: Vec<String> = Vec::new();
+    for relay in relays_of(author, |relay| relay.write)
+        .into_iter()
+        .chain(relays_of(recipient, |relay| relay.read))
+    {
+        // The urls of a relay list are not trusted, the invalid ones are skipped
+        if let Ok(relay) = normalize_relay_url(&relay) {
+            if !outbox.contains(&relay) {
+                outbox.push(relay);
+            }
+        }
+    }
+
+    outbox
+}
+
+#[cfg(feature = "net")]
+/// Filter of the relay lists of the author and of the recipient of `Client::publish_to_recipient`
+fn relay_lists_filter(author: &str, recipient: &str) -> ReqFilter {
+    ReqFilter {
+        authors: Some(vec![author.to_string(), recipient.to_string()]),
+        kinds: Some(vec![RELAY_LIST_KIND]),
+        ..Default::default()
+    }
+}
+
+#[cfg(feature = "net")]
+/// The relays of the signer are looked up, the event must be signed by it
+fn check_author(signer: &dyn Signer, event: &Event) -> Result<(), ClientError> {
+    if event.pub_key != signer.public_key() {
+        return Err(SignerError::WrongPublicKey(event.pub_key.clone()).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "net")]
+impl Client {
+    #[cfg(not(feature = "async"))]
+    /// Publish an event to the write relays of the signer and to the read relays of the
+    /// recipient (outbox model), e.g. for a reply or a private message
+    ///
+    /// The relay lists (kind 10002) are fetched from every relay of the client, which replace a
+    /// missing relay list. The relays missing from the client are added, with `drop_added_relays`
+    /// they are removed once the event is published. The event is sent to each relay separately:
+    /// return the result of each relay, a failing relay doesn't prevent the others from
+    /// receiving the event, and a relay which can't be removed is listed with its error.
+    /// An event not signed by the signer gets `SignerError::WrongPublicKey` before any request
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let recipient = "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6";
+    /// let event = identity.make_event(1, "Hello!", &[vec!["p".to_string(), recipient.to_string()]], 0);
+    /// for (relay, result) in client.publish_to_recipient(&identity, recipient, &event, true).unwrap() {
+    ///     println!("{}: {:?}", relay, result);
+    /// }
+    /// ```
+    ///
+    /// An event of another author is rejected:
+    /// ```rust
+    /// use nostr_rust::{nostr_client::{Client, ClientError}, signer::SignerError, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// let identity = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    /// let other = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000002").unwrap();
+    ///
+    /// let event = other.make_event(1, "Hello!", &[], 0);
+    /// assert!(matches!(
+    ///     client.publish_to_recipient(&identity, &other.public_key_str, &event, true),
+    ///     Err(ClientError::SignerError(SignerError::WrongPublicKey(_)))
+    /// ));
+    /// ```
+    pub fn publish_to_recipient(
+        &mut self,
+        signer: &dyn Signer,
+        recipient_pubkey: &str,
+        event: &Event,
+        drop_added_relays: bool,
+    ) -> Result<Vec<RelayResult>, ClientError> {
+        check_author(signer, event)?;
+
+        let relay_lists = self.get_events_of_per_relay(vec![relay_lists_filter(
+            &signer.public_key(),
+            recipient_pubkey,
+        )])?;
+
+        let mut results: Vec<RelayResult> = Vec::new();
+        let mut relays: Vec<String> = Vec::new();
+        let mut added_relays: Vec<String> = Vec::new();
+        for relay in outbox_relays(
+            relay_lists
+                .into_values()
+                .filter_map(Result::ok)
+                .flatten()
+                .collect(),
+            &signer.public_key(),
+            recipient_pubkey,
+            self.relay_urls(),
+        ) {
+            match self.find_relay(&relay) {
+                Some(relay) => relays.push(relay),
+                None => match self.add_relay(&relay) {
+                    Ok(()) => {
+                        relays.push(relay.clone());
+                        added_relays.push(relay);
+                    }
+                    Err(error) => results.push((relay, Err(error))),
+                },
+            }
+        }
+
+        let message = Message::text(json!(["EVENT", event]).to_string());
+        results.extend(self.send_event_message_per_relay(&relays, &message));
+
+        if drop_added_relays {
+            for relay in added_relays {
+                if let Err(error) = self.remove_relay(&relay) {
+                    results.push((relay, Err(error)));
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    #[cfg(feature = "async")]
+    /// Publish an event to the write relays of the signer and to the read relays of the
+    /// recipient (outbox model), e.g. for a reply or a private message
+    ///
+    /// The relay lists (kind 10002) are fetched from every relay of the client, which replace a
+    /// missing relay list. The relays missing from the client are added, with `drop_added_relays`
+    /// they are removed once the event is published. The event is sent to each relay separately:
+    /// return the result of each relay, a failing relay doesn't prevent the others from
+    /// receiving the event, and a relay which can't be removed is listed with its error.
+    /// An event not signed by the signer gets `SignerError::WrongPublicKey` before any request
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_publish_to_recipient() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let recipient = "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6";
+    ///     let event = identity.make_event(1, "Hello!", &[vec!["p".to_string(), recipient.to_string()]], 0);
+    ///     for (relay, result) in client.publish_to_recipient(&identity, recipient, &event, true).await.unwrap() {
+    ///         println!("{}: {:?}", relay, result);
+    ///     }
+    /// }
+    /// ```
+    pub async fn publish_to_recipient(
+        &mut self,
+        signer: &dyn Signer,
+        recipient_pubkey: &str,
+        event: &Event,
+        drop_added_relays: bool,
+    ) -> Result<Vec<RelayResult>, ClientError> {
+        check_author(signer, event)?;
+
+        let relay_lists = self
+            .get_events_of_per_relay(vec![relay_lists_filter(
+                &signer.public_key(),
+                recipient_pubkey,
+            )])
+            .await?;
+
+        let mut results: Vec<RelayResult> = Vec::new();
+        let mut relays: Vec<String> = Vec::new();
+        let mut added_relays: Vec<String> = Vec::new();
+        for relay in outbox_relays(
+            relay_lists
+                .into_values()
+                .filter_map(Result::ok)
+                .flatten()
+                .collect(),
+            &signer.public_key(),
+            recipient_pubkey,
+            self.relay_urls(),
+        ) {
+            match self.find_relay(&relay) {
+                Some(relay) => relays.push(relay),
+                None => match self.add_relay(&relay).await {
+                    Ok(()) => {
+                        relays.push(relay.clone());
+                        added_relays.push(relay);
+                    }
+                    Err(error) => results.push((relay, Err(error))),
+                },
+            }
+        }
+
+        let message = Message::text(json!(["EVENT", event]).to_string());
+        results.extend(self.send_event_message_per_relay(&relays, &message).await);
+
+        if drop_added_relays {
+            for relay in added_relays {
+                if let Err(error) = self.remove_relay(&relay).await {
+                    results.push((relay, Err(error)));
+                }
+            }
+        }
+
+        Ok(results)
+    }
+}
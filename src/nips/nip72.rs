@@ -0,0 +1,557 @@
+#[cfg(feature = "net")]
+use crate::{
+    events::EventPrepare,
+    nostr_client::{Client, ClientError},
+    req::ReqFilter,
+    signer::Signer,
+    utils::get_timestamp,
+};
+use crate::{
+    events::{Address, Event},
+    utils::tag_value,
+};
+use std::collections::HashSet;
+use std::str::FromStr;
+use thiserror::Error;
+
+// Implementation of the NIP72 protocol
+// https://github.com/nostr-protocol/nips/blob/master/72.md
+
+/// Kind of a community definition
+pub const COMMUNITY_KIND: u16 = 34550;
+/// Kind of a community post approval
+pub const COMMUNITY_POST_APPROVAL_KIND: u16 = 4550;
+
+#[derive(Error, Debug)]
+pub enum NIP72Error {
+    #[cfg(feature = "net")]
+    #[error("The client has an error")]
+    ClientError(ClientError),
+
+    #[error(
+        "Invalid community coordinate \"{}\", the format must be 34550:<pubkey>:<identifier>",
+        _0
+    )]
+    InvalidCoordinate(String),
+
+    #[error("The community has no identifier")]
+    MissingIdentifier,
+
+    #[error("Serde Error: {}", _0)]
+    SerdeError(#[from] serde_json::Error),
+}
+
+#[cfg(feature = "net")]
+impl From<ClientError> for NIP72Error {
+    fn from(err: ClientError) -> Self {
+        Self::ClientError(err)
+    }
+}
+
+/// Community definition (kind 34550)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Community {
+    /// Identifier of the community ("d" tag)
+    pub identifier: String,
+    /// Display name, no "name" tag is added when empty
+    pub name: String,
+    pub description: Option<String>,
+    /// Public keys of the moderators (hex)
+    pub moderators: Vec<String>,
+    /// Relays where the posts and approvals of the community are published
+    pub relays: Vec<String>,
+}
+
+impl Community {
+    /// Tags of the community definition
+    pub fn to_tags(&self) -> Vec<Vec<String>> {
+        let mut tags = vec![vec!["d".to_string(), self.identifier.clone()]];
+
+        if !self.name.is_empty() {
+            tags.push(vec!["name".to_string(), self.name.clone()]);
+        }
+
+        if let Some(description) = &self.description {
+            tags.push(vec!["description".to_string(), description.clone()]);
+        }
+
+        for moderator in &self.moderators {
+            tags.push(vec![
+                "p".to_string(),
+                moderator.clone(),
+                String::new(),
+                "moderator".to_string(),
+            ]);
+        }
+
+        for relay in &self.relays {
+            tags.push(vec!["relay".to_string(), relay.clone()]);
+        }
+
+        tags
+    }
+
+    /// Coordinate of the community created by `pub_key`, used by the "a" tags of the posts
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nips::nip72::Community;
+    ///
+    /// let community = Community {
+    ///   identifier: "rust".to_string(),
+    ///   name: "Rust".to_string(),
+    ///   description: None,
+    ///   moderators: vec![],
+    ///   relays: vec![],
+    /// };
+    ///
+    /// let pub_key = "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6";
+    /// assert_eq!(community.coordinate(pub_key), format!("34550:{}:rust", pub_key));
+    /// ```
+    pub fn coordinate(&self, pub_key: &str) -> String {
+        Address {
+            kind: COMMUNITY_KIND,
+            pub_key: pub_key.to_string(),
+            identifier: self.identifier.clone(),
+        }
+        .to_string()
+    }
+}
+
+/// Check a community coordinate (`34550:<pubkey>:<identifier>`) and return its address, with
+/// the public key of its creator
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::nips::nip72::validate_community_coordinate;
+///
+/// let pub_key = "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6";
+/// let address = validate_community_coordinate(&format!("34550:{}:rust", pub_key)).unwrap();
+/// assert_eq!(address.pub_key, pub_key);
+/// assert_eq!(address.identifier, "rust");
+///
+/// assert!(validate_community_coordinate(&format!("30023:{}:rust", pub_key)).is_err());
+/// assert!(validate_community_coordinate("34550:npub:rust").is_err());
+/// assert!(validate_community_coordinate(&format!("34550:{}:", pub_key)).is_err());
+/// ```
+pub fn validate_community_coordinate(coordinate: &str) -> Result<Address, NIP72Error> {
+    let invalid = || NIP72Error::InvalidCoordinate(coordinate.to_string());
+    let address = Address::from_str(coordinate).map_err(|_| invalid())?;
+
+    if address.kind != COMMUNITY_KIND || address.identifier.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(address)
+}
+
+/// Get the posts approved for a community from approval events (kind 4550)
+///
+/// Only the approvals of `approvers`, the community creator and its moderators, are taken into
+/// account. The approved post must have a valid id, referenced by the "e" tag of its approval.
+/// Approvals of other communities and approvals whose content is not an event are skipped,
+/// a post approved several times is returned once.
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::{Event, EventPrepare}, nips::nip72::approved_posts, Identity};
+/// use std::str::FromStr;
+///
+/// let identity = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+/// let moderator = identity.public_key_str.clone();
+/// let coordinate = format!("34550:{}:rust", moderator);
+/// let post = EventPrepare::builder(&moderator).content("hello").build().unwrap().to_event(&identity, 0);
+///
+/// let approval = |pub_key: &str, coordinate: &str, post_id: &str| Event {
+///   id: String::new(),
+///   pub_key: pub_key.to_string(),
+///   created_at: 0,
+///   kind: 4550,
+///   tags: vec![
+///     vec!["a".to_string(), coordinate.to_string()],
+///     vec!["e".to_string(), post_id.to_string()],
+///   ],
+///   content: serde_json::to_string(&post).unwrap(),
+///   sig: String::new(),
+/// };
+///
+/// let approvals = vec![
+///   approval(&moderator, &coordinate, &post.id),
+///   approval(&moderator, &coordinate, &post.id),
+///   approval(&moderator, "34550:other:rust", &post.id),
+///   // Not a moderator of the community
+///   approval("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798", &coordinate, &post.id),
+///   // The "e" tag references another post
+///   approval(&moderator, &coordinate, "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4"),
+/// ];
+/// let posts = approved_posts(&approvals, &coordinate, &[moderator.clone()]);
+/// assert_eq!(posts.len(), 1);
+/// assert_eq!(posts[0].content, "hello");
+///
+/// // The embedded post was changed after its id was computed
+/// let mut tampered = approval(&moderator, &coordinate, &post.id);
+/// tampered.content = tampered.content.replace("hello", "spam");
+/// assert!(approved_posts(&[tampered], &coordinate, &[moderator]).is_empty());
+/// ```
+pub fn approved_posts(
+    approvals: &[Event],
+    community_coordinate: &str,
+    approvers: &[String],
+) -> Vec<Event> {
+    let mut seen: HashSet<String> = HashSet::new();
+
+    approvals
+        .iter()
+        .filter(|approval| {
+            approval.kind == COMMUNITY_POST_APPROVAL_KIND
+                && approvers.contains(&approval.pub_key)
+                && approval
+                    .tags_by_name("a")
+                    .any(|tag| tag_value(tag, 1) == Some(community_coordinate))
+        })
+        .filter_map(|approval| {
+            let post = serde_json::from_str::<Event>(&approval.content).ok()?;
+            let referenced = approval
+                .tags_by_name("e")
+                .any(|tag| tag_value(tag, 1) == Some(post.id.as_str()));
+
+            (referenced && post.id == post.get_content_id()).then_some(post)
+        })
+        .filter(|post| seen.insert(post.id.clone()))
+        .collect()
+}
+
+/// Public keys whose approvals count for a community: its creator and its moderators
+#[cfg(feature = "net")]
+fn community_approvers(creator: &str, moderators: &[String]) -> Vec<String> {
+    let mut approvers = vec![creator.to_string()];
+    approvers.extend(moderators.iter().cloned());
+
+    approvers
+}
+
+#[cfg(feature = "net")]
+fn community_post_tags(community: &Address) -> Vec<Vec<String>> {
+    vec![community.to_tag()]
+}
+
+#[cfg(feature = "net")]
+fn approval_tags(community: &Address, post: &Event) -> Vec<Vec<String>> {
+    vec![
+        community.to_tag(),
+        vec!["e".to_string(), post.id.clone()],
+        vec!["p".to_string(), post.pub_key.clone()],
+        vec!["k".to_string(), post.kind.to_string()],
+    ]
+}
+
+#[cfg(feature = "net")]
+fn community_event(pub_key: &str, community: &Community) -> Result<EventPrepare, NIP72Error> {
+    if community.identifier.is_empty() {
+        return Err(NIP72Error::MissingIdentifier);
+    }
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: COMMUNITY_KIND,
+        tags: community.to_tags(),
+        content: String::new(),
+    })
+}
+
+#[cfg(feature = "net")]
+fn community_post_event(
+    pub_key: &str,
+    community_coordinate: &str,
+    content: &str,
+) -> Result<EventPrepare, NIP72Error> {
+    let community = validate_community_coordinate(community_coordinate)?;
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: 1,
+        tags: community_post_tags(&community),
+        content: content.to_string(),
+    })
+}
+
+#[cfg(feature = "net")]
+fn approval_event(
+    pub_key: &str,
+    community_coordinate: &str,
+    post: &Event,
+) -> Result<EventPrepare, NIP72Error> {
+    let community = validate_community_coordinate(community_coordinate)?;
+
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: COMMUNITY_POST_APPROVAL_KIND,
+        tags: approval_tags(&community, post),
+        content: serde_json::to_string(post)?,
+    })
+}
+
+#[cfg(feature = "net")]
+/// Approvals are requested by author (creator and moderators) since `ReqFilter` has no "#a" filter
+fn approvals_filter(approvers: &[String]) -> ReqFilter {
+    ReqFilter {
+        authors: Some(approvers.to_vec()),
+        kinds: Some(vec![COMMUNITY_POST_APPROVAL_KIND]),
+        ..Default::default()
+    }
+}
+
+#[cfg(feature = "net")]
+impl Client {
+    #[cfg(not(feature = "async"))]
+    /// Publish a community definition (kind 34550)
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity, nips::nip72::Community};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let community = Community {
+    ///   identifier: "rust".to_string(),
+    ///   name: "Rust".to_string(),
+    ///   description: Some("Everything about Rust".to_string()),
+    ///   moderators: vec![env!("PUBLIC_KEY").to_string()],
+    ///   relays: vec![env!("RELAY_URL").to_string()],
+    /// };
+    ///
+    /// client.publish_community(&identity, &community, 0).unwrap();
+    /// ```
+    pub fn publish_community(
+        &mut self,
+        signer: &dyn Signer,
+        community: &Community,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP72Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| community_event(pub_key, community),
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Publish a community definition (kind 34550)
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity, nips::nip72::Community};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_publish_community() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let community = Community {
+    ///       identifier: "rust".to_string(),
+    ///       name: "Rust".to_string(),
+    ///       description: Some("Everything about Rust".to_string()),
+    ///       moderators: vec![env!("PUBLIC_KEY").to_string()],
+    ///       relays: vec![env!("RELAY_URL").to_string()],
+    ///     };
+    ///
+    ///     client.publish_community(&identity, &community, 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn publish_community(
+        &mut self,
+        signer: &dyn Signer,
+        community: &Community,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP72Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| community_event(pub_key, community),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Post a text note (kind 1) to a community, given by its coordinate
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let community = format!("34550:{}:rust", env!("PUBLIC_KEY"));
+    /// client.post_to_community(&identity, &community, "Hello Rustaceans!", 0).unwrap();
+    /// ```
+    pub fn post_to_community(
+        &mut self,
+        signer: &dyn Signer,
+        community_coordinate: &str,
+        content: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP72Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| community_post_event(pub_key, community_coordinate, content),
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Post a text note (kind 1) to a community, given by its coordinate
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_post_to_community() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let community = format!("34550:{}:rust", env!("PUBLIC_KEY"));
+    ///     client.post_to_community(&identity, &community, "Hello Rustaceans!", 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn post_to_community(
+        &mut self,
+        signer: &dyn Signer,
+        community_coordinate: &str,
+        content: &str,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP72Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| community_post_event(pub_key, community_coordinate, content),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Approve a community post (kind 4550), the approved post is embedded in the content
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let community = format!("34550:{}:rust", env!("PUBLIC_KEY"));
+    /// let post = client.post_to_community(&identity, &community, "Hello Rustaceans!", 0).unwrap();
+    /// client.approve_community_post(&identity, &community, &post, 0).unwrap();
+    /// ```
+    pub fn approve_community_post(
+        &mut self,
+        signer: &dyn Signer,
+        community_coordinate: &str,
+        post: &Event,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP72Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| approval_event(pub_key, community_coordinate, post),
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Approve a community post (kind 4550), the approved post is embedded in the content
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_approve_community_post() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let community = format!("34550:{}:rust", env!("PUBLIC_KEY"));
+    ///     let post = client.post_to_community(&identity, &community, "Hello Rustaceans!", 0).await.unwrap();
+    ///     client.approve_community_post(&identity, &community, &post, 0).await.unwrap();
+    /// }
+    /// ```
+    pub async fn approve_community_post(
+        &mut self,
+        signer: &dyn Signer,
+        community_coordinate: &str,
+        post: &Event,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP72Error> {
+        self.send_template(
+            signer,
+            |pub_key: &str| approval_event(pub_key, community_coordinate, post),
+            difficulty_target,
+        )
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get the approved posts of a community
+    ///
+    /// Only the approvals of the community creator and of the given moderators are taken into
+    /// account, whatever the relays send, see `approved_posts`
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    ///
+    /// let community = format!("34550:{}:rust", env!("PUBLIC_KEY"));
+    /// let posts = client.get_community_approved_posts(&community, &[]).unwrap();
+    /// ```
+    pub fn get_community_approved_posts(
+        &mut self,
+        community_coordinate: &str,
+        moderators: &[String],
+    ) -> Result<Vec<Event>, NIP72Error> {
+        let community = validate_community_coordinate(community_coordinate)?;
+        let approvers = community_approvers(&community.pub_key, moderators);
+        let approvals = self.get_events_of(vec![approvals_filter(&approvers)])?;
+
+        Ok(approved_posts(&approvals, community_coordinate, &approvers))
+    }
+
+    #[cfg(feature = "async")]
+    /// Get the approved posts of a community
+    ///
+    /// Only the approvals of the community creator and of the given moderators are taken into
+    /// account, whatever the relays send, see `approved_posts`
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// #[tokio::test]
+    /// async fn test_get_community_approved_posts() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///
+    ///     let community = format!("34550:{}:rust", env!("PUBLIC_KEY"));
+    ///     let posts = client.get_community_approved_posts(&community, &[]).await.unwrap();
+    /// }
+    /// ```
+    pub async fn get_community_approved_posts(
+        &mut self,
+        community_coordinate: &str,
+        moderators: &[String],
+    ) -> Result<Vec<Event>, NIP72Error> {
+        let community = validate_community_coordinate(community_coordinate)?;
+        let approvers = community_approvers(&community.pub_key, moderators);
+        let approvals = self
+            .get_events_of(vec![approvals_filter(&approvers)])
+            .await?;
+
+        Ok(approved_posts(&approvals, community_coordinate, &approvers))
+    }
+}
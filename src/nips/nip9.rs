@@ -1,23 +1,119 @@
+use crate::{events::Event, utils::tag_value};
+#[cfg(feature = "net")]
 use crate::{
-    events::{Event, EventPrepare},
+    events::EventPrepare,
     nostr_client::{Client, ClientError},
+    signer::Signer,
     utils::get_timestamp,
-    Identity,
 };
 use thiserror::Error;
 
+// Implementation of the NIP9 protocol
+// https://github.com/nostr-protocol/nips/blob/master/09.md
+
+/// Kind of a deletion event
+pub const DELETION_KIND: u16 = 5;
+
 #[derive(Error, Debug)]
 pub enum NIP9Error {
+    #[cfg(feature = "net")]
     #[error("The client has an error")]
     ClientError(ClientError),
 }
 
+#[cfg(feature = "net")]
 impl From<ClientError> for NIP9Error {
     fn from(err: ClientError) -> Self {
         Self::ClientError(err)
     }
 }
 
+/// References of the events a deletion asks to delete: the ids of its "e" tags and the
+/// coordinates (`<kind>:<pubkey>:<identifier>`) of its "a" tags
+///
+/// Events which are not deletions (kind 5) reference nothing
+pub fn deletion_targets(event: &Event) -> Vec<String> {
+    if event.kind != DELETION_KIND {
+        return Vec::new();
+    }
+
+    event
+        .tags
+        .iter()
+        .filter(|tag| matches!(tag_value(tag, 0), Some("e" | "a")))
+        .filter_map(|tag| tag_value(tag, 1).map(str::to_string))
+        .collect()
+}
+
+/// Check if a deletion may delete the target event: it references the target and has the same
+/// author
+///
+/// An "a" tag only deletes the versions of the replaceable event published before the deletion.
+/// Deletions published by another public key than the author of the target must be ignored.
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::Event, nips::nip9::{authorizes_deletion_of, deletion_targets}};
+///
+/// let event = |id: &str, pub_key: &str, kind: u16, created_at: u64, tags: Vec<Vec<&str>>| Event {
+///   id: id.to_string(),
+///   pub_key: pub_key.to_string(),
+///   created_at,
+///   kind,
+///   tags: tags.into_iter().map(|tag| tag.into_iter().map(str::to_string).collect()).collect(),
+///   content: String::new(),
+///   sig: String::new(),
+/// };
+///
+/// let note = event("note", "alice", 1, 10, vec![]);
+/// let article = event("article", "alice", 30023, 10, vec![vec!["d", "intro"]]);
+/// let deletion = event("del", "alice", 5, 20, vec![vec!["e", "note"], vec!["a", "30023:alice:intro"]]);
+///
+/// assert_eq!(deletion_targets(&deletion), vec!["note", "30023:alice:intro"]);
+/// assert!(authorizes_deletion_of(&deletion, &note));
+/// assert!(authorizes_deletion_of(&deletion, &article));
+///
+/// // Another author can't delete the events of alice
+/// let forged = event("forged", "mallory", 5, 20, vec![vec!["e", "note"], vec!["a", "30023:alice:intro"]]);
+/// assert!(!authorizes_deletion_of(&forged, &note));
+/// assert!(!authorizes_deletion_of(&forged, &article));
+///
+/// // A newer version of the article is not deleted
+/// let newer_article = event("article2", "alice", 30023, 30, vec![vec!["d", "intro"]]);
+/// assert!(!authorizes_deletion_of(&deletion, &newer_article));
+/// ```
+pub fn authorizes_deletion_of(deletion: &Event, target: &Event) -> bool {
+    if deletion.kind != DELETION_KIND || deletion.pub_key != target.pub_key {
+        return false;
+    }
+
+    let coordinate = format!(
+        "{}:{}:{}",
+        target.kind,
+        target.pub_key,
+        target.first_tag_value("d").unwrap_or("")
+    );
+
+    deletion.tags.iter().any(|tag| match tag.as_slice() {
+        [name, id, ..] if name == "e" => id == &target.id,
+        [name, address, ..] if name == "a" => {
+            address == &coordinate && target.created_at <= deletion.created_at
+        }
+        _ => false,
+    })
+}
+
+#[cfg(feature = "net")]
+fn deletion_event(pub_key: &str, event_id: &str, reason: &str) -> Result<EventPrepare, NIP9Error> {
+    Ok(EventPrepare {
+        pub_key: pub_key.to_string(),
+        created_at: get_timestamp(),
+        kind: DELETION_KIND,
+        tags: vec![vec!["e".to_string(), event_id.to_string()]],
+        content: reason.to_string(),
+    })
+}
+
+#[cfg(feature = "net")]
 impl Client {
     #[cfg(not(feature = "async"))]
     /// Delete an event
@@ -37,11 +133,11 @@ impl Client {
     /// ```
     pub fn delete_event(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         difficulty_target: u16,
     ) -> Result<Event, NIP9Error> {
-        self.delete_event_with_reason(identity, event_id, "", difficulty_target)
+        self.delete_event_with_reason(signer, event_id, "", difficulty_target)
     }
 
     #[cfg(feature = "async")]
@@ -66,11 +162,11 @@ impl Client {
     /// ```
     pub async fn delete_event(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         difficulty_target: u16,
     ) -> Result<Event, NIP9Error> {
-        self.delete_event_with_reason(identity, event_id, "", difficulty_target)
+        self.delete_event_with_reason(signer, event_id, "", difficulty_target)
             .await
     }
 
@@ -92,22 +188,16 @@ impl Client {
     /// ```
     pub fn delete_event_with_reason(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         reason: &str,
         difficulty_target: u16,
     ) -> Result<Event, NIP9Error> {
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 5,
-            tags: vec![vec!["e".to_string(), event_id.to_string()]],
-            content: reason.to_string(),
-        }
-        .to_event(identity, difficulty_target);
-
-        self.publish_event(&event)?;
-        Ok(event)
+        self.send_template(
+            signer,
+            |pub_key: &str| deletion_event(pub_key, event_id, reason),
+            difficulty_target,
+        )
     }
 
     #[cfg(feature = "async")]
@@ -132,21 +222,16 @@ impl Client {
     /// ```
     pub async fn delete_event_with_reason(
         &mut self,
-        identity: &Identity,
+        signer: &dyn Signer,
         event_id: &str,
         reason: &str,
         difficulty_target: u16,
     ) -> Result<Event, NIP9Error> {
-        let event = EventPrepare {
-            pub_key: identity.public_key_str.clone(),
-            created_at: get_timestamp(),
-            kind: 5,
-            tags: vec![vec!["e".to_string(), event_id.to_string()]],
-            content: reason.to_string(),
-        }
-        .to_event(identity, difficulty_target);
-
-        self.publish_event(&event).await?;
-        Ok(event)
+        self.send_template(
+            signer,
+            |pub_key: &str| deletion_event(pub_key, event_id, reason),
+            difficulty_target,
+        )
+        .await
     }
 }
@@ -0,0 +1,91 @@
+use crate::{events::Event, Identity};
+use base64::Engine;
+use thiserror::Error;
+use url::Url;
+
+// Implementation of the NIP98 protocol
+// https://github.com/nostr-protocol/nips/blob/master/98.md
+
+/// Kind of an HTTP auth event
+pub const HTTP_AUTH_KIND: u16 = 27235;
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum NIP98Error {
+    #[error("The url \"{}\" is not absolute", _0)]
+    InvalidUrl(String),
+
+    #[error("The HTTP method \"{}\" must be uppercase", _0)]
+    InvalidMethod(String),
+}
+
+impl Identity {
+    /// Make an HTTP auth event (kind 27235) for a request to `url` with the HTTP `method`
+    ///
+    /// `payload_hash` is the hex encoded sha256 of the request body, if any
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nips::nip98::to_authorization_header, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = identity.http_auth_event("https://media.example/upload", "PUT", None, 0).unwrap();
+    ///
+    /// assert_eq!(event.kind, 27235);
+    /// assert_eq!(event.first_tag_value("u"), Some("https://media.example/upload"));
+    /// assert_eq!(event.first_tag_value("method"), Some("PUT"));
+    /// assert!(to_authorization_header(&event).starts_with("Nostr "));
+    ///
+    /// assert!(identity.http_auth_event("/upload", "PUT", None, 0).is_err());
+    /// assert!(identity.http_auth_event("https://media.example/upload", "put", None, 0).is_err());
+    /// ```
+    pub fn http_auth_event(
+        &self,
+        url: &str,
+        method: &str,
+        payload_hash: Option<&str>,
+        difficulty_target: u16,
+    ) -> Result<Event, NIP98Error> {
+        if !Url::parse(url).is_ok_and(|url| url.has_host()) {
+            return Err(NIP98Error::InvalidUrl(url.to_string()));
+        }
+
+        if method.is_empty() || !method.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(NIP98Error::InvalidMethod(method.to_string()));
+        }
+
+        let mut tags = vec![
+            vec!["u".to_string(), url.to_string()],
+            vec!["method".to_string(), method.to_string()],
+        ];
+
+        if let Some(payload_hash) = payload_hash {
+            tags.push(vec!["payload".to_string(), payload_hash.to_string()]);
+        }
+
+        Ok(self.make_event(HTTP_AUTH_KIND, "", &tags, difficulty_target))
+    }
+}
+
+/// Value of the `Authorization` HTTP header for an HTTP auth event: `Nostr <base64 event>`
+///
+/// # Example
+/// ```rust
+/// use nostr_rust::{events::Event, nips::nip98::to_authorization_header, Identity};
+/// use base64::Engine;
+/// use std::str::FromStr;
+///
+/// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+/// let event = identity.http_auth_event("https://media.example/upload", "POST", Some("abcd"), 0).unwrap();
+///
+/// let header = to_authorization_header(&event);
+/// let decoded = base64::prelude::BASE64_STANDARD.decode(header.trim_start_matches("Nostr ")).unwrap();
+/// let decoded: Event = serde_json::from_slice(&decoded).unwrap();
+/// assert_eq!(decoded.id, event.id);
+/// assert_eq!(decoded.first_tag_value("payload"), Some("abcd"));
+/// ```
+pub fn to_authorization_header(event: &Event) -> String {
+    format!(
+        "Nostr {}",
+        base64::prelude::BASE64_STANDARD.encode(serde_json::json!(event).to_string())
+    )
+}
@@ -1,11 +1,21 @@
-use crate::events::Event;
+use crate::cache::{EventCache, NoCache};
+use crate::events::EventPrepare;
+use crate::events::{Event, KindClass};
+use crate::http::HttpConfig;
 use crate::req::{Req, ReqFilter};
-use crate::websocket::{self, SimplifiedWS};
+use crate::signer::{Signer, SignerError};
+use crate::utils::{get_timestamp, random_hash};
+use crate::websocket::{self, SimplifiedWS, SizeLimits};
 use crate::Message;
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use url::Url;
 
 #[derive(Error, Debug)]
 pub enum ClientError {
@@ -18,8 +28,32 @@ pub enum ClientError {
     #[error("Relay does not exist")]
     RelayDoesNotExist,
 
+    #[error("Subscription does not exist")]
+    SubscriptionDoesNotExist,
+
+    #[error("The relay {} has reached its maximum number of subscriptions", _0)]
+    TooManySubscriptions(String),
+
+    #[error(
+        "Every open connection has a subscription, none can be closed to connect to {}",
+        _0
+    )]
+    NoIdleConnection(String),
+
+    #[error("The raw event is invalid: {}", _0)]
+    InvalidRawEvent(String),
+
+    #[error("The relay {} did not answer in time", _0)]
+    Timeout(String),
+
+    #[error("The relay {} closed the subscription: {}", _0, _1)]
+    SubscriptionClosed(String, String),
+
     #[error("Serde Error: {}", _0)]
     SerdeError(#[from] serde_json::Error),
+
+    #[error("Signer Error: {}", _0)]
+    SignerError(#[from] SignerError),
 }
 
 impl From<websocket::SimplifiedWSError> for ClientError {
@@ -28,34 +62,505 @@ impl From<websocket::SimplifiedWSError> for ClientError {
     }
 }
 
+/// Maximum number of rejected events kept by the client, see `Client::take_rejected_events`
+const MAX_REJECTED_EVENTS: usize = 100;
+
+/// Maximum number of events whose OK answers are kept by the client until `Client::wait_for_ok`
+const MAX_OK_ANSWERS: usize = 100;
+
+/// Maximum number of messages read from each relay by a call of `Client::next_data_all`, so a
+/// busy relay doesn't starve the others
+const MAX_MESSAGES_PER_READ: usize = 100;
+
+/// Maximum number of ids generated by `Client::subscribe` to find one not used by an open
+/// subscription, a custom strategy may keep returning the same id
+const MAX_SUBSCRIPTION_ID_ATTEMPTS: usize = 100;
+
+/// Message of a relay, parsed once by the reads of the client
+enum RelayMessage {
+    /// EVENT message with its subscription id and its event
+    Event(String, Event),
+    /// EVENT message whose event is invalid, with its subscription id
+    Rejected(String),
+    /// EOSE message with its subscription id
+    Eose(String),
+    /// CLOSED message with its subscription id and its reason
+    Closed(String, String),
+    /// NOTICE message
+    Notice(String),
+    /// OK message with the event id and the answer of the relay
+    Ok(String, RelayAck),
+    /// Any other message
+    Other,
+}
+
+impl RelayMessage {
+    /// Parse a message of a relay, an EVENT message (`["EVENT", <subscription_id>, <event>]`)
+    /// in one pass
+    fn parse(message: &Message) -> Self {
+        let Ok(text) = message.to_text() else {
+            return Self::Other;
+        };
+
+        if let Ok((kind, subscription_id, event)) =
+            serde_json::from_str::<(&str, String, Event)>(text)
+        {
+            if kind == "EVENT" {
+                return Self::Event(subscription_id, event);
+            }
+        }
+
+        let Ok(value) = serde_json::from_str::<Value>(text) else {
+            return Self::Other;
+        };
+        let string = |index: usize| value[index].as_str().unwrap_or_default().to_string();
+
+        match value[0].as_str() {
+            Some("EVENT") => Self::Rejected(string(1)),
+            Some("EOSE") => Self::Eose(string(1)),
+            Some("CLOSED") => Self::Closed(string(1), string(2)),
+            Some("NOTICE") => Self::Notice(string(1)),
+            Some("OK") => match (value[1].as_str(), value[2].as_bool()) {
+                (Some(event_id), Some(accepted)) => Self::Ok(
+                    event_id.to_string(),
+                    RelayAck {
+                        accepted,
+                        message: string(3),
+                    },
+                ),
+                _ => Self::Other,
+            },
+            _ => Self::Other,
+        }
+    }
+}
+
+/// NIP-40 expiration of a message stored in a subscription, see `Client::get_events`
+#[derive(Debug, Clone, Copy)]
+enum StoredExpiration {
+    /// Read from the event parsed when the message was received, `None` if it never expires
+    Known(Option<u64>),
+    /// The message was stored by `Client::add_event`, without parsing it
+    Unknown,
+}
+
+/// Filters matching only the events created from now
+fn live_filters(filters: Vec<ReqFilter>) -> Vec<ReqFilter> {
+    let now = get_timestamp();
+
+    filters
+        .into_iter()
+        .map(|filter| ReqFilter {
+            since: Some(now),
+            ..filter
+        })
+        .collect()
+}
+
+/// How `Client::subscribe` generates the subscription ids
+#[derive(Debug, Clone, Copy, Default)]
+pub enum SubscriptionIdStrategy {
+    /// 64 characters random hash (default)
+    #[default]
+    Random,
+    /// "sub-1", "sub-2", ...
+    Incremental,
+    /// Id built from the number of the subscription, starting at 1
+    Custom(fn(u64) -> String),
+}
+
+/// Unsigned event built by a client method from the public key of the signer
+///
+/// The NIP helpers prepare their events with templates, signed with `Client::sign_template` or
+/// published with `Client::send_template`, so the sync and async clients send the same events.
+/// Any `FnOnce(&str) -> Result<EventPrepare, E>` is a template.
+pub trait EventTemplate {
+    type Error: From<ClientError>;
+
+    /// Check the parameters and prepare the event of `pub_key`
+    fn prepare(self, pub_key: &str) -> Result<EventPrepare, Self::Error>;
+}
+
+impl<F, E> EventTemplate for F
+where
+    F: FnOnce(&str) -> Result<EventPrepare, E>,
+    E: From<ClientError>,
+{
+    type Error = E;
+
+    fn prepare(self, pub_key: &str) -> Result<EventPrepare, E> {
+        self(pub_key)
+    }
+}
+
+/// Token bucket spacing out the published events
+struct RateLimiter {
+    events_per_sec: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    fn new(events_per_sec: f64) -> Self {
+        Self {
+            events_per_sec,
+            tokens: events_per_sec.max(1.0),
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take a token and return how long to wait before it is available
+    fn reserve(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.events_per_sec).min(self.events_per_sec.max(1.0));
+        self.last_refill = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.events_per_sec)
+        }
+    }
+}
+
+/// Keep the `limit` newest events, sorted newest first
+fn keep_newest(events: &mut Vec<Event>, limit: usize) {
+    events.sort_by_key(|event| (Reverse(event.created_at), event.id.clone()));
+    events.truncate(limit);
+}
+
+/// Key deduplicating the messages of a subscription: a hash of the raw message, without parsing it
+fn message_key(message: &Message) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    match message {
+        Message::Text(text) => text.hash(&mut hasher),
+        Message::Binary(data) => data.hash(&mut hasher),
+        other => other.to_string().hash(&mut hasher),
+    }
+
+    hasher.finish()
+}
+
+/// Events sent by at least `min_relays` relays, newest first, the failing relays are ignored
+fn quorum_events(events_per_relay: EventsPerRelay, min_relays: usize) -> Vec<Event> {
+    let mut relay_counts: HashMap<String, (usize, Event)> = HashMap::new();
+
+    for events in events_per_relay.into_values().filter_map(Result::ok) {
+        // A relay sending an event twice counts once
+        let mut relay_ids: HashSet<String> = HashSet::new();
+
+        for event in events {
+            if relay_ids.insert(event.id.clone()) {
+                relay_counts.entry(event.id.clone()).or_insert((0, event)).0 += 1;
+            }
+        }
+    }
+
+    let mut events: Vec<Event> = relay_counts
+        .into_values()
+        .filter(|(count, _)| *count >= min_relays)
+        .map(|(_, event)| event)
+        .collect();
+    events.sort_by_key(|event| (Reverse(event.created_at), event.id.clone()));
+
+    events
+}
+
+/// Filter of the candidates of `Client::get_latest_replaceable`
+fn latest_replaceable_filter(pubkey: &str, kind: u16, d: Option<&str>) -> ReqFilter {
+    ReqFilter {
+        authors: Some(vec![pubkey.to_string()]),
+        kinds: Some(vec![kind]),
+        d: (KindClass::of(kind) == KindClass::Addressable)
+            .then(|| vec![d.unwrap_or_default().to_string()]),
+        ..Default::default()
+    }
+}
+
+/// Newest event sent by any relay, the lowest id wins a tie (NIP-01), the failing relays are ignored
+fn latest_replaceable_event(events_per_relay: EventsPerRelay) -> Option<Event> {
+    events_per_relay
+        .into_values()
+        .filter_map(Result::ok)
+        .flatten()
+        .min_by(|a, b| (Reverse(a.created_at), &a.id).cmp(&(Reverse(b.created_at), &b.id)))
+}
+
+/// Fields every event must have (NIP-01)
+const EVENT_FIELDS: [&str; 7] = [
+    "id",
+    "pubkey",
+    "created_at",
+    "kind",
+    "tags",
+    "content",
+    "sig",
+];
+
+/// EVENT message of an event given as JSON, embedded verbatim
+fn raw_event_message(json: &str) -> Result<Message, ClientError> {
+    let value: Value = serde_json::from_str(json)
+        .map_err(|error| ClientError::InvalidRawEvent(error.to_string()))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| ClientError::InvalidRawEvent("not a JSON object".to_string()))?;
+
+    if let Some(field) = EVENT_FIELDS
+        .iter()
+        .find(|field| !object.contains_key(**field))
+    {
+        return Err(ClientError::InvalidRawEvent(format!(
+            "missing \"{}\"",
+            field
+        )));
+    }
+
+    Ok(Message::text(format!("[\"EVENT\",{}]", json.trim())))
+}
+
+/// Delay between two reads of the relays while waiting for EOSE
+const EOSE_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Time given to each relay to send EOSE, see `Client::set_query_timeout`
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Callback of `Client::on_notice`, called with the relay and the message
+pub type NoticeCallback = Box<dyn FnMut(&str, &str) + Send>;
+
+/// Callback of `Client::on_closed`, called with the relay, the subscription id and the reason
+pub type ClosedCallback = Box<dyn FnMut(&str, &str, &str) + Send>;
+
+/// Result of sending a message to a relay, see `Client::publish_to_recipient`
+pub type RelayResult = (String, Result<(), ClientError>);
+
+/// Relay which failed, with its error, see `Client::last_failed_relays`
+type RelayError = (String, ClientError);
+
+/// Events of each relay, or the error of the relay, see `Client::get_events_of_per_relay`
+pub type EventsPerRelay = HashMap<String, Result<Vec<Event>, ClientError>>;
+
+/// Result of `Client::wait_for_eose`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EoseStatus {
+    /// Relays which sent EOSE for the subscription
+    pub received: Vec<String>,
+    /// Relays which refused or ended the subscription (CLOSED) instead of sending EOSE, with
+    /// their reason, e.g. "auth-required: ..."
+    pub closed: HashMap<String, String>,
+    /// Relays which didn't send EOSE before the timeout
+    pub timed_out: Vec<String>,
+}
+
+impl EoseStatus {
+    fn mark(&mut self, relay: String, answer: SubscriptionAnswer) {
+        let Some(index) = self.timed_out.iter().position(|r| r == &relay) else {
+            return;
+        };
+        self.timed_out.remove(index);
+
+        match answer {
+            SubscriptionAnswer::Eose => self.received.push(relay),
+            SubscriptionAnswer::Closed(reason) => {
+                self.closed.insert(relay, reason);
+            }
+        }
+    }
+}
+
+/// End of the stored events of a subscription sent by a relay
+enum SubscriptionAnswer {
+    /// EOSE: every stored event was sent
+    Eose,
+    /// CLOSED: the relay refused or ended the subscription, with its reason
+    Closed(String),
+}
+
+/// What the relays sent for a query, see `Client::wait_for_query`
+#[derive(Default)]
+struct QueryAnswers {
+    /// Events of the query, with their relay
+    events: Vec<(String, Event)>,
+    /// Relays which sent EOSE
+    answered_relays: Vec<String>,
+    /// Relays which can't be reached, closed the subscription or timed out, with their error
+    errors: HashMap<String, ClientError>,
+}
+
+/// Answer of a relay to a published event (NIP-01 "OK" message)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayAck {
+    /// Whether the relay accepted the event
+    pub accepted: bool,
+    /// Message of the relay, e.g. "duplicate: already have this event", empty if none
+    pub message: String,
+}
+
+/// Result of `Client::wait_for_ok`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OkStatus {
+    /// Answers of the relays which sent OK for the event
+    pub received: HashMap<String, RelayAck>,
+    /// Relays which didn't send OK before the timeout
+    pub timed_out: Vec<String>,
+}
+
 #[cfg(not(feature = "async"))]
 /// Nostr Client
 pub struct Client {
+    /// Open connections
     pub relays: HashMap<String, Arc<std::sync::Mutex<SimplifiedWS>>>,
     pub subscriptions: HashMap<String, Vec<Message>>,
+    /// Keys of the messages stored in each subscription, see `message_key`
+    subscription_keys: HashMap<String, HashMap<u64, StoredExpiration>>,
+    /// Filters of the open subscriptions
+    subscription_filters: HashMap<String, Vec<ReqFilter>>,
+    /// Open subscriptions of each relay
+    relay_subscriptions: HashMap<String, HashSet<String>>,
+    /// Maximum number of open subscriptions of each relay, see `Client::set_max_subscriptions`
+    max_subscriptions: HashMap<String, usize>,
+    /// Every relay added to the client, connected or not
+    relay_urls: Vec<String>,
+    /// Maximum number of open connections, None to connect every relay eagerly
+    max_connections: Option<usize>,
+    last_used: HashMap<String, Instant>,
+    cache: Box<dyn EventCache>,
+    /// Raw EVENT messages whose event couldn't be parsed
+    rejected_events: Vec<String>,
+    /// OK answers of the relays received by any read, by event id then relay, oldest event first
+    ok_answers: Vec<(String, HashMap<String, RelayAck>)>,
+    /// Limit of published events per second, None for unlimited
+    publish_rate_limit: Option<RateLimiter>,
+    /// Time taken by each relay to send EOSE during the last `get_events_of`
+    query_timings: HashMap<String, Duration>,
+    /// Time given to each relay to send EOSE by the queries waiting for every relay
+    query_timeout: Duration,
+    /// Relays left out of the last subscription, every connection allowed being busy
+    skipped_relays: Vec<String>,
+    /// Relays which failed to receive the last subscription or published event, with their error
+    failed_relays: Vec<(String, ClientError)>,
+    /// Name added in a "client" tag to the events built by the client
+    client_tag: Option<String>,
+    subscription_id_strategy: SubscriptionIdStrategy,
+    /// Number of subscriptions created by `subscribe`
+    subscription_count: u64,
+    /// Trim the result of `get_events_of` to the limit of the filters instead of a limit per relay
+    aggregate_limit: bool,
+    /// Maximum size of the messages received from the relays
+    size_limits: SizeLimits,
+    /// Difficulty of the proof of work used when a method is given a `difficulty_target` of 0
+    default_difficulty: u16,
+    /// Timeout, retries and User-Agent of the NIP-05 and NIP-11 requests
+    http_config: HttpConfig,
+    /// Called with the relay and the message of each NOTICE
+    on_notice: Option<NoticeCallback>,
+    /// Called with the relay, the subscription id and the reason of each CLOSED
+    on_closed: Option<ClosedCallback>,
 }
 
 #[cfg(feature = "async")]
 /// Nostr Client
 pub struct Client {
+    /// Open connections
     pub relays: HashMap<String, Arc<tokio::sync::Mutex<SimplifiedWS>>>,
     pub subscriptions: HashMap<String, Vec<Message>>,
+    /// Keys of the messages stored in each subscription, see `message_key`
+    subscription_keys: HashMap<String, HashMap<u64, StoredExpiration>>,
+    /// Filters of the open subscriptions
+    subscription_filters: HashMap<String, Vec<ReqFilter>>,
+    /// Open subscriptions of each relay
+    relay_subscriptions: HashMap<String, HashSet<String>>,
+    /// Maximum number of open subscriptions of each relay, see `Client::set_max_subscriptions`
+    max_subscriptions: HashMap<String, usize>,
+    /// Every relay added to the client, connected or not
+    relay_urls: Vec<String>,
+    /// Maximum number of open connections, None to connect every relay eagerly
+    max_connections: Option<usize>,
+    last_used: HashMap<String, Instant>,
+    cache: Box<dyn EventCache>,
+    /// Raw EVENT messages whose event couldn't be parsed
+    rejected_events: Vec<String>,
+    /// OK answers of the relays received by any read, by event id then relay, oldest event first
+    ok_answers: Vec<(String, HashMap<String, RelayAck>)>,
+    /// Limit of published events per second, None for unlimited
+    publish_rate_limit: Option<RateLimiter>,
+    /// Time taken by each relay to send EOSE during the last `get_events_of`
+    query_timings: HashMap<String, Duration>,
+    /// Time given to each relay to send EOSE by the queries waiting for every relay
+    query_timeout: Duration,
+    /// Relays left out of the last subscription, every connection allowed being busy
+    skipped_relays: Vec<String>,
+    /// Relays which failed to receive the last subscription or published event, with their error
+    failed_relays: Vec<(String, ClientError)>,
+    /// Name added in a "client" tag to the events built by the client
+    client_tag: Option<String>,
+    subscription_id_strategy: SubscriptionIdStrategy,
+    /// Number of subscriptions created by `subscribe`
+    subscription_count: u64,
+    /// Trim the result of `get_events_of` to the limit of the filters instead of a limit per relay
+    aggregate_limit: bool,
+    /// Maximum size of the messages received from the relays
+    size_limits: SizeLimits,
+    /// Difficulty of the proof of work used when a method is given a `difficulty_target` of 0
+    default_difficulty: u16,
+    /// Timeout, retries and User-Agent of the NIP-05 and NIP-11 requests
+    http_config: HttpConfig,
+    /// Called with the relay and the message of each NOTICE
+    on_notice: Option<NoticeCallback>,
+    /// Called with the relay, the subscription id and the reason of each CLOSED
+    on_closed: Option<ClosedCallback>,
+}
+
+impl Default for Client {
+    /// Client without relay, see `Client::add_relay`
+    fn default() -> Self {
+        Self {
+            relays: HashMap::new(),
+            subscriptions: HashMap::new(),
+            subscription_keys: HashMap::new(),
+            subscription_filters: HashMap::new(),
+            relay_subscriptions: HashMap::new(),
+            max_subscriptions: HashMap::new(),
+            relay_urls: Vec::new(),
+            max_connections: None,
+            last_used: HashMap::new(),
+            cache: Box::new(NoCache),
+            rejected_events: Vec::new(),
+            ok_answers: Vec::new(),
+            publish_rate_limit: None,
+            query_timings: HashMap::new(),
+            query_timeout: DEFAULT_QUERY_TIMEOUT,
+            skipped_relays: Vec::new(),
+            failed_relays: Vec::new(),
+            client_tag: None,
+            subscription_id_strategy: SubscriptionIdStrategy::Random,
+            subscription_count: 0,
+            aggregate_limit: false,
+            size_limits: SizeLimits::default(),
+            default_difficulty: 0,
+            http_config: HttpConfig::default(),
+            on_notice: None,
+            on_closed: None,
+        }
+    }
 }
 
 impl Client {
     #[cfg(not(feature = "async"))]
     /// Create a new client with a list of default relays
     ///
+    /// Fails if any relay fails to connect, see `Client::try_new_partial` to ignore the unreachable relays
+    ///
     /// # Example
     /// ```rust
     /// use nostr_rust::nostr_client::Client;
     /// let client = Client::new(vec![env!("RELAY_URL")]).unwrap();
     /// ```
     pub fn new(default_relays: Vec<&str>) -> Result<Self, ClientError> {
-        let mut client = Self {
-            relays: HashMap::new(),
-            subscriptions: HashMap::new(),
-        };
+        let mut client = Self::default();
 
         for relay in default_relays {
             client.add_relay(relay)?;
@@ -67,6 +572,8 @@ impl Client {
     #[cfg(feature = "async")]
     /// Create a new client with a list of default relays
     ///
+    /// Fails if any relay fails to connect, see `Client::try_new_partial` to ignore the unreachable relays
+    ///
     /// # Example
     /// ```rust, async_await
     /// use nostr_rust::nostr_client::Client;
@@ -77,10 +584,7 @@ impl Client {
     /// }
     /// ```
     pub async fn new(default_relays: Vec<&str>) -> Result<Self, ClientError> {
-        let mut client = Self {
-            relays: HashMap::new(),
-            subscriptions: HashMap::new(),
-        };
+        let mut client = Self::default();
 
         for relay in default_relays {
             client.add_relay(relay).await?;
@@ -88,673 +592,3764 @@ impl Client {
 
         Ok(client)
     }
-}
 
-impl Client {
     #[cfg(not(feature = "async"))]
-    /// Add a relay to the client
+    /// Create a new client connected to every relay it can reach
+    ///
+    /// Unlike `Client::new`, a relay failing to connect doesn't prevent the client from being created:
+    /// it is not added and is returned with its error
     /// # Example
     /// ```rust
     /// use nostr_rust::nostr_client::Client;
-    /// let mut client = Client::new(vec![]).unwrap();
-    /// client.add_relay(env!("RELAY_URL")).unwrap();
+    ///
+    /// let (client, failed_relays) = Client::try_new_partial(vec![env!("RELAY_URL"), "not a url"]);
+    /// assert_eq!(failed_relays.len() + client.relay_urls().len(), 2);
+    /// assert_eq!(failed_relays.last().unwrap().0, "not a url");
     /// ```
-    pub fn add_relay(&mut self, relay: &str) -> Result<(), ClientError> {
-        let client = match SimplifiedWS::new(relay) {
-            Ok(client) => client,
-            Err(err) => return Err(ClientError::WSError(err)),
-        };
+    pub fn try_new_partial(default_relays: Vec<&str>) -> (Self, Vec<(String, ClientError)>) {
+        let mut client = Self::default();
+        let mut failed_relays = Vec::new();
 
-        // Check if relay is already added
-        if self.relays.contains_key(relay) {
-            return Err(ClientError::AlreadySubscribed);
+        for relay in default_relays {
+            if let Err(err) = client.add_relay(relay) {
+                failed_relays.push((relay.to_string(), err));
+            }
         }
 
-        self.relays
-            .insert(relay.to_string(), Arc::new(std::sync::Mutex::new(client)));
-
-        Ok(())
+        (client, failed_relays)
     }
 
     #[cfg(feature = "async")]
-    /// Add a relay to the client
+    /// Create a new client connected to every relay it can reach
+    ///
+    /// Unlike `Client::new`, a relay failing to connect doesn't prevent the client from being created:
+    /// it is not added and is returned with its error
     /// # Example
     /// ```rust
     /// use nostr_rust::nostr_client::Client;
     ///
     /// #[tokio::test]
-    /// async fn test_add_relay() {
-    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
-    ///     client.add_relay("wss://relay.damus.io").await.unwrap();
+    /// async fn test_try_new_partial() {
+    ///     let (client, failed_relays) = Client::try_new_partial(vec![env!("RELAY_URL"), "not a url"]).await;
+    ///     assert_eq!(failed_relays.len() + client.relay_urls().len(), 2);
     /// }
     /// ```
-    pub async fn add_relay(&mut self, relay: &str) -> Result<(), ClientError> {
-        let client = match SimplifiedWS::new(relay).await {
-            Ok(client) => client,
-            Err(err) => return Err(ClientError::WSError(err)),
-        };
+    pub async fn try_new_partial(default_relays: Vec<&str>) -> (Self, Vec<(String, ClientError)>) {
+        let mut client = Self::default();
+        let mut failed_relays = Vec::new();
 
-        // Check if relay is already added
-        if self.relays.contains_key(relay) {
-            return Err(ClientError::AlreadySubscribed);
+        for relay in default_relays {
+            if let Err(err) = client.add_relay(relay).await {
+                failed_relays.push((relay.to_string(), err));
+            }
         }
 
-        self.relays
-            .insert(relay.to_string(), Arc::new(tokio::sync::Mutex::new(client)));
-
-        Ok(())
+        (client, failed_relays)
     }
 
-    #[cfg(not(feature = "async"))]
-    /// Remove a relay from the client
+    /// Create a new client connecting to the relays lazily, with at most `max_connections` open
+    /// connections, see `Client::set_max_connections`
+    ///
+    /// No relay is connected by the constructor, so only an invalid url makes it fail
     /// # Example
     /// ```rust
     /// use nostr_rust::nostr_client::Client;
-    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
-    /// client.remove_relay(env!("RELAY_URL")).unwrap();
+    ///
+    /// let client = Client::with_max_connections(vec!["wss://relay.damus.io", "wss://nos.lol"], 1).unwrap();
+    /// assert_eq!(client.relay_urls().len(), 2);
+    /// assert!(client.relays.is_empty());
+    ///
+    /// assert!(Client::with_max_connections(vec!["not a url"], 1).is_err());
     /// ```
-    pub fn remove_relay(&mut self, relay: &str) -> Result<(), ClientError> {
-        if !self.relays.contains_key(relay) {
-            return Err(ClientError::RelayDoesNotExist);
-        }
+    pub fn with_max_connections(
+        default_relays: Vec<&str>,
+        max_connections: usize,
+    ) -> Result<Self, ClientError> {
+        let mut client = Self::default();
+        client.set_max_connections(Some(max_connections));
 
-        // Close the connection
-        self.relays
-            .remove(relay)
-            .unwrap()
-            .lock()
-            .unwrap()
-            .socket
-            .close(None)
-            .unwrap();
+        for relay in default_relays {
+            client.register_relay(relay)?;
+        }
 
-        Ok(())
+        Ok(client)
     }
 
-    #[cfg(feature = "async")]
-    /// Remove a relay from the client
+    /// Connect to the relays lazily with at most `max_connections` open connections, `None` to
+    /// connect every relay when it is added (default)
+    ///
+    /// Relays are then connected on first use and the least recently used connection without
+    /// subscription is closed to make room for a new one. A relay left without connection, every
+    /// open connection having a subscription, fails with `ClientError::NoIdleConnection` while
+    /// the other relays still receive the message, see `Client::last_failed_relays`.
+    ///
     /// # Example
     /// ```rust
-    /// use nostr_rust::nostr_client::Client;
+    /// use nostr_rust::nostr_client::{Client, ClientError};
     ///
-    /// #[tokio::test]
-    /// async fn test_remove_relay() {
-    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
-    ///     client.remove_relay(env!("RELAY_URL")).await.unwrap();
-    /// }
+    /// let mut client = Client::default();
+    /// client.set_max_connections(Some(1));
+    /// # #[cfg(not(feature = "async"))]
+    /// # {
+    /// client.add_relay("wss://relay.damus.io").unwrap();
+    /// client.add_relay("wss://nos.lol").unwrap();
+    /// assert_eq!(client.relay_urls().len(), 2);
+    /// assert!(client.relays.is_empty());
+    ///
+    /// assert!(matches!(client.add_relay("not a url"), Err(ClientError::WSError(_))));
+    /// # }
     /// ```
-    pub async fn remove_relay(&mut self, relay: &str) -> Result<(), ClientError> {
-        if !self.relays.contains_key(relay) {
-            return Err(ClientError::RelayDoesNotExist);
-        }
-
-        // Close the connection
-        self.relays
-            .remove(relay)
-            .unwrap()
-            .lock()
-            .await
-            .socket
-            .close(None)
-            .await
-            .unwrap();
+    pub fn set_max_connections(&mut self, max_connections: Option<usize>) {
+        self.max_connections = max_connections.map(|max_connections| max_connections.max(1));
+    }
 
-        Ok(())
+    /// Set how `Client::subscribe` generates the subscription ids, random hashes by default
+    ///
+    /// Short ids save bandwidth and some relays truncate long ones. An id already used by an
+    /// open subscription is skipped, `Client::subscribe` returns `ClientError::AlreadySubscribed`
+    /// when the strategy finds no free id.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::{Client, ClientError, SubscriptionIdStrategy};
+    ///
+    /// let mut client = Client::default();
+    /// client.set_subscription_id_strategy(SubscriptionIdStrategy::Incremental);
+    /// # #[cfg(not(feature = "async"))]
+    /// # {
+    /// assert_eq!(client.subscribe(vec![]).unwrap(), "sub-1");
+    /// assert_eq!(client.subscribe(vec![]).unwrap(), "sub-2");
+    ///
+    /// client.set_subscription_id_strategy(SubscriptionIdStrategy::Custom(|n| format!("feed{}", n)));
+    /// assert_eq!(client.subscribe(vec![]).unwrap(), "feed3");
+    ///
+    /// client.set_subscription_id_strategy(SubscriptionIdStrategy::Custom(|_| "feed".to_string()));
+    /// assert_eq!(client.subscribe(vec![]).unwrap(), "feed");
+    /// assert!(matches!(client.subscribe(vec![]), Err(ClientError::AlreadySubscribed)));
+    /// # }
+    /// ```
+    pub fn set_subscription_id_strategy(&mut self, strategy: SubscriptionIdStrategy) {
+        self.subscription_id_strategy = strategy;
     }
 
-    #[cfg(not(feature = "async"))]
-    /// Publish a Nostr event
-    pub fn publish_event(&mut self, event: &Event) -> Result<(), ClientError> {
-        let json_stringified = json!(["EVENT", event]).to_string();
-        let message = Message::text(json_stringified);
+    fn next_subscription_id(&mut self) -> Result<String, ClientError> {
+        for _ in 0..MAX_SUBSCRIPTION_ID_ATTEMPTS {
+            self.subscription_count += 1;
+
+            let subscription_id = match self.subscription_id_strategy {
+                SubscriptionIdStrategy::Random => random_hash(),
+                SubscriptionIdStrategy::Incremental => format!("sub-{}", self.subscription_count),
+                SubscriptionIdStrategy::Custom(generate) => generate(self.subscription_count),
+            };
 
-        for relay in self.relays.values() {
-            let mut relay = relay.lock().unwrap();
-            relay.send_message(&message)?;
+            if !self.subscription_filters.contains_key(&subscription_id) {
+                return Ok(subscription_id);
+            }
         }
 
-        Ok(())
+        Err(ClientError::AlreadySubscribed)
     }
 
-    #[cfg(feature = "async")]
-    /// Publish a Nostr event
-    pub async fn publish_event(&mut self, event: &Event) -> Result<(), ClientError> {
-        let json_stringified = json!(["EVENT", event]).to_string();
-        let message = Message::text(json_stringified);
-
-        for relay in self.relays.values() {
-            let mut relay = relay.lock().await;
-            relay.send_message(&message).await?;
-        }
+    /// Add a ["client", name] tag to every event built and signed by the client methods,
+    /// None (the default) to add no tag
+    ///
+    /// A client tag given by the caller is never replaced. Events published with
+    /// `Client::publish_event` are already signed so they are left untouched.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let mut client = Client::default();
+    /// client.set_client_tag(Some("my-app"));
+    /// assert_eq!(client.client_tag(), Some("my-app"));
+    ///
+    /// # #[cfg(not(feature = "async"))]
+    /// # {
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = client.publish(&identity, 1, "Hello Nostr!", &[], 0).unwrap();
+    /// assert_eq!(event.client_name(), Some("my-app"));
+    ///
+    /// let tags = vec![vec!["client".to_string(), "other-app".to_string()]];
+    /// let event = client.publish(&identity, 1, "Hello Nostr!", &tags, 0).unwrap();
+    /// assert_eq!(event.client_name(), Some("other-app"));
+    /// # }
+    /// ```
+    pub fn set_client_tag(&mut self, client_tag: Option<&str>) {
+        self.client_tag = client_tag.map(str::to_string);
+    }
 
-        Ok(())
+    /// Name added in a "client" tag to the events built by the client, see `Client::set_client_tag`
+    pub fn client_tag(&self) -> Option<&str> {
+        self.client_tag.as_deref()
     }
 
-    #[cfg(not(feature = "async"))]
-    /// Get next data from the relays
+    /// Time taken by each relay to send EOSE during the last `Client::get_events_of` or
+    /// `Client::get_events_of_per_relay`
+    ///
+    /// Every relay queried is listed: a relay which didn't send EOSE before the query timeout
+    /// (see `Client::set_query_timeout`) is listed with the query timeout
     /// # Example
     /// ```rust
-    /// use std::{
-    ///  sync::{Arc, Mutex},
-    ///  thread,
-    /// };
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// let client = Client::default();
+    /// assert!(client.last_query_timings().is_empty());
+    /// ```
+    ///
+    /// With a mock relay sending EOSE right away and one sending it late (see
+    /// `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter, websocket::SimplifiedWS};
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, thread, time::Duration};
     /// use tungstenite::Message;
-    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
     ///
-    /// fn handle_message(relay_url: &String, message: &Message) -> Result<(), String> {
-    ///   println!("Received message: {:?}", message);
+    /// // Answer every REQ with EOSE after `delay`
+    /// let spawn_relay = |delay: Duration| {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let url = format!("ws://{}", listener.local_addr().unwrap());
+    ///     thread::spawn(move || {
+    ///         let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
     ///
-    ///   Ok(())
+    ///         while let Ok(message) = relay.read_message() {
+    ///             let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///
+    ///             if request[0] == "REQ" {
+    ///                 thread::sleep(delay);
+    ///                 relay.write_message(Message::text(json!(["EOSE", request[1]]).to_string())).unwrap();
+    ///             }
+    ///         }
+    ///     });
+    ///
+    ///     url
+    /// };
+    ///
+    /// # #[cfg(not(feature = "async"))]
+    /// # {
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// let fast = spawn_relay(Duration::ZERO);
+    /// let slow = spawn_relay(Duration::from_millis(100));
+    /// let silent = spawn_relay(Duration::from_secs(5));
+    /// for url in [&fast, &slow, &silent] {
+    ///     let (socket, _) = tungstenite::connect(url.as_str()).unwrap();
+    ///     client.add_connected_relay(url, SimplifiedWS::from_socket(url, socket).unwrap()).unwrap();
     /// }
     ///
-    /// let mut client = Arc::new(Mutex::new(Client::new(vec![env!("RELAY_URL")]).unwrap()));
+    /// client.set_query_timeout(Duration::from_millis(500));
+    /// client.get_events_of(vec![ReqFilter::default()]).unwrap();
     ///
-    /// // Run a new thread to listen
-    /// let nostr_clone = client.clone();
-    /// let nostr_thread = thread::spawn(move || loop {
-    ///    let events = nostr_clone.lock().unwrap().next_data().unwrap();
-    ///    
-    ///   for (relay_url, message) in events.iter() {
-    ///     handle_message(relay_url, message).unwrap();
-    ///   }
-    /// });
+    /// let timings = client.last_query_timings();
+    /// assert!(timings[&fast] < timings[&slow]);
+    /// assert!(timings[&slow] >= Duration::from_millis(100));
+    /// assert_eq!(timings[&silent], Duration::from_millis(500));
+    /// assert_eq!(client.last_failed_relays()[0].0, silent);
+    /// # }
+    /// ```
+    pub fn last_query_timings(&self) -> &HashMap<String, Duration> {
+        &self.query_timings
+    }
+
+    /// Relays left out of the last `Client::subscribe` (and of `Client::get_events_of`, built on
+    /// it): with `Client::set_max_connections`, the open connections then the other relays
+    /// receive the subscription until every connection allowed has one
     ///
-    /// // Subscribe to the most beautiful Nostr profile event
-    /// client
-    /// .lock()
+    /// A relay which can't be reached doesn't take a connection, the next relay is tried instead.
+    /// `Client::get_events_of_per_relay` queries the relays batch by batch instead of leaving
+    /// them out
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    /// use std::{net::TcpListener, thread};
+    ///
+    /// // Mock relay accepting the connection
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let url = format!("ws://{}", listener.local_addr().unwrap());
+    /// thread::spawn(move || {
+    ///     let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///     while relay.read_message().is_ok() {}
+    /// });
+    ///
+    /// let mut client = Client::with_max_connections(vec!["ws://127.0.0.1:1", url.as_str(), "ws://127.0.0.1:2"], 1).unwrap();
+    /// # #[cfg(not(feature = "async"))]
+    /// # {
+    /// // The first relay can't be reached, the second one takes the connection, the third one is left out
+    /// client.subscribe(vec![]).unwrap();
+    /// assert_eq!(client.last_failed_relays()[0].0, "ws://127.0.0.1:1");
+    /// assert_eq!(client.last_skipped_relays(), ["ws://127.0.0.1:2"]);
+    /// # }
+    /// ```
+    pub fn last_skipped_relays(&self) -> &[String] {
+        &self.skipped_relays
+    }
+
+    /// Relays which failed to receive the last subscription (`Client::subscribe`,
+    /// `Client::subscribe_with_id`) or published event, with their error. After
+    /// `Client::get_events_of`, the relays which closed the subscription, failed or timed out
+    /// before sending EOSE are listed too, after `Client::next_data_all` the relays whose read
+    /// failed
+    ///
+    /// A relay which can't be reached, has no free connection or reached its maximum number of
+    /// subscriptions doesn't prevent the other relays from receiving the message: the method only
+    /// fails when no relay received it, returning the error of the first relay, which is then not
+    /// listed here
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// let client = Client::default();
+    /// assert!(client.last_failed_relays().is_empty());
+    /// ```
+    pub fn last_failed_relays(&self) -> &[(String, ClientError)] {
+        &self.failed_relays
+    }
+
+    /// Whether a read of the relays failed as a whole: every relay failed before sending a
+    /// message, see `Client::next_data_all`
+    fn read_failed(&self, messages_read: usize, errors: &[RelayError]) -> bool {
+        messages_read == 0 && !errors.is_empty() && errors.len() == self.relays.len()
+    }
+
+    /// Keep the relays which failed to receive a message, see `Client::last_failed_relays`, and
+    /// return the error of the first relay when none received it
+    fn record_relay_results(&mut self, results: Vec<RelayResult>) -> Result<(), ClientError> {
+        let received = results.is_empty() || results.iter().any(|(_, result)| result.is_ok());
+        self.failed_relays = results
+            .into_iter()
+            .filter_map(|(relay, result)| result.err().map(|error| (relay, error)))
+            .collect();
+
+        if received {
+            Ok(())
+        } else {
+            Err(self.failed_relays.remove(0).1)
+        }
+    }
+
+    /// Set the time given to each relay to send EOSE by the queries (`Client::get_events_of`,
+    /// `Client::get_events_of_per_relay` and the methods built on them), 10 seconds by default
+    ///
+    /// A relay which doesn't answer in time gets a `ClientError::Timeout` instead of blocking the
+    /// query
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::{Client, DEFAULT_QUERY_TIMEOUT};
+    /// use std::time::Duration;
+    ///
+    /// let mut client = Client::default();
+    /// assert_eq!(client.query_timeout(), DEFAULT_QUERY_TIMEOUT);
+    ///
+    /// client.set_query_timeout(Duration::from_secs(3));
+    /// assert_eq!(client.query_timeout(), Duration::from_secs(3));
+    /// ```
+    pub fn set_query_timeout(&mut self, query_timeout: Duration) {
+        self.query_timeout = query_timeout;
+    }
+
+    /// Time given to each relay to send EOSE, see `Client::set_query_timeout`
+    pub fn query_timeout(&self) -> Duration {
+        self.query_timeout
+    }
+
+    /// Apply the `limit` of the filters to the whole result of `Client::get_events_of` instead of
+    /// to each relay (the default)
+    ///
+    /// Each relay honors the limit on its own, so 5 relays queried with `limit: 50` may return up
+    /// to 250 events. With the aggregate limit, only the newest events are kept once deduplicated.
+    /// The limit is the sum of the limits of the filters and applies only if every filter has one.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// let mut client = Client::default();
+    /// assert!(!client.aggregate_limit());
+    ///
+    /// // Keep only the `limit` newest events of all the relays
+    /// client.set_aggregate_limit(true);
+    /// assert!(client.aggregate_limit());
+    /// ```
+    pub fn set_aggregate_limit(&mut self, aggregate_limit: bool) {
+        self.aggregate_limit = aggregate_limit;
+    }
+
+    /// Whether the `limit` of the filters applies to the whole result of `Client::get_events_of`,
+    /// see `Client::set_aggregate_limit`
+    pub fn aggregate_limit(&self) -> bool {
+        self.aggregate_limit
+    }
+
+    /// Limit of the whole result of `get_events_of`, None if the result must not be trimmed
+    fn total_limit(&self, filters: &[ReqFilter]) -> Option<usize> {
+        if !self.aggregate_limit {
+            return None;
+        }
+
+        filters
+            .iter()
+            .map(|filter| filter.limit.map(|limit| limit as usize))
+            .sum()
+    }
+
+    /// Set the maximum size of the messages received from the relays connected from now on
+    ///
+    /// A relay sending a larger message makes the read fail with
+    /// `SimplifiedWSError::MessageTooLarge` instead of allocating unbounded memory. The default
+    /// limits are 10 MB.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, websocket::SizeLimits};
+    ///
+    /// let mut client = Client::default();
+    /// client.set_size_limits(SizeLimits {
+    ///     max_message_size: 1 << 20,
+    ///     max_frame_size: 1 << 20,
+    /// });
+    /// assert_eq!(client.size_limits().max_message_size, 1 << 20);
+    /// ```
+    pub fn set_size_limits(&mut self, size_limits: SizeLimits) {
+        self.size_limits = size_limits;
+    }
+
+    /// Maximum size of the messages received from the relays, see `Client::set_size_limits`
+    pub fn size_limits(&self) -> SizeLimits {
+        self.size_limits
+    }
+
+    /// Set the proof of work difficulty of the events built by the client methods when they are
+    /// given a `difficulty_target` of 0, 0 (the default) for no proof of work
+    ///
+    /// A `difficulty_target` other than 0 given to a method takes precedence. Events published
+    /// with `Client::publish_event` are already signed so they are left untouched.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let mut client = Client::default();
+    /// client.set_default_difficulty(8);
+    /// assert_eq!(client.default_difficulty(), 8);
+    ///
+    /// # #[cfg(not(feature = "async"))]
+    /// # {
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = client.publish(&identity, 1, "Hello Nostr!", &[], 0).unwrap();
+    /// let event_id = hex::decode(&event.id).unwrap();
+    /// assert!(EventPrepare::count_leading_zero_bits(event_id) >= 8);
+    /// # }
+    /// ```
+    pub fn set_default_difficulty(&mut self, difficulty: u16) {
+        self.default_difficulty = difficulty;
+    }
+
+    /// Proof of work difficulty used when a method is given a `difficulty_target` of 0,
+    /// see `Client::set_default_difficulty`
+    pub fn default_difficulty(&self) -> u16 {
+        self.default_difficulty
+    }
+
+    /// Difficulty to reach for an event built with `difficulty_target`, falling back to the default
+    pub(crate) fn difficulty(&self, difficulty_target: u16) -> u16 {
+        if difficulty_target > 0 {
+            difficulty_target
+        } else {
+            self.default_difficulty
+        }
+    }
+
+    /// Set the User-Agent of the HTTP requests sent by the client methods: the NIP-05 verifications
+    /// and the NIP-11 relay information documents
+    ///
+    /// Some domains block the requests without a User-Agent, the default is `DEFAULT_USER_AGENT`
+    /// (`nostr_rust/<version>`)
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, DEFAULT_USER_AGENT};
+    ///
+    /// let mut client = Client::default();
+    /// assert_eq!(client.http_user_agent(), DEFAULT_USER_AGENT);
+    ///
+    /// client.set_http_user_agent("my-app/1.0");
+    /// assert_eq!(client.http_user_agent(), "my-app/1.0");
+    /// ```
+    pub fn set_http_user_agent(&mut self, user_agent: &str) {
+        self.http_config.user_agent = user_agent.to_string();
+    }
+
+    /// User-Agent of the HTTP requests sent by the client methods, see `Client::set_http_user_agent`
+    pub fn http_user_agent(&self) -> &str {
+        &self.http_config.user_agent
+    }
+
+    /// Set the timeout, the retries and the User-Agent of the HTTP requests sent by the client
+    /// methods: the NIP-05 verifications and the NIP-11 relay information documents
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{http::HttpConfig, nostr_client::Client};
+    /// use std::time::Duration;
+    ///
+    /// let mut client = Client::default();
+    /// assert_eq!(client.http_config(), &HttpConfig::default());
+    ///
+    /// client.set_http_config(HttpConfig { timeout: Duration::from_secs(3), retries: 2, user_agent: "my-app/1.0".to_string() });
+    /// assert_eq!(client.http_config().retries, 2);
+    /// assert_eq!(client.http_user_agent(), "my-app/1.0");
+    /// ```
+    pub fn set_http_config(&mut self, http_config: HttpConfig) {
+        self.http_config = http_config;
+    }
+
+    /// Settings of the HTTP requests sent by the client methods, see `Client::set_http_config`
+    pub fn http_config(&self) -> &HttpConfig {
+        &self.http_config
+    }
+
+    /// Limit the number of events published per second, to avoid being banned by the relays
+    ///
+    /// Bursts of up to `events_per_sec` events are sent at once, then `Client::publish_event`
+    /// waits for the next slot. A rate of 0 or less removes the limit (the default).
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// let mut client = Client::default();
+    /// client.set_publish_rate_limit(5.0);
+    /// ```
+    pub fn set_publish_rate_limit(&mut self, events_per_sec: f64) {
+        self.publish_rate_limit = if events_per_sec > 0.0 && events_per_sec.is_finite() {
+            Some(RateLimiter::new(events_per_sec))
+        } else {
+            None
+        };
+    }
+
+    /// Take the raw EVENT messages whose event couldn't be parsed by `Client::get_events_of`
+    ///
+    /// Only the last 100 rejected messages are kept
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// let mut client = Client::default();
+    /// assert!(client.take_rejected_events().is_empty());
+    /// ```
+    pub fn take_rejected_events(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.rejected_events)
+    }
+
+    fn reject_event(&mut self, message: &Message) {
+        if self.rejected_events.len() >= MAX_REJECTED_EVENTS {
+            self.rejected_events.remove(0);
+        }
+
+        self.rejected_events.push(message.to_string());
+    }
+
+    /// Register a callback called with the relay and the message of each NOTICE, e.g. to log
+    /// "rate-limited" warnings
+    ///
+    /// The callback is called by every read of the relays (`next_data`, `get_events_of`...)
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// let mut client = Client::default();
+    /// client.on_notice(|relay, message| println!("NOTICE from {}: {}", relay, message));
+    /// ```
+    pub fn on_notice(&mut self, callback: impl FnMut(&str, &str) + Send + 'static) {
+        self.on_notice = Some(Box::new(callback));
+    }
+
+    /// Register a callback called with the relay, the subscription id and the reason of each
+    /// CLOSED, sent by a relay which ended a subscription, e.g. "auth-required: ..."
+    ///
+    /// The callback is called by every read of the relays (`next_data`, `get_events_of`...)
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// let mut client = Client::default();
+    /// client.on_closed(|relay, subscription_id, reason| {
+    ///     println!("{} closed {}: {}", relay, subscription_id, reason);
+    /// });
+    /// ```
+    pub fn on_closed(&mut self, callback: impl FnMut(&str, &str, &str) + Send + 'static) {
+        self.on_closed = Some(Box::new(callback));
+    }
+
+    /// Call the NOTICE and CLOSED callbacks for a message of a relay, forget the subscriptions
+    /// closed by the relay and keep the OK answers for `Client::wait_for_ok`
+    fn notify(&mut self, relay: &str, message: &Message) {
+        let Ok(text) = message.to_text() else {
+            return;
+        };
+
+        // Skip parsing the other messages
+        if !text.contains("\"CLOSED\"") && !text.contains("\"NOTICE\"") && !text.contains("\"OK\"")
+        {
+            return;
+        }
+
+        self.notify_parsed(relay, &RelayMessage::parse(message));
+    }
+
+    /// Parse a message of a relay once, for the notifications and for the caller, see
+    /// `Client::notify`
+    fn receive(&mut self, relay: &str, message: &Message) -> RelayMessage {
+        let parsed = RelayMessage::parse(message);
+        self.notify_parsed(relay, &parsed);
+
+        parsed
+    }
+
+    fn notify_parsed(&mut self, relay: &str, parsed: &RelayMessage) {
+        match parsed {
+            RelayMessage::Notice(notice) => {
+                if let Some(on_notice) = &mut self.on_notice {
+                    on_notice(relay, notice);
+                }
+            }
+            RelayMessage::Closed(subscription_id, reason) => {
+                if let Some(subscriptions) = self.relay_subscriptions.get_mut(relay) {
+                    subscriptions.remove(subscription_id);
+                }
+
+                // The subscription is no longer open once every relay closed it
+                if !self
+                    .relay_subscriptions
+                    .values()
+                    .any(|subscriptions| subscriptions.contains(subscription_id))
+                {
+                    self.subscription_filters.remove(subscription_id);
+                }
+
+                if let Some(on_closed) = &mut self.on_closed {
+                    on_closed(relay, subscription_id, reason);
+                }
+            }
+            RelayMessage::Ok(event_id, ack) => {
+                self.keep_ok_answer(event_id, relay, ack.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn keep_ok_answer(&mut self, event_id: &str, relay: &str, ack: RelayAck) {
+        if let Some((_, answers)) = self.ok_answers.iter_mut().find(|(id, _)| id == event_id) {
+            answers.insert(relay.to_string(), ack);
+            return;
+        }
+
+        if self.ok_answers.len() >= MAX_OK_ANSWERS {
+            self.ok_answers.remove(0);
+        }
+
+        self.ok_answers.push((
+            event_id.to_string(),
+            HashMap::from([(relay.to_string(), ack)]),
+        ));
+    }
+
+    /// Take the OK answers received for an event, by relay
+    fn take_ok_answers(&mut self, event_id: &str) -> HashMap<String, RelayAck> {
+        match self.ok_answers.iter().position(|(id, _)| id == event_id) {
+            Some(index) => self.ok_answers.remove(index).1,
+            None => HashMap::new(),
+        }
+    }
+
+    /// Set the maximum number of open subscriptions of a relay, `None` for no limit (default)
+    ///
+    /// The relay at its limit gets `TooManySubscriptions` instead of a REQ it would drop, the other
+    /// relays still receive the subscription (see `Client::last_failed_relays`). A subscription closed by the relay (CLOSED) or with `Client::unsubscribe`
+    /// makes room for another. See `Client::load_max_subscriptions` to use the limits advertised
+    /// by the relays (NIP-11).
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::{Client, ClientError}, req::ReqFilter};
+    ///
+    /// let mut client = Client::default();
+    /// client.set_max_connections(Some(1));
+    /// # #[cfg(not(feature = "async"))]
+    /// client.add_relay("wss://relay.damus.io").unwrap();
+    /// client.set_max_subscriptions("wss://relay.damus.io", Some(0));
+    ///
+    /// # #[cfg(not(feature = "async"))]
+    /// assert!(matches!(
+    ///     client.subscribe(vec![ReqFilter::default()]),
+    ///     Err(ClientError::TooManySubscriptions(relay)) if relay == "wss://relay.damus.io"
+    /// ));
+    /// ```
+    pub fn set_max_subscriptions(&mut self, relay: &str, max_subscriptions: Option<usize>) {
+        match max_subscriptions {
+            Some(max_subscriptions) => {
+                self.max_subscriptions
+                    .insert(relay.to_string(), max_subscriptions);
+            }
+            None => {
+                self.max_subscriptions.remove(relay);
+            }
+        }
+    }
+
+    /// Get the number of open subscriptions of a relay
+    pub fn open_subscriptions(&self, relay: &str) -> usize {
+        self.relay_subscriptions.get(relay).map_or(0, HashSet::len)
+    }
+
+    /// Check that a new subscription doesn't exceed the maximum number of subscriptions of the
+    /// relay, replacing the filters of an open subscription is always allowed
+    fn check_subscription_limit(
+        &self,
+        subscription_id: &str,
+        relay: &str,
+    ) -> Result<(), ClientError> {
+        let Some(max_subscriptions) = self.max_subscriptions.get(relay) else {
+            return Ok(());
+        };
+
+        let subscriptions = self.relay_subscriptions.get(relay);
+        if subscriptions.is_some_and(|subscriptions| subscriptions.contains(subscription_id)) {
+            return Ok(());
+        }
+
+        if subscriptions.map_or(0, HashSet::len) >= *max_subscriptions {
+            return Err(ClientError::TooManySubscriptions(relay.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Set the cache used by `get_events_of`, events are first read from the cache then the relays
+    /// are queried and their events stored in the cache
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{cache::MemoryCache, nostr_client::Client};
+    ///
+    /// let mut client = Client::default();
+    /// client.set_cache(Box::new(MemoryCache::default()));
+    /// ```
+    pub fn set_cache(&mut self, cache: Box<dyn EventCache>) {
+        self.cache = cache;
+    }
+
+    /// Cached events matching the filters, without duplicates
+    fn cached_events(&self, filters: &[ReqFilter]) -> Vec<Event> {
+        let mut seen_ids: HashSet<String> = HashSet::new();
+
+        filters
+            .iter()
+            .flat_map(|filter| self.cache.get(filter))
+            .filter(|event| seen_ids.insert(event.id.clone()))
+            .collect()
+    }
+
+    /// Get the urls of every relay added to the client, connected or not
+    pub fn relay_urls(&self) -> &[String] {
+        &self.relay_urls
+    }
+
+    /// Get the filters of the open subscriptions by subscription id
+    ///
+    /// A subscription closed by every relay which received it (CLOSED) is no longer listed
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// let client = Client::default();
+    /// assert!(client.active_subscriptions().is_empty());
+    /// ```
+    pub fn active_subscriptions(&self) -> &HashMap<String, Vec<ReqFilter>> {
+        &self.subscription_filters
+    }
+
+    /// Add a relay without connecting to it
+    fn register_relay(&mut self, relay: &str) -> Result<(), ClientError> {
+        if Url::parse(relay).is_err() {
+            return Err(ClientError::WSError(
+                websocket::SimplifiedWSError::UrlParseError,
+            ));
+        }
+
+        // Check if relay is already added
+        if self.relay_urls.iter().any(|url| url == relay) {
+            return Err(ClientError::AlreadySubscribed);
+        }
+
+        self.relay_urls.push(relay.to_string());
+
+        Ok(())
+    }
+
+    /// Every relay of the client, the open connections first, for the subscriptions and the
+    /// queries connecting the other relays when there is room
+    fn query_relays(&self) -> Vec<String> {
+        let mut relays: Vec<String> = self.relays.keys().cloned().collect();
+        for relay in &self.relay_urls {
+            if !relays.contains(relay) {
+                relays.push(relay.clone());
+            }
+        }
+
+        relays
+    }
+
+    /// Relay of the client with the given normalized url, see `websocket::normalize_relay_url`
+    pub(crate) fn find_relay(&self, normalized_url: &str) -> Option<String> {
+        self.relay_urls
+            .iter()
+            .find(|url| websocket::normalize_relay_url(url).as_deref() == Ok(normalized_url))
+            .cloned()
+    }
+
+    /// Least recently used open connection without subscription, closing it loses no events
+    fn least_recently_used_idle_relay(&self) -> Option<String> {
+        self.relays
+            .keys()
+            .filter(|relay| self.open_subscriptions(relay) == 0)
+            .min_by_key(|relay| self.last_used.get(*relay))
+            .cloned()
+    }
+}
+
+impl Client {
+    #[cfg(not(feature = "async"))]
+    /// Add a relay to the client
+    ///
+    /// With `Client::set_max_connections`, the relay is connected on first use
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// client.add_relay(env!("RELAY_URL")).unwrap();
+    /// ```
+    pub fn add_relay(&mut self, relay: &str) -> Result<(), ClientError> {
+        if self.max_connections.is_some() {
+            return self.register_relay(relay);
+        }
+
+        let client = match SimplifiedWS::new_with_limits(relay, self.size_limits) {
+            Ok(client) => client,
+            Err(err) => return Err(ClientError::WSError(err)),
+        };
+
+        self.register_relay(relay)?;
+        self.relays
+            .insert(relay.to_string(), Arc::new(std::sync::Mutex::new(client)));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    /// Add a relay to the client
+    ///
+    /// With `Client::set_max_connections`, the relay is connected on first use
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// #[tokio::test]
+    /// async fn test_add_relay() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     client.add_relay("wss://relay.damus.io").await.unwrap();
+    /// }
+    /// ```
+    pub async fn add_relay(&mut self, relay: &str) -> Result<(), ClientError> {
+        if self.max_connections.is_some() {
+            return self.register_relay(relay);
+        }
+
+        let client = match SimplifiedWS::new_with_limits(relay, self.size_limits).await {
+            Ok(client) => client,
+            Err(err) => return Err(ClientError::WSError(err)),
+        };
+
+        self.register_relay(relay)?;
+        self.relays
+            .insert(relay.to_string(), Arc::new(tokio::sync::Mutex::new(client)));
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Add a relay with an already open connection
+    ///
+    /// This allows to use a custom connection, or a mock relay to test without network
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter, websocket::SimplifiedWS};
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, thread};
+    /// use tungstenite::Message;
+    ///
+    /// // Mock relay answering every request with one event
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let url = format!("ws://{}", listener.local_addr().unwrap());
+    /// thread::spawn(move || {
+    ///     let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///
+    ///     while let Ok(message) = relay.read_message() {
+    ///         let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///
+    ///         if request[0] == "REQ" {
+    ///             let event = json!({
+    ///                 "id": "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4",
+    ///                 "pubkey": "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6",
+    ///                 "created_at": 1673131597,
+    ///                 "kind": 1,
+    ///                 "tags": [],
+    ///                 "content": "Hello",
+    ///                 "sig": "",
+    ///             });
+    ///             relay.write_message(Message::text(json!(["EVENT", request[1], event]).to_string())).unwrap();
+    ///             relay.write_message(Message::text(json!(["EOSE", request[1]]).to_string())).unwrap();
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// let (socket, _) = tungstenite::connect(&url).unwrap();
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// client.add_connected_relay(&url, SimplifiedWS::from_socket(&url, socket).unwrap()).unwrap();
+    ///
+    /// let events = client.get_events_of(vec![ReqFilter::default()]).unwrap();
+    /// assert_eq!(events.len(), 1);
+    /// assert_eq!(events[0].content, "Hello");
+    /// ```
+    pub fn add_connected_relay(
+        &mut self,
+        relay: &str,
+        connection: SimplifiedWS,
+    ) -> Result<(), ClientError> {
+        self.register_relay(relay)?;
+        self.relays.insert(
+            relay.to_string(),
+            Arc::new(std::sync::Mutex::new(connection)),
+        );
+        self.last_used.insert(relay.to_string(), Instant::now());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    /// Add a relay with an already open connection
+    ///
+    /// This allows to use a custom connection, or a mock relay to test without network
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, websocket::SimplifiedWS};
+    ///
+    /// #[tokio::test]
+    /// async fn test_add_connected_relay() {
+    ///     let (socket, _) = tokio_tungstenite::connect_async(env!("RELAY_URL")).await.unwrap();
+    ///     let mut client = Client::new(vec![]).await.unwrap();
+    ///     client.add_connected_relay(env!("RELAY_URL"), SimplifiedWS::from_socket(env!("RELAY_URL"), socket).unwrap()).unwrap();
+    /// }
+    /// ```
+    pub fn add_connected_relay(
+        &mut self,
+        relay: &str,
+        connection: SimplifiedWS,
+    ) -> Result<(), ClientError> {
+        self.register_relay(relay)?;
+        self.relays.insert(
+            relay.to_string(),
+            Arc::new(tokio::sync::Mutex::new(connection)),
+        );
+        self.last_used.insert(relay.to_string(), Instant::now());
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Remove a relay from the client
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// client.remove_relay(env!("RELAY_URL")).unwrap();
+    /// ```
+    pub fn remove_relay(&mut self, relay: &str) -> Result<(), ClientError> {
+        if !self.relay_urls.iter().any(|url| url == relay) {
+            return Err(ClientError::RelayDoesNotExist);
+        }
+
+        self.relay_urls.retain(|url| url != relay);
+        self.last_used.remove(relay);
+
+        self.relay_subscriptions.remove(relay);
+
+        // Close the connection, it is dropped anyway so the closing error is ignored
+        if let Some(socket) = self.relays.remove(relay) {
+            let _ = socket.lock().unwrap().socket.close(None);
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Connect to a relay if needed, closing the least recently used connection without subscription
+    /// when the limit is reached
+    fn ensure_connection(&mut self, relay: &str) -> Result<(), ClientError> {
+        if !self.relays.contains_key(relay) {
+            if let Some(max_connections) = self.max_connections {
+                while self.relays.len() >= max_connections {
+                    let least_recently_used = self
+                        .least_recently_used_idle_relay()
+                        .ok_or_else(|| ClientError::NoIdleConnection(relay.to_string()))?;
+                    let socket = self.relays.remove(&least_recently_used).unwrap();
+                    self.relay_subscriptions.remove(&least_recently_used);
+                    // The connection is dropped anyway, ignore the closing error
+                    let _ = socket.lock().unwrap().socket.close(None);
+                }
+            }
+
+            let client = SimplifiedWS::new_with_limits(relay, self.size_limits)?;
+            self.relays
+                .insert(relay.to_string(), Arc::new(std::sync::Mutex::new(client)));
+        }
+
+        self.last_used.insert(relay.to_string(), Instant::now());
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    /// Remove a relay from the client
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// #[tokio::test]
+    /// async fn test_remove_relay() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     client.remove_relay(env!("RELAY_URL")).await.unwrap();
+    /// }
+    /// ```
+    pub async fn remove_relay(&mut self, relay: &str) -> Result<(), ClientError> {
+        if !self.relay_urls.iter().any(|url| url == relay) {
+            return Err(ClientError::RelayDoesNotExist);
+        }
+
+        self.relay_urls.retain(|url| url != relay);
+        self.last_used.remove(relay);
+
+        self.relay_subscriptions.remove(relay);
+
+        // Close the connection, it is dropped anyway so the closing error is ignored
+        if let Some(socket) = self.relays.remove(relay) {
+            let _ = socket.lock().await.socket.close(None).await;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    /// Connect to a relay if needed, closing the least recently used connection without subscription
+    /// when the limit is reached
+    async fn ensure_connection(&mut self, relay: &str) -> Result<(), ClientError> {
+        if !self.relays.contains_key(relay) {
+            if let Some(max_connections) = self.max_connections {
+                while self.relays.len() >= max_connections {
+                    let least_recently_used = self
+                        .least_recently_used_idle_relay()
+                        .ok_or_else(|| ClientError::NoIdleConnection(relay.to_string()))?;
+                    let socket = self.relays.remove(&least_recently_used).unwrap();
+                    self.relay_subscriptions.remove(&least_recently_used);
+                    // The connection is dropped anyway, ignore the closing error
+                    let _ = socket.lock().await.socket.close(None).await;
+                }
+            }
+
+            let client = SimplifiedWS::new_with_limits(relay, self.size_limits).await?;
+            self.relays
+                .insert(relay.to_string(), Arc::new(tokio::sync::Mutex::new(client)));
+        }
+
+        self.last_used.insert(relay.to_string(), Instant::now());
+        Ok(())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Publish a Nostr event
+    ///
+    /// A relay which can't be reached doesn't prevent the others from receiving the event, see
+    /// `Client::last_failed_relays`. The error of the first relay is returned when none received it
+    pub fn publish_event(&mut self, event: &Event) -> Result<(), ClientError> {
+        let json_stringified = json!(["EVENT", event]).to_string();
+
+        self.send_event_message(&Message::text(json_stringified))
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Publish a Nostr event to the given relays of the client only
+    pub(crate) fn publish_event_to(
+        &mut self,
+        relays: &[String],
+        event: &Event,
+    ) -> Result<(), ClientError> {
+        let json_stringified = json!(["EVENT", event]).to_string();
+
+        self.send_event_message_to(relays, &Message::text(json_stringified))
+    }
+
+    #[cfg(feature = "async")]
+    /// Publish a Nostr event
+    ///
+    /// A relay which can't be reached doesn't prevent the others from receiving the event, see
+    /// `Client::last_failed_relays`. The error of the first relay is returned when none received it
+    pub async fn publish_event(&mut self, event: &Event) -> Result<(), ClientError> {
+        let json_stringified = json!(["EVENT", event]).to_string();
+
+        self.send_event_message(&Message::text(json_stringified))
+            .await
+    }
+
+    #[cfg(feature = "async")]
+    /// Publish a Nostr event to the given relays of the client only
+    pub(crate) async fn publish_event_to(
+        &mut self,
+        relays: &[String],
+        event: &Event,
+    ) -> Result<(), ClientError> {
+        let json_stringified = json!(["EVENT", event]).to_string();
+
+        self.send_event_message_to(relays, &Message::text(json_stringified))
+            .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Publish a signed event given as JSON verbatim, e.g. an event signed by another tool
+    ///
+    /// The event is not serialized again, so the field order and the formatting the id was
+    /// computed on are kept. Only the presence of the NIP-01 fields is checked, not the signature.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::{Client, ClientError};
+    ///
+    /// let mut client = Client::default();
+    /// assert!(matches!(client.publish_raw("{\"id\":\"event_id\"}"), Err(ClientError::InvalidRawEvent(_))));
+    /// assert!(matches!(client.publish_raw("[]"), Err(ClientError::InvalidRawEvent(_))));
+    /// ```
+    pub fn publish_raw(&mut self, json: &str) -> Result<(), ClientError> {
+        let message = raw_event_message(json)?;
+
+        self.send_event_message(&message)
+    }
+
+    #[cfg(feature = "async")]
+    /// Publish a signed event given as JSON verbatim, e.g. an event signed by another tool
+    ///
+    /// The event is not serialized again, so the field order and the formatting the id was
+    /// computed on are kept. Only the presence of the NIP-01 fields is checked, not the signature.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::{Client, ClientError};
+    ///
+    /// #[tokio::test]
+    /// async fn test_publish_raw() {
+    ///     let mut client = Client::default();
+    ///     assert!(matches!(client.publish_raw("{\"id\":\"event_id\"}").await, Err(ClientError::InvalidRawEvent(_))));
+    /// }
+    /// ```
+    pub async fn publish_raw(&mut self, json: &str) -> Result<(), ClientError> {
+        let message = raw_event_message(json)?;
+
+        self.send_event_message(&message).await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Send an EVENT message to every relay, waiting for the publish rate limit
+    fn send_event_message(&mut self, message: &Message) -> Result<(), ClientError> {
+        self.send_event_message_to(&self.relay_urls.clone(), message)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Send an EVENT message to the given relays of the client, waiting for the publish rate limit.
+    /// Fail only when no relay received it, see `Client::last_failed_relays`
+    fn send_event_message_to(
+        &mut self,
+        relays: &[String],
+        message: &Message,
+    ) -> Result<(), ClientError> {
+        let results = self.send_event_message_per_relay(relays, message);
+
+        self.record_relay_results(results)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Send an EVENT message to each of the given relays of the client, waiting for the publish
+    /// rate limit once. A failing relay doesn't prevent the others from receiving the message,
+    /// return the result of each relay
+    pub(crate) fn send_event_message_per_relay(
+        &mut self,
+        relays: &[String],
+        message: &Message,
+    ) -> Vec<RelayResult> {
+        if let Some(limiter) = &mut self.publish_rate_limit {
+            std::thread::sleep(limiter.reserve());
+        }
+
+        relays
+            .iter()
+            .map(|relay| {
+                let result = self.ensure_connection(relay).and_then(|_| {
+                    self.relays[relay]
+                        .lock()
+                        .unwrap()
+                        .send_message(message)
+                        .map_err(ClientError::from)
+                });
+
+                (relay.clone(), result)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "async")]
+    /// Send an EVENT message to every relay, waiting for the publish rate limit
+    async fn send_event_message(&mut self, message: &Message) -> Result<(), ClientError> {
+        self.send_event_message_to(&self.relay_urls.clone(), message)
+            .await
+    }
+
+    #[cfg(feature = "async")]
+    /// Send an EVENT message to the given relays of the client, waiting for the publish rate limit.
+    /// Fail only when no relay received it, see `Client::last_failed_relays`
+    async fn send_event_message_to(
+        &mut self,
+        relays: &[String],
+        message: &Message,
+    ) -> Result<(), ClientError> {
+        let results = self.send_event_message_per_relay(relays, message).await;
+
+        self.record_relay_results(results)
+    }
+
+    #[cfg(feature = "async")]
+    /// Send an EVENT message to each of the given relays of the client, waiting for the publish
+    /// rate limit once. A failing relay doesn't prevent the others from receiving the message,
+    /// return the result of each relay
+    pub(crate) async fn send_event_message_per_relay(
+        &mut self,
+        relays: &[String],
+        message: &Message,
+    ) -> Vec<RelayResult> {
+        if let Some(limiter) = &mut self.publish_rate_limit {
+            tokio::time::sleep(limiter.reserve()).await;
+        }
+
+        let mut results = Vec::new();
+        for relay in relays {
+            let result = match self.ensure_connection(relay).await {
+                Ok(()) => self.relays[relay]
+                    .lock()
+                    .await
+                    .send_message(message)
+                    .await
+                    .map_err(ClientError::from),
+                Err(error) => Err(error),
+            };
+
+            results.push((relay.clone(), result));
+        }
+
+        results
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Build, sign and publish an event of any kind, return the published event
+    ///
+    /// The signer can be an `Identity` or any other `Signer`, such as a `RemoteSigner` (NIP-46)
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let mut client = Client::default();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let tags = vec![vec!["t".to_string(), "rust".to_string()]];
+    /// let event = client.publish(&identity, 30078, "custom data", &tags, 0).unwrap();
+    /// assert_eq!(event.kind, 30078);
+    /// assert_eq!(event.tags, tags);
+    /// ```
+    pub fn publish(
+        &mut self,
+        signer: &dyn Signer,
+        kind: u16,
+        content: &str,
+        tags: &[Vec<String>],
+        difficulty_target: u16,
+    ) -> Result<Event, ClientError> {
+        self.send_template(
+            signer,
+            |pub_key: &str| {
+                Ok::<_, ClientError>(EventPrepare {
+                    pub_key: pub_key.to_string(),
+                    created_at: get_timestamp(),
+                    kind,
+                    tags: tags.to_vec(),
+                    content: content.to_string(),
+                })
+            },
+            difficulty_target,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Build, sign and publish an event of any kind, return the published event
+    ///
+    /// The signer can be an `Identity` or any other blocking `Signer`. A `RemoteSigner` (NIP-46)
+    /// is not a `Signer` with the async feature: sign with `RemoteSigner::sign_event` and send
+    /// with `Client::publish_event`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// #[tokio::test]
+    /// async fn test_publish() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let tags = vec![vec!["t".to_string(), "rust".to_string()]];
+    ///     let event = client.publish(&identity, 30078, "custom data", &tags, 0).await.unwrap();
+    ///     assert_eq!(event.kind, 30078);
+    /// }
+    /// ```
+    pub async fn publish(
+        &mut self,
+        signer: &dyn Signer,
+        kind: u16,
+        content: &str,
+        tags: &[Vec<String>],
+        difficulty_target: u16,
+    ) -> Result<Event, ClientError> {
+        self.send_template(
+            signer,
+            |pub_key: &str| {
+                Ok::<_, ClientError>(EventPrepare {
+                    pub_key: pub_key.to_string(),
+                    created_at: get_timestamp(),
+                    kind,
+                    tags: tags.to_vec(),
+                    content: content.to_string(),
+                })
+            },
+            difficulty_target,
+        )
+        .await
+    }
+
+    /// Sign the event of a template with the client tag and the proof of work of the client,
+    /// without publishing it
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, nostr_client::{Client, ClientError}, utils::get_timestamp, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let mut client = Client::default();
+    /// client.set_client_tag(Some("my-app"));
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let note = |pub_key: &str| {
+    ///     Ok::<_, ClientError>(EventPrepare {
+    ///         pub_key: pub_key.to_string(),
+    ///         created_at: get_timestamp(),
+    ///         kind: 1,
+    ///         tags: vec![],
+    ///         content: "Hello Nostr!".to_string(),
+    ///     })
+    /// };
+    ///
+    /// let event = client.sign_template(&identity, note, 0).unwrap();
+    /// event.verify().unwrap();
+    /// assert_eq!(event.client_name(), Some("my-app"));
+    /// ```
+    pub fn sign_template<T: EventTemplate>(
+        &self,
+        signer: &dyn Signer,
+        template: T,
+        difficulty_target: u16,
+    ) -> Result<Event, T::Error> {
+        let mut event = template
+            .prepare(&signer.public_key())?
+            .with_client_tag(self.client_tag());
+
+        let difficulty = self.difficulty(difficulty_target);
+        if difficulty > 0 {
+            event.to_pow_event(difficulty).unwrap();
+        }
+
+        Ok(signer.sign(event).map_err(ClientError::from)?)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Sign the event of a template, see `Client::sign_template`, and publish it
+    pub fn send_template<T: EventTemplate>(
+        &mut self,
+        signer: &dyn Signer,
+        template: T,
+        difficulty_target: u16,
+    ) -> Result<Event, T::Error> {
+        let event = self.sign_template(signer, template, difficulty_target)?;
+
+        self.publish_event(&event)?;
+        Ok(event)
+    }
+
+    #[cfg(feature = "async")]
+    /// Sign the event of a template, see `Client::sign_template`, and publish it
+    pub async fn send_template<T: EventTemplate>(
+        &mut self,
+        signer: &dyn Signer,
+        template: T,
+        difficulty_target: u16,
+    ) -> Result<Event, T::Error> {
+        let event = self.sign_template(signer, template, difficulty_target)?;
+
+        self.publish_event(&event).await?;
+        Ok(event)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Publish a Nostr event then query it back by id to confirm the relays store it
+    ///
+    /// Wait until every relay which received the confirmation query answered (with the event, EOSE
+    /// or CLOSED) or until the timeout, and return the relays which sent the event back. The
+    /// confirmation subscription is closed before returning, even when reading the relays fails.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::{str::FromStr, time::Duration};
+    ///
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    /// let event = identity.make_event(1, "Hello Nostr!", &vec![], 0);
+    /// let relays = client.publish_and_confirm(&event, Duration::from_secs(5)).unwrap();
+    /// println!("Event stored by {:?}", relays);
+    /// ```
+    ///
+    /// With a mock relay refusing the confirmation query (see `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, websocket::SimplifiedWS, Identity};
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, str::FromStr, thread, time::{Duration, Instant}};
+    /// use tungstenite::Message;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let url = format!("ws://{}", listener.local_addr().unwrap());
+    /// thread::spawn(move || {
+    ///     let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///
+    ///     while let Ok(message) = relay.read_message() {
+    ///         let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///
+    ///         let answer = match request[0].as_str() {
+    ///             Some("EVENT") => json!(["OK", request[1]["id"], true, ""]),
+    ///             Some("REQ") => json!(["CLOSED", request[1], "restricted: no queries"]),
+    ///             _ => continue,
+    ///         };
+    ///         relay.write_message(Message::text(answer.to_string())).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let (socket, _) = tungstenite::connect(&url).unwrap();
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// client.add_connected_relay(&url, SimplifiedWS::from_socket(&url, socket).unwrap()).unwrap();
+    ///
+    /// let identity = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    /// let event = identity.make_event(1, "Hello Nostr!", &vec![], 0);
+    ///
+    /// // The relay answered without confirming, no need to wait for the timeout
+    /// let started = Instant::now();
+    /// let relays = client.publish_and_confirm(&event, Duration::from_secs(5)).unwrap();
+    /// assert!(relays.is_empty());
+    /// assert!(started.elapsed() < Duration::from_secs(5));
+    /// assert!(client.active_subscriptions().is_empty());
+    /// ```
+    pub fn publish_and_confirm(
+        &mut self,
+        event: &Event,
+        timeout: Duration,
+    ) -> Result<Vec<String>, ClientError> {
+        self.publish_event(event)?;
+
+        let deadline = Instant::now() + timeout;
+        let subscription_id = self.subscribe(vec![ReqFilter {
+            ids: Some(vec![event.id.clone()]),
+            ..Default::default()
+        }])?;
+
+        let mut pending: Vec<String> = self.subscribed_relays(&subscription_id);
+        let mut confirmed: Vec<String> = Vec::new();
+
+        let result = loop {
+            if pending.is_empty() || Instant::now() >= deadline {
+                break Ok(confirmed);
+            }
+
+            let data = match self.receive_all() {
+                Ok(data) => data,
+                Err(error) => break Err(error),
+            };
+
+            if data.is_empty() {
+                std::thread::sleep(EOSE_POLL_INTERVAL);
+                continue;
+            }
+
+            for (relay, message, parsed) in data {
+                if let Some(has_event) =
+                    self.confirmation_answer(&subscription_id, &event.id, message, parsed)
+                {
+                    if let Some(index) = pending.iter().position(|r| r == &relay) {
+                        pending.remove(index);
+
+                        if has_event {
+                            confirmed.push(relay);
+                        }
+                    }
+                }
+            }
+        };
+
+        // The subscription is closed even when the read failed, a relay which can't be reached
+        // has no subscription left to close
+        self.unsubscribe_per_relay(&subscription_id);
+        result
+    }
+
+    #[cfg(feature = "async")]
+    /// Publish a Nostr event then query it back by id to confirm the relays store it
+    ///
+    /// Wait until every relay which received the confirmation query answered (with the event, EOSE
+    /// or CLOSED) or until the timeout, and return the relays which sent the event back. The
+    /// confirmation subscription is closed before returning, even when reading the relays fails.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::{str::FromStr, time::Duration};
+    ///
+    /// #[tokio::test]
+    /// async fn test_publish_and_confirm() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///
+    ///     let event = identity.make_event(1, "Hello Nostr!", &vec![], 0);
+    ///     let relays = client.publish_and_confirm(&event, Duration::from_secs(5)).await.unwrap();
+    ///     println!("Event stored by {:?}", relays);
+    /// }
+    /// ```
+    pub async fn publish_and_confirm(
+        &mut self,
+        event: &Event,
+        timeout: Duration,
+    ) -> Result<Vec<String>, ClientError> {
+        self.publish_event(event).await?;
+
+        let deadline = Instant::now() + timeout;
+        let subscription_id = self
+            .subscribe(vec![ReqFilter {
+                ids: Some(vec![event.id.clone()]),
+                ..Default::default()
+            }])
+            .await?;
+
+        let mut pending: Vec<String> = self.subscribed_relays(&subscription_id);
+        let mut confirmed: Vec<String> = Vec::new();
+
+        let result = loop {
+            if pending.is_empty() || Instant::now() >= deadline {
+                break Ok(confirmed);
+            }
+
+            let data = match self.receive_all().await {
+                Ok(data) => data,
+                Err(error) => break Err(error),
+            };
+
+            if data.is_empty() {
+                tokio::time::sleep(EOSE_POLL_INTERVAL).await;
+                continue;
+            }
+
+            for (relay, message, parsed) in data {
+                if let Some(has_event) =
+                    self.confirmation_answer(&subscription_id, &event.id, message, parsed)
+                {
+                    if let Some(index) = pending.iter().position(|r| r == &relay) {
+                        pending.remove(index);
+
+                        if has_event {
+                            confirmed.push(relay);
+                        }
+                    }
+                }
+            }
+        };
+
+        // The subscription is closed even when the read failed, a relay which can't be reached
+        // has no subscription left to close
+        self.unsubscribe_per_relay(&subscription_id).await;
+        result
+    }
+
+    /// Answer of a relay to a confirmation query: `Some(true)` when it sent the event, `Some(false)`
+    /// on EOSE or CLOSED
+    ///
+    /// The events of the other subscriptions are stored, see `Client::get_events`
+    fn confirmation_answer(
+        &mut self,
+        subscription_id: &str,
+        event_id: &str,
+        message: Message,
+        parsed: RelayMessage,
+    ) -> Option<bool> {
+        match parsed {
+            RelayMessage::Eose(id) | RelayMessage::Closed(id, _) if id == subscription_id => {
+                Some(false)
+            }
+            RelayMessage::Event(id, event) if id == subscription_id => {
+                (event.id == event_id).then_some(true)
+            }
+            RelayMessage::Rejected(id) if id == subscription_id => None,
+            parsed => {
+                self.store_event(message, parsed);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get next data from the relays
+    /// # Example
+    /// ```rust
+    /// use std::{
+    ///  sync::{Arc, Mutex},
+    ///  thread,
+    /// };
+    /// use tungstenite::Message;
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    ///
+    /// fn handle_message(relay_url: &String, message: &Message) -> Result<(), String> {
+    ///   println!("Received message: {:?}", message);
+    ///
+    ///   Ok(())
+    /// }
+    ///
+    /// let mut client = Arc::new(Mutex::new(Client::new(vec![env!("RELAY_URL")]).unwrap()));
+    ///
+    /// // Run a new thread to listen
+    /// let nostr_clone = client.clone();
+    /// let nostr_thread = thread::spawn(move || loop {
+    ///    let events = nostr_clone.lock().unwrap().next_data().unwrap();
+    ///    
+    ///   for (relay_url, message) in events.iter() {
+    ///     handle_message(relay_url, message).unwrap();
+    ///   }
+    /// });
+    ///
+    /// // Subscribe to the most beautiful Nostr profile event
+    /// client
+    /// .lock()
     /// .unwrap()
     /// .subscribe(vec![ReqFilter {
-    ///     ids: None,
-    ///     authors: Some(vec![
-    ///         "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
-    ///     ]),
-    ///     kinds: None,
-    ///     e: None,
-    ///     p: None,
-    ///     since: None,
-    ///     until: None,
-    ///     limit: Some(1),
+    ///     ids: None,
+    ///     authors: Some(vec![
+    ///         "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///     ]),
+    ///     kinds: None,
+    ///     e: None,
+    ///     p: None,
+    ///     d: None,
+    ///     h: None,
+    ///     since: None,
+    ///     until: None,
+    ///     limit: Some(1),
+    /// }])
+    /// .unwrap();
+    ///
+    /// // Wait 3s for the thread to finish
+    /// std::thread::sleep(std::time::Duration::from_secs(3));
+    /// ```
+    pub fn next_data(&mut self) -> Result<Vec<(String, tungstenite::Message)>, ClientError> {
+        let mut events: Vec<(String, tungstenite::Message)> = Vec::new();
+
+        for (relay_name, socket) in self.relays.iter() {
+            let message = socket.lock().unwrap().read_message()?;
+            events.push((relay_name.clone(), message));
+        }
+
+        for (relay_name, message) in events.iter() {
+            self.notify(relay_name, message);
+        }
+
+        Ok(events)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get every message immediately available from the relays, without waiting
+    ///
+    /// Unlike `next_data`, which reads exactly one message per relay, this drains the messages
+    /// already received from each relay so bursts can be processed at once. At most 100 messages
+    /// are read from each relay per call, so a busy relay doesn't starve the others.
+    /// A relay which fails doesn't prevent reading the others: the messages it sent before are
+    /// returned and it is listed in `Client::last_failed_relays`. The call only fails when every
+    /// relay failed before sending a message
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    ///
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// client.subscribe(vec![ReqFilter { kinds: Some(vec![1]), limit: Some(100), ..Default::default() }]).unwrap();
+    ///
+    /// for (relay_url, message) in client.next_data_all().unwrap() {
+    ///     println!("{}: {:?}", relay_url, message);
+    /// }
+    /// ```
+    ///
+    /// With a mock relay sending a burst of 150 messages and one closing the connection (see
+    /// `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, websocket::SimplifiedWS};
+    /// use std::{net::TcpListener, thread};
+    /// use tungstenite::Message;
+    ///
+    /// let spawn_relay = |burst: usize| {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let url = format!("ws://{}", listener.local_addr().unwrap());
+    ///     thread::spawn(move || {
+    ///         let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///         if burst == 0 {
+    ///             return;
+    ///         }
+    ///
+    ///         for i in 0..burst {
+    ///             relay.write_message(Message::text(format!("[\"NOTICE\",\"{}\"]", i))).unwrap();
+    ///         }
+    ///         while relay.read_message().is_ok() {}
+    ///     });
+    ///
+    ///     url
+    /// };
+    ///
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// let busy = spawn_relay(150);
+    /// let closing = spawn_relay(0);
+    /// for url in [&busy, &closing] {
+    ///     let (socket, _) = tungstenite::connect(url.as_str()).unwrap();
+    ///     client.add_connected_relay(url, SimplifiedWS::from_socket(url, socket).unwrap()).unwrap();
+    /// }
+    ///
+    /// let mut received = 0;
+    /// while received < 150 || client.last_failed_relays().is_empty() {
+    ///     let messages = client.next_data_all().unwrap();
+    ///     assert!(messages.len() <= 100);
+    ///     received += messages.len();
+    /// }
+    /// assert_eq!(received, 150);
+    /// assert_eq!(client.last_failed_relays()[0].0, closing);
+    /// ```
+    pub fn next_data_all(&mut self) -> Result<Vec<(String, tungstenite::Message)>, ClientError> {
+        let (events, errors) = self.drain_relays();
+
+        for (relay_name, message) in events.iter() {
+            self.notify(relay_name, message);
+        }
+
+        let failed = self.read_failed(events.len(), &errors);
+        self.failed_relays = errors;
+        if failed {
+            return Err(self.failed_relays.remove(0).1);
+        }
+
+        Ok(events)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get every message immediately available from the relays, parsed once, see
+    /// `Client::next_data_all`
+    fn receive_all(&mut self) -> Result<Vec<(String, Message, RelayMessage)>, ClientError> {
+        let (events, mut errors) = self.drain_relays();
+        if self.read_failed(events.len(), &errors) {
+            return Err(errors.remove(0).1);
+        }
+
+        Ok(events
+            .into_iter()
+            .map(|(relay, message)| {
+                let parsed = self.receive(&relay, &message);
+                (relay, message, parsed)
+            })
+            .collect())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Read the messages immediately available from the relays, at most `MAX_MESSAGES_PER_READ`
+    /// per relay, without parsing them
+    ///
+    /// A relay which fails doesn't prevent reading the others: the messages it sent before are
+    /// kept and it is returned with its error
+    fn drain_relays(&mut self) -> (Vec<(String, Message)>, Vec<RelayError>) {
+        let mut events: Vec<(String, Message)> = Vec::new();
+        let mut errors: Vec<RelayError> = Vec::new();
+
+        for (relay_name, socket) in self.relays.iter() {
+            let mut socket = socket.lock().unwrap();
+
+            for _ in 0..MAX_MESSAGES_PER_READ {
+                match socket.try_read_message() {
+                    Ok(Some(message)) => events.push((relay_name.clone(), message)),
+                    Ok(None) => break,
+                    Err(error) => {
+                        errors.push((relay_name.clone(), error.into()));
+                        break;
+                    }
+                }
+            }
+        }
+
+        (events, errors)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get every message immediately available from a single relay, without waiting, parsed once
+    fn next_data_of(&mut self, relay: &str) -> Result<Vec<(Message, RelayMessage)>, ClientError> {
+        let mut messages: Vec<Message> = Vec::new();
+
+        {
+            let mut socket = self.relays[relay].lock().unwrap();
+
+            for _ in 0..MAX_MESSAGES_PER_READ {
+                match socket.try_read_message() {
+                    Ok(Some(message)) => messages.push(message),
+                    Ok(None) => break,
+                    // The error is returned by the next read, after the messages sent before it
+                    Err(_) if !messages.is_empty() => break,
+                    Err(error) => return Err(error.into()),
+                }
+            }
+        }
+
+        Ok(messages
+            .into_iter()
+            .map(|message| {
+                let parsed = self.receive(relay, &message);
+                (message, parsed)
+            })
+            .collect())
+    }
+
+    #[cfg(feature = "async")]
+    /// Get next data from the relays
+    /// # Example
+    /// ```rust
+    /// use std::{
+    ///  sync::{Arc, Mutex},
+    ///  thread,
+    /// };
+    /// use tungstenite::Message;
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    ///
+    /// fn handle_message(relay_url: &String, message: &Message) -> Result<(), String> {
+    ///   println!("Received message: {:?}", message);
+    ///
+    ///   Ok(())
+    /// }
+    ///
+    /// #[tokio::test]
+    /// async fn test_next_data() {
+    ///     let mut client = Arc::new(Mutex::new(Client::new(vec![env!("RELAY_URL")]).await.unwrap()));
+    ///
+    ///     // Run a new thread to listen
+    ///     let nostr_clone = client.clone();
+    ///     let nostr_thread = thread::spawn(move || loop {
+    ///         let events = nostr_clone.lock().unwrap().next_data().await.unwrap();
+    ///    
+    ///         for (relay_url, message) in events.iter() {
+    ///             handle_message(relay_url, message).unwrap();
+    ///         }
+    ///     });
+    ///
+    ///     // Subscribe to the most beautiful Nostr profile event
+    ///     client
+    ///     .lock()
+    ///     .unwrap()
+    ///     .subscribe(vec![ReqFilter {
+    ///         ids: None,
+    ///         authors: Some(vec![
+    ///             "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///         ]),
+    ///         kinds: None,
+    ///         e: None,
+    ///         p: None,
+    ///         d: None,
+    ///         h: None,
+    ///         since: None,
+    ///         until: None,
+    ///         limit: Some(1),
+    ///     }])
+    ///     .await
+    ///     .unwrap();
+    ///
+    /// // Wait 3s for the thread to finish
+    /// std::thread::sleep(std::time::Duration::from_secs(3));
+    /// }
+    /// ```
+    pub async fn next_data(&mut self) -> Result<Vec<(String, tungstenite::Message)>, ClientError> {
+        let mut events: Vec<(String, tungstenite::Message)> = Vec::new();
+
+        for (relay_name, socket) in self.relays.iter() {
+            let message = socket.lock().await.read_message().await?;
+            events.push((relay_name.clone(), message));
+        }
+
+        for (relay_name, message) in events.iter() {
+            self.notify(relay_name, message);
+        }
+
+        Ok(events)
+    }
+
+    #[cfg(feature = "async")]
+    /// Get every message immediately available from the relays, without waiting
+    ///
+    /// Unlike `next_data`, which reads exactly one message per relay, this drains the messages
+    /// already received from each relay so bursts can be processed at once. At most 100 messages
+    /// are read from each relay per call, so a busy relay doesn't starve the others.
+    /// A relay which fails doesn't prevent reading the others: the messages it sent before are
+    /// returned and it is listed in `Client::last_failed_relays`. The call only fails when every
+    /// relay failed before sending a message
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    ///
+    /// #[tokio::test]
+    /// async fn test_next_data_all() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     client.subscribe(vec![ReqFilter { kinds: Some(vec![1]), limit: Some(100), ..Default::default() }]).await.unwrap();
+    ///
+    ///     for (relay_url, message) in client.next_data_all().await.unwrap() {
+    ///         println!("{}: {:?}", relay_url, message);
+    ///     }
+    /// }
+    /// ```
+    pub async fn next_data_all(
+        &mut self,
+    ) -> Result<Vec<(String, tungstenite::Message)>, ClientError> {
+        let (events, errors) = self.drain_relays().await;
+
+        for (relay_name, message) in events.iter() {
+            self.notify(relay_name, message);
+        }
+
+        let failed = self.read_failed(events.len(), &errors);
+        self.failed_relays = errors;
+        if failed {
+            return Err(self.failed_relays.remove(0).1);
+        }
+
+        Ok(events)
+    }
+
+    #[cfg(feature = "async")]
+    /// Get every message immediately available from the relays, parsed once, see
+    /// `Client::next_data_all`
+    async fn receive_all(&mut self) -> Result<Vec<(String, Message, RelayMessage)>, ClientError> {
+        let (events, mut errors) = self.drain_relays().await;
+        if self.read_failed(events.len(), &errors) {
+            return Err(errors.remove(0).1);
+        }
+
+        Ok(events
+            .into_iter()
+            .map(|(relay, message)| {
+                let parsed = self.receive(&relay, &message);
+                (relay, message, parsed)
+            })
+            .collect())
+    }
+
+    #[cfg(feature = "async")]
+    /// Read the messages immediately available from the relays, at most `MAX_MESSAGES_PER_READ`
+    /// per relay, without parsing them
+    ///
+    /// A relay which fails doesn't prevent reading the others: the messages it sent before are
+    /// kept and it is returned with its error
+    async fn drain_relays(&mut self) -> (Vec<(String, Message)>, Vec<RelayError>) {
+        let mut events: Vec<(String, Message)> = Vec::new();
+        let mut errors: Vec<RelayError> = Vec::new();
+
+        for (relay_name, socket) in self.relays.iter() {
+            let mut socket = socket.lock().await;
+
+            for _ in 0..MAX_MESSAGES_PER_READ {
+                match socket.try_read_message().await {
+                    Ok(Some(message)) => events.push((relay_name.clone(), message)),
+                    Ok(None) => break,
+                    Err(error) => {
+                        errors.push((relay_name.clone(), error.into()));
+                        break;
+                    }
+                }
+            }
+        }
+
+        (events, errors)
+    }
+
+    #[cfg(feature = "async")]
+    /// Get every message immediately available from a single relay, without waiting, parsed once
+    async fn next_data_of(
+        &mut self,
+        relay: &str,
+    ) -> Result<Vec<(Message, RelayMessage)>, ClientError> {
+        let mut messages: Vec<Message> = Vec::new();
+
+        {
+            let mut socket = self.relays[relay].lock().await;
+
+            for _ in 0..MAX_MESSAGES_PER_READ {
+                match socket.try_read_message().await {
+                    Ok(Some(message)) => messages.push(message),
+                    Ok(None) => break,
+                    // The error is returned by the next read, after the messages sent before it
+                    Err(_) if !messages.is_empty() => break,
+                    Err(error) => return Err(error.into()),
+                }
+            }
+        }
+
+        Ok(messages
+            .into_iter()
+            .map(|message| {
+                let parsed = self.receive(relay, &message);
+                (message, parsed)
+            })
+            .collect())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Send a REQ message to a relay, connecting it if needed, and record the subscription
+    fn send_subscription(
+        &mut self,
+        relay: &str,
+        subscription_id: &str,
+        message: &Message,
+    ) -> Result<(), ClientError> {
+        self.check_subscription_limit(subscription_id, relay)?;
+        self.ensure_connection(relay)?;
+        self.relays[relay].lock().unwrap().send_message(message)?;
+        self.relay_subscriptions
+            .entry(relay.to_string())
+            .or_default()
+            .insert(subscription_id.to_string());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    /// Send a REQ message to a relay, connecting it if needed, and record the subscription
+    async fn send_subscription(
+        &mut self,
+        relay: &str,
+        subscription_id: &str,
+        message: &Message,
+    ) -> Result<(), ClientError> {
+        self.check_subscription_limit(subscription_id, relay)?;
+        self.ensure_connection(relay).await?;
+        self.relays[relay]
+            .lock()
+            .await
+            .send_message(message)
+            .await?;
+        self.relay_subscriptions
+            .entry(relay.to_string())
+            .or_default()
+            .insert(subscription_id.to_string());
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Send a REQ message to the open connections then to the other relays until every connection
+    /// allowed has the subscription, the relays left out are kept in `Client::last_skipped_relays`.
+    /// Fail only when no relay received it, see `Client::last_failed_relays`
+    fn send_subscriptions(
+        &mut self,
+        subscription_id: &str,
+        message: &Message,
+    ) -> Result<(), ClientError> {
+        let capacity = self.max_connections.unwrap_or(usize::MAX);
+        let mut results: Vec<RelayResult> = Vec::new();
+        let mut skipped_relays: Vec<String> = Vec::new();
+        let mut subscribed = 0;
+
+        for relay in self.query_relays() {
+            if subscribed >= capacity {
+                skipped_relays.push(relay);
+                continue;
+            }
+
+            let result = self.send_subscription(&relay, subscription_id, message);
+            if result.is_ok() {
+                subscribed += 1;
+            }
+            results.push((relay, result));
+        }
+
+        self.skipped_relays = skipped_relays;
+        self.record_relay_results(results)
+    }
+
+    #[cfg(feature = "async")]
+    /// Send a REQ message to the open connections then to the other relays until every connection
+    /// allowed has the subscription, the relays left out are kept in `Client::last_skipped_relays`.
+    /// Fail only when no relay received it, see `Client::last_failed_relays`
+    async fn send_subscriptions(
+        &mut self,
+        subscription_id: &str,
+        message: &Message,
+    ) -> Result<(), ClientError> {
+        let capacity = self.max_connections.unwrap_or(usize::MAX);
+        let mut results: Vec<RelayResult> = Vec::new();
+        let mut skipped_relays: Vec<String> = Vec::new();
+        let mut subscribed = 0;
+
+        for relay in self.query_relays() {
+            if subscribed >= capacity {
+                skipped_relays.push(relay);
+                continue;
+            }
+
+            let result = self
+                .send_subscription(&relay, subscription_id, message)
+                .await;
+            if result.is_ok() {
+                subscribed += 1;
+            }
+            results.push((relay, result));
+        }
+
+        self.skipped_relays = skipped_relays;
+        self.record_relay_results(results)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Subscribe
+    ///
+    /// Return the subscription id, generated according to `Client::set_subscription_id_strategy`.
+    /// With `Client::set_max_connections`, the relays without free connection are left out, see
+    /// `Client::last_skipped_relays`. A relay which can't be reached doesn't prevent the others
+    /// from receiving the subscription, see `Client::last_failed_relays`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// client
+    /// .subscribe(vec![ReqFilter { // None means generate a random ID
+    ///     ids: None,
+    ///     authors: Some(vec![
+    ///         "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///     ]),
+    ///     kinds: None,
+    ///     e: None,
+    ///     p: None,
+    ///     d: None,
+    ///     h: None,
+    ///     since: None,
+    ///     until: None,
+    ///     limit: Some(1),
+    /// }])
+    /// .unwrap();
+    /// ```
+    pub fn subscribe(&mut self, filters: Vec<ReqFilter>) -> Result<String, ClientError> {
+        let subscription_id = self.next_subscription_id()?;
+        let req = Req::new(Some(&subscription_id), filters);
+        let message = Message::text(req.to_string());
+        self.send_subscriptions(&req.subscription_id, &message)?;
+
+        self.subscription_filters
+            .insert(req.subscription_id.clone(), req.filters);
+
+        Ok(req.subscription_id)
+    }
+
+    #[cfg(feature = "async")]
+    /// Subscribe
+    ///
+    /// Return the subscription id, generated according to `Client::set_subscription_id_strategy`.
+    /// With `Client::set_max_connections`, the relays without free connection are left out, see
+    /// `Client::last_skipped_relays`. A relay which can't be reached doesn't prevent the others
+    /// from receiving the subscription, see `Client::last_failed_relays`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    ///
+    /// #[tokio::test]
+    /// async fn test_subscribe() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     client
+    ///     .subscribe(vec![ReqFilter { // None means generate a random ID
+    ///         ids: None,
+    ///         authors: Some(vec![
+    ///             "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///         ]),
+    ///         kinds: None,
+    ///         e: None,
+    ///         p: None,
+    ///         d: None,
+    ///         h: None,
+    ///         since: None,
+    ///         until: None,
+    ///         limit: Some(1),
+    ///     }])
+    ///     .await
+    ///     .unwrap();
+    /// }
+    /// ```
+    pub async fn subscribe(&mut self, filters: Vec<ReqFilter>) -> Result<String, ClientError> {
+        let subscription_id = self.next_subscription_id()?;
+        let req = Req::new(Some(&subscription_id), filters);
+        let message = Message::text(req.to_string());
+        self.send_subscriptions(&req.subscription_id, &message)
+            .await?;
+
+        self.subscription_filters
+            .insert(req.subscription_id.clone(), req.filters);
+
+        Ok(req.subscription_id)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Subscribe with a specific ID
+    ///
+    /// The relays without free connection are left out, see `Client::last_skipped_relays`, and a
+    /// relay which can't be reached doesn't prevent the others from receiving the subscription,
+    /// see `Client::last_failed_relays`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// client
+    /// .subscribe_with_id("my_subscription_id", vec![ReqFilter {
+    ///    ids: None,
+    ///    authors: Some(vec![
+    ///      "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///    ]),
+    ///    kinds: None,
+    ///    e: None,
+    ///    p: None,
+    ///    d: None,
+    ///    h: None,
+    ///    since: None,
+    ///    until: None,
+    ///    limit: Some(1),
+    /// }])
+    /// .unwrap();
+    /// ```
+    pub fn subscribe_with_id(
+        &mut self,
+        subscription_id: &str,
+        filters: Vec<ReqFilter>,
+    ) -> Result<(), ClientError> {
+        let req = Req::new(Some(subscription_id), filters);
+        let message = Message::text(req.to_string());
+        self.send_subscriptions(&req.subscription_id, &message)?;
+
+        self.subscription_filters
+            .insert(req.subscription_id, req.filters);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "async")]
+    /// Subscribe with a specific ID
+    ///
+    /// The relays without free connection are left out, see `Client::last_skipped_relays`, and a
+    /// relay which can't be reached doesn't prevent the others from receiving the subscription,
+    /// see `Client::last_failed_relays`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    ///
+    /// #[tokio::test]
+    /// async fn test_subscribe_with_id() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     client
+    ///     .subscribe_with_id("my_subscription_id", vec![ReqFilter {
+    ///        ids: None,
+    ///        authors: Some(vec![
+    ///          "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///        ]),
+    ///        kinds: None,
+    ///        e: None,
+    ///        p: None,
+    ///        d: None,
+    ///        h: None,
+    ///        since: None,
+    ///        until: None,
+    ///        limit: Some(1),
+    ///     }])
+    ///     .await
+    ///     .unwrap();
+    /// }
+    /// ```
+    pub async fn subscribe_with_id(
+        &mut self,
+        subscription_id: &str,
+        filters: Vec<ReqFilter>,
+    ) -> Result<(), ClientError> {
+        let req = Req::new(Some(subscription_id), filters);
+        let message = Message::text(req.to_string());
+        self.send_subscriptions(&req.subscription_id, &message)
+            .await?;
+
+        self.subscription_filters
+            .insert(req.subscription_id, req.filters);
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Subscribe to the new events only, skipping the stored ones
+    ///
+    /// The `since` of every filter is set to now. A relay whose clock is behind may still send
+    /// the events created in the last seconds.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// client.subscribe_live(vec![ReqFilter { kinds: Some(vec![1]), ..Default::default() }]).unwrap();
+    /// ```
+    pub fn subscribe_live(&mut self, filters: Vec<ReqFilter>) -> Result<String, ClientError> {
+        self.subscribe(live_filters(filters))
+    }
+
+    #[cfg(feature = "async")]
+    /// Subscribe to the new events only, skipping the stored ones
+    ///
+    /// The `since` of every filter is set to now. A relay whose clock is behind may still send
+    /// the events created in the last seconds.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    ///
+    /// #[tokio::test]
+    /// async fn test_subscribe_live() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     client.subscribe_live(vec![ReqFilter { kinds: Some(vec![1]), ..Default::default() }]).await.unwrap();
+    /// }
+    /// ```
+    pub async fn subscribe_live(&mut self, filters: Vec<ReqFilter>) -> Result<String, ClientError> {
+        self.subscribe(live_filters(filters)).await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Replace the filters of a subscription without closing it
+    ///
+    /// A new REQ is sent with the same subscription id, relays overwrite the previous filters
+    /// (NIP-01) so no event is missed between the old and the new filters.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::{Client, ClientError}, req::ReqFilter};
+    ///
+    /// let mut client = Client::default();
+    /// client.subscribe_with_id("feed", vec![ReqFilter { kinds: Some(vec![1]), ..Default::default() }]).unwrap();
+    /// client.update_subscription("feed", vec![ReqFilter { kinds: Some(vec![1, 6]), ..Default::default() }]).unwrap();
+    /// assert_eq!(client.active_subscriptions()["feed"][0].kinds, Some(vec![1, 6]));
+    ///
+    /// assert!(matches!(
+    ///     client.update_subscription("unknown", vec![]),
+    ///     Err(ClientError::SubscriptionDoesNotExist)
+    /// ));
+    /// ```
+    pub fn update_subscription(
+        &mut self,
+        subscription_id: &str,
+        filters: Vec<ReqFilter>,
+    ) -> Result<(), ClientError> {
+        if !self.subscription_filters.contains_key(subscription_id) {
+            return Err(ClientError::SubscriptionDoesNotExist);
+        }
+
+        self.subscribe_with_id(subscription_id, filters)
+    }
+
+    #[cfg(feature = "async")]
+    /// Replace the filters of a subscription without closing it
+    ///
+    /// A new REQ is sent with the same subscription id, relays overwrite the previous filters
+    /// (NIP-01) so no event is missed between the old and the new filters.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    ///
+    /// #[tokio::test]
+    /// async fn test_update_subscription() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     client.subscribe_with_id("feed", vec![ReqFilter { kinds: Some(vec![1]), ..Default::default() }]).await.unwrap();
+    ///     client.update_subscription("feed", vec![ReqFilter { kinds: Some(vec![1, 6]), ..Default::default() }]).await.unwrap();
+    /// }
+    /// ```
+    pub async fn update_subscription(
+        &mut self,
+        subscription_id: &str,
+        filters: Vec<ReqFilter>,
+    ) -> Result<(), ClientError> {
+        if !self.subscription_filters.contains_key(subscription_id) {
+            return Err(ClientError::SubscriptionDoesNotExist);
+        }
+
+        self.subscribe_with_id(subscription_id, filters).await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Unsubscribe
+    ///
+    /// Every relay is unsubscribed even if one fails, the first error is returned
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let subscription_id = client
+    /// .subscribe(vec![ReqFilter {
+    ///    ids: None,
+    ///   authors: Some(vec![
+    ///        "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///   ]),
+    ///  kinds: None,
+    ///  e: None,
+    ///  p: None,
+    ///  d: None,
+    ///  h: None,
+    ///  since: None,
+    ///  until: None,
+    ///  limit: Some(1),
     /// }])
     /// .unwrap();
+    /// client.unsubscribe(&subscription_id).unwrap();
+    /// ```
+    pub fn unsubscribe(&mut self, subscription_id: &str) -> Result<(), ClientError> {
+        self.unsubscribe_per_relay(subscription_id)
+            .into_iter()
+            .try_for_each(|(_, result)| result)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Unsubscribe from every relay, a failing relay doesn't prevent the others from being unsubscribed
+    ///
+    /// Return the result of each relay
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// let mut client = Client::default();
+    /// assert!(client.unsubscribe_per_relay("my_subscription_id").is_empty());
+    /// ```
+    pub fn unsubscribe_per_relay(
+        &mut self,
+        subscription_id: &str,
+    ) -> Vec<(String, Result<(), ClientError>)> {
+        let message = Message::text(json!(["CLOSE", subscription_id]).to_string());
+        self.subscription_filters.remove(subscription_id);
+        for subscriptions in self.relay_subscriptions.values_mut() {
+            subscriptions.remove(subscription_id);
+        }
+
+        self.relays
+            .iter()
+            .map(|(relay_url, relay)| {
+                let result = relay
+                    .lock()
+                    .unwrap()
+                    .send_message(&message)
+                    .map_err(ClientError::from);
+
+                (relay_url.clone(), result)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "async")]
+    /// Unsubscribe
+    ///
+    /// Every relay is unsubscribed even if one fails, the first error is returned
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    ///
+    /// #[tokio::test]
+    /// async fn test_unsubscribe() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let subscription_id = client
+    ///     .subscribe(vec![ReqFilter {
+    ///        ids: None,
+    ///       authors: Some(vec![
+    ///            "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///       ]),
+    ///      kinds: None,
+    ///      e: None,
+    ///      p: None,
+    ///      d: None,
+    ///      h: None,
+    ///      since: None,
+    ///      until: None,
+    ///      limit: Some(1),
+    ///     }])
+    ///     .await
+    ///     .unwrap();
+    ///     client.unsubscribe(&subscription_id).await.unwrap();
+    /// }
+    /// ```
+    pub async fn unsubscribe(&mut self, subscription_id: &str) -> Result<(), ClientError> {
+        self.unsubscribe_per_relay(subscription_id)
+            .await
+            .into_iter()
+            .try_for_each(|(_, result)| result)
+    }
+
+    #[cfg(feature = "async")]
+    /// Unsubscribe from every relay, a failing relay doesn't prevent the others from being unsubscribed
+    ///
+    /// Return the result of each relay
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// #[tokio::test]
+    /// async fn test_unsubscribe_per_relay() {
+    ///     let mut client = Client::default();
+    ///     assert!(client.unsubscribe_per_relay("my_subscription_id").await.is_empty());
+    /// }
+    /// ```
+    pub async fn unsubscribe_per_relay(
+        &mut self,
+        subscription_id: &str,
+    ) -> Vec<(String, Result<(), ClientError>)> {
+        let message = Message::text(json!(["CLOSE", subscription_id]).to_string());
+        self.subscription_filters.remove(subscription_id);
+        for subscriptions in self.relay_subscriptions.values_mut() {
+            subscriptions.remove(subscription_id);
+        }
+
+        let mut results = Vec::new();
+
+        for (relay_url, relay) in self.relays.iter() {
+            let result = relay
+                .lock()
+                .await
+                .send_message(&message)
+                .await
+                .map_err(ClientError::from);
+
+            results.push((relay_url.clone(), result));
+        }
+
+        results
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Wait until every relay sent EOSE (end of stored events) for a subscription, or until the timeout
+    ///
+    /// A relay refusing the subscription (CLOSED) is listed in `EoseStatus::closed` rather than as
+    /// received. Events received meanwhile are stored in their subscription, see `Client::get_events`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// use std::time::Duration;
+    ///
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let subscription_id = client.subscribe(vec![ReqFilter { kinds: Some(vec![1]), limit: Some(10), ..Default::default() }]).unwrap();
+    ///
+    /// let status = client.wait_for_eose(&subscription_id, Duration::from_secs(5)).unwrap();
+    /// println!("EOSE received from {:?}, timed out: {:?}", status.received, status.timed_out);
+    /// ```
+    ///
+    /// With a mock relay refusing the subscription (see `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter, websocket::SimplifiedWS};
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, thread, time::Duration};
+    /// use tungstenite::Message;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let url = format!("ws://{}", listener.local_addr().unwrap());
+    /// thread::spawn(move || {
+    ///     let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///
+    ///     while let Ok(message) = relay.read_message() {
+    ///         let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///
+    ///         if request[0] == "REQ" {
+    ///             let closed = json!(["CLOSED", request[1], "auth-required: log in first"]);
+    ///             relay.write_message(Message::text(closed.to_string())).unwrap();
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// let (socket, _) = tungstenite::connect(&url).unwrap();
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// client.add_connected_relay(&url, SimplifiedWS::from_socket(&url, socket).unwrap()).unwrap();
+    ///
+    /// let subscription_id = client.subscribe(vec![ReqFilter::default()]).unwrap();
+    /// let status = client.wait_for_eose(&subscription_id, Duration::from_secs(5)).unwrap();
+    /// assert!(status.received.is_empty() && status.timed_out.is_empty());
+    /// assert_eq!(status.closed[&url], "auth-required: log in first");
+    /// assert!(client.active_subscriptions().is_empty());
+    /// ```
+    pub fn wait_for_eose(
+        &mut self,
+        subscription_id: &str,
+        timeout: Duration,
+    ) -> Result<EoseStatus, ClientError> {
+        let deadline = Instant::now() + timeout;
+        let mut status = EoseStatus {
+            timed_out: self.relays.keys().cloned().collect(),
+            ..Default::default()
+        };
+
+        while !status.timed_out.is_empty() && Instant::now() < deadline {
+            let data = self.receive_all()?;
+
+            if data.is_empty() {
+                std::thread::sleep(EOSE_POLL_INTERVAL);
+                continue;
+            }
+
+            for (relay, message, parsed) in data {
+                if let Some(answer) = self.store_until_eose(subscription_id, message, parsed) {
+                    status.mark(relay, answer);
+                }
+            }
+        }
+
+        Ok(status)
+    }
+
+    #[cfg(feature = "async")]
+    /// Wait until every relay sent EOSE (end of stored events) for a subscription, or until the timeout
+    ///
+    /// A relay refusing the subscription (CLOSED) is listed in `EoseStatus::closed` rather than as
+    /// received. Events received meanwhile are stored in their subscription, see `Client::get_events`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::test]
+    /// async fn test_wait_for_eose() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let subscription_id = client.subscribe(vec![ReqFilter { kinds: Some(vec![1]), limit: Some(10), ..Default::default() }]).await.unwrap();
+    ///
+    ///     let status = client.wait_for_eose(&subscription_id, Duration::from_secs(5)).await.unwrap();
+    ///     println!("EOSE received from {:?}, timed out: {:?}", status.received, status.timed_out);
+    /// }
+    /// ```
+    pub async fn wait_for_eose(
+        &mut self,
+        subscription_id: &str,
+        timeout: Duration,
+    ) -> Result<EoseStatus, ClientError> {
+        let deadline = Instant::now() + timeout;
+        let mut status = EoseStatus {
+            timed_out: self.relays.keys().cloned().collect(),
+            ..Default::default()
+        };
+
+        while !status.timed_out.is_empty() && Instant::now() < deadline {
+            let data = self.receive_all().await?;
+
+            if data.is_empty() {
+                tokio::time::sleep(EOSE_POLL_INTERVAL).await;
+                continue;
+            }
+
+            for (relay, message, parsed) in data {
+                if let Some(answer) = self.store_until_eose(subscription_id, message, parsed) {
+                    status.mark(relay, answer);
+                }
+            }
+        }
+
+        Ok(status)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Wait until every relay answered with an OK message for a published event, or until the timeout
     ///
-    /// // Wait 3s for the thread to finish
-    /// std::thread::sleep(std::time::Duration::from_secs(3));
+    /// The OK messages read before, e.g. by `Client::get_events_of` or `Client::wait_for_eose`, are
+    /// kept by the client (for the last 100 events) and count too. Events received meanwhile are
+    /// stored in their subscription, see `Client::get_events`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::{str::FromStr, time::Duration};
+    ///
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let event = client.publish_text_note(&identity, "Hello Nostr!", &[], 0).unwrap();
+    ///
+    /// let status = client.wait_for_ok(&event.id, Duration::from_secs(5)).unwrap();
+    /// for (relay, ack) in status.received {
+    ///     println!("{}: accepted {} {}", relay, ack.accepted, ack.message);
+    /// }
     /// ```
-    pub fn next_data(&mut self) -> Result<Vec<(String, tungstenite::Message)>, ClientError> {
-        let mut events: Vec<(String, tungstenite::Message)> = Vec::new();
+    ///
+    /// With a mock relay whose OK is read by a query made meanwhile (see `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter, websocket::SimplifiedWS, Identity};
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, str::FromStr, thread, time::Duration};
+    /// use tungstenite::Message;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let url = format!("ws://{}", listener.local_addr().unwrap());
+    /// thread::spawn(move || {
+    ///     let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///
+    ///     while let Ok(message) = relay.read_message() {
+    ///         let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///
+    ///         let answer = match request[0].as_str() {
+    ///             Some("EVENT") => json!(["OK", request[1]["id"], true, ""]),
+    ///             Some("REQ") => json!(["EOSE", request[1]]),
+    ///             _ => continue,
+    ///         };
+    ///         relay.write_message(Message::text(answer.to_string())).unwrap();
+    ///     }
+    /// });
+    ///
+    /// let (socket, _) = tungstenite::connect(&url).unwrap();
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// client.add_connected_relay(&url, SimplifiedWS::from_socket(&url, socket).unwrap()).unwrap();
+    ///
+    /// let identity = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    /// let event = client.publish_text_note(&identity, "Hello Nostr!", &[], 0).unwrap();
+    /// client.get_events_of_per_relay(vec![ReqFilter::default()]).unwrap();
+    ///
+    /// let status = client.wait_for_ok(&event.id, Duration::from_secs(1)).unwrap();
+    /// assert!(status.received[&url].accepted);
+    /// ```
+    pub fn wait_for_ok(
+        &mut self,
+        event_id: &str,
+        timeout: Duration,
+    ) -> Result<OkStatus, ClientError> {
+        let deadline = Instant::now() + timeout;
+        let mut status = OkStatus {
+            received: HashMap::new(),
+            timed_out: self.relays.keys().cloned().collect(),
+        };
 
-        for (relay_name, socket) in self.relays.iter() {
-            let message = socket.lock().unwrap().read_message()?;
-            events.push((relay_name.clone(), message));
+        loop {
+            // The OK answers are kept by every read, including the reads of other methods
+            for (relay, ack) in self.take_ok_answers(event_id) {
+                status.timed_out.retain(|r| r != &relay);
+                status.received.insert(relay, ack);
+            }
+
+            if status.timed_out.is_empty() || Instant::now() >= deadline {
+                break;
+            }
+
+            let data = self.receive_all()?;
+
+            if data.is_empty() {
+                std::thread::sleep(EOSE_POLL_INTERVAL);
+                continue;
+            }
+
+            for (_, message, parsed) in data {
+                self.store_event(message, parsed);
+            }
         }
 
-        Ok(events)
+        Ok(status)
     }
 
     #[cfg(feature = "async")]
-    /// Get next data from the relays
+    /// Wait until every relay answered with an OK message for a published event, or until the timeout
+    ///
+    /// The OK messages read before, e.g. by `Client::get_events_of` or `Client::wait_for_eose`, are
+    /// kept by the client (for the last 100 events) and count too. Events received meanwhile are
+    /// stored in their subscription, see `Client::get_events`
     /// # Example
     /// ```rust
-    /// use std::{
-    ///  sync::{Arc, Mutex},
-    ///  thread,
-    /// };
-    /// use tungstenite::Message;
-    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// use nostr_rust::{nostr_client::Client, Identity};
+    /// use std::{str::FromStr, time::Duration};
     ///
-    /// fn handle_message(relay_url: &String, message: &Message) -> Result<(), String> {
-    ///   println!("Received message: {:?}", message);
+    /// #[tokio::test]
+    /// async fn test_wait_for_ok() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    ///     let event = client.publish_text_note(&identity, "Hello Nostr!", &[], 0).await.unwrap();
     ///
-    ///   Ok(())
+    ///     let status = client.wait_for_ok(&event.id, Duration::from_secs(5)).await.unwrap();
+    ///     for (relay, ack) in status.received {
+    ///         println!("{}: accepted {} {}", relay, ack.accepted, ack.message);
+    ///     }
     /// }
+    /// ```
+    pub async fn wait_for_ok(
+        &mut self,
+        event_id: &str,
+        timeout: Duration,
+    ) -> Result<OkStatus, ClientError> {
+        let deadline = Instant::now() + timeout;
+        let mut status = OkStatus {
+            received: HashMap::new(),
+            timed_out: self.relays.keys().cloned().collect(),
+        };
+
+        loop {
+            // The OK answers are kept by every read, including the reads of other methods
+            for (relay, ack) in self.take_ok_answers(event_id) {
+                status.timed_out.retain(|r| r != &relay);
+                status.received.insert(relay, ack);
+            }
+
+            if status.timed_out.is_empty() || Instant::now() >= deadline {
+                break;
+            }
+
+            let data = self.receive_all().await?;
+
+            if data.is_empty() {
+                tokio::time::sleep(EOSE_POLL_INTERVAL).await;
+                continue;
+            }
+
+            for (_, message, parsed) in data {
+                self.store_event(message, parsed);
+            }
+        }
+
+        Ok(status)
+    }
+
+    /// Store an EVENT message in its subscription, with the expiration of its parsed event
+    fn store_event(&mut self, message: Message, parsed: RelayMessage) {
+        match parsed {
+            RelayMessage::Event(subscription_id, event) => self.store_message(
+                &subscription_id,
+                message,
+                StoredExpiration::Known(event.expiration()),
+            ),
+            RelayMessage::Rejected(subscription_id) => {
+                self.store_message(&subscription_id, message, StoredExpiration::Known(None))
+            }
+            _ => {}
+        }
+    }
+
+    /// Store an EVENT message in its subscription, return the answer if the message is the EOSE
+    /// or the CLOSED of the given subscription
+    fn store_until_eose(
+        &mut self,
+        subscription_id: &str,
+        message: Message,
+        parsed: RelayMessage,
+    ) -> Option<SubscriptionAnswer> {
+        match parsed {
+            RelayMessage::Eose(id) if id == subscription_id => Some(SubscriptionAnswer::Eose),
+            // A relay closing the subscription won't send EOSE
+            RelayMessage::Closed(id, reason) if id == subscription_id => {
+                Some(SubscriptionAnswer::Closed(reason))
+            }
+            parsed => {
+                self.store_event(message, parsed);
+                None
+            }
+        }
+    }
+
+    /// Add event to a subscription
     ///
-    /// #[tokio::test]
-    /// async fn test_next_data() {
-    ///     let mut client = Arc::new(Mutex::new(Client::new(vec![env!("RELAY_URL")]).await.unwrap()));
+    /// A message already in the subscription, e.g. the same event sent again, is skipped
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Message};
     ///
-    ///     // Run a new thread to listen
-    ///     let nostr_clone = client.clone();
-    ///     let nostr_thread = thread::spawn(move || loop {
-    ///         let events = nostr_clone.lock().unwrap().next_data().await.unwrap();
-    ///    
-    ///         for (relay_url, message) in events.iter() {
-    ///             handle_message(relay_url, message).unwrap();
+    /// let mut client = Client::default();
+    /// let first = Message::Text(r#"["EVENT","sub",{"id":"a"}]"#.to_string());
+    /// client.add_event("sub", first.clone());
+    /// client.add_event("sub", Message::Text(r#"["EVENT","sub",{"id":"b"}]"#.to_string()));
+    /// client.add_event("sub", first.clone());
+    /// assert_eq!(client.subscriptions["sub"].len(), 2);
+    ///
+    /// // A message taken out of the subscription is stored again when received again
+    /// client.subscriptions.get_mut("sub").unwrap().remove(0);
+    /// client.add_event("sub", first);
+    /// assert_eq!(client.subscriptions["sub"].len(), 2);
+    /// ```
+    pub fn add_event(&mut self, subscription_id: &str, message: Message) {
+        self.store_message(subscription_id, message, StoredExpiration::Unknown);
+    }
+
+    fn store_message(
+        &mut self,
+        subscription_id: &str,
+        message: Message,
+        expiration: StoredExpiration,
+    ) {
+        let messages = self
+            .subscriptions
+            .entry(subscription_id.to_string())
+            .or_default();
+        let keys = self
+            .subscription_keys
+            .entry(subscription_id.to_string())
+            .or_default();
+
+        // `subscriptions` is public: each stored message has a key unless the messages were
+        // changed without `add_event`, the keys are then rebuilt from the messages
+        if keys.len() != messages.len() {
+            let mut old_keys = std::mem::take(keys);
+            *keys = messages
+                .iter()
+                .map(|message| {
+                    let key = message_key(message);
+                    let expiration = old_keys.remove(&key).unwrap_or(StoredExpiration::Unknown);
+
+                    (key, expiration)
+                })
+                .collect();
+        }
+
+        // Check if the message is already in the subscription, the messages keep their order
+        if let Entry::Vacant(entry) = keys.entry(message_key(&message)) {
+            entry.insert(expiration);
+            messages.push(message);
+        }
+    }
+
+    /// Get events and remove them from the subscription
+    ///
+    /// The events whose NIP-40 expiration has passed are skipped, only the messages stored by
+    /// `Client::add_event` are parsed to read it
+    pub fn get_events(&mut self, subscription_id: &str) -> Option<Vec<Message>> {
+        let keys = self
+            .subscription_keys
+            .remove(subscription_id)
+            .unwrap_or_default();
+        let mut messages = self.subscriptions.remove(subscription_id)?;
+        let now = get_timestamp();
+
+        messages.retain(|message| {
+            let expiration = match keys.get(&message_key(message)) {
+                Some(StoredExpiration::Known(expiration)) => *expiration,
+                _ => match RelayMessage::parse(message) {
+                    RelayMessage::Event(_, event) => event.expiration(),
+                    _ => None,
+                },
+            };
+
+            expiration.map_or(true, |expiration| expiration > now)
+        });
+
+        Some(messages)
+    }
+
+    /// Get the number of messages buffered by each subscription, e.g. to spot a subscription
+    /// consuming memory
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, Message};
+    ///
+    /// let mut client = Client::default();
+    /// client.add_event("sub", Message::Text(r#"["EVENT","sub",{"id":"a"}]"#.to_string()));
+    /// client.add_event("sub", Message::Text(r#"["EVENT","sub",{"id":"b"}]"#.to_string()));
+    /// assert_eq!(client.subscription_stats().get("sub"), Some(&2));
+    ///
+    /// assert_eq!(client.clear_subscription("sub"), 2);
+    /// assert_eq!(client.subscription_stats().get("sub"), None);
+    /// assert_eq!(client.clear_subscription("sub"), 0);
+    /// ```
+    pub fn subscription_stats(&self) -> HashMap<String, usize> {
+        self.subscriptions
+            .iter()
+            .map(|(id, messages)| (id.clone(), messages.len()))
+            .collect()
+    }
+
+    /// Drop the messages buffered by a subscription without closing it, return how many were
+    /// dropped
+    pub fn clear_subscription(&mut self, subscription_id: &str) -> usize {
+        self.subscription_keys.remove(subscription_id);
+
+        self.subscriptions
+            .remove(subscription_id)
+            .map_or(0, |messages| messages.len())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get events of a given filters
+    ///
+    /// The events of the cache (see `Client::set_cache`) matching the filters are returned too,
+    /// invalid messages sent by the relays are ignored (see `Client::take_rejected_events`).
+    /// With `Client::set_aggregate_limit`, when every filter has a limit, only the newest events
+    /// are returned, newest first. With `Client::set_max_connections`, the relays without free
+    /// connection are not queried, see `Client::last_skipped_relays`.
+    /// Every relay which received the subscription is waited for until it sends EOSE or until the
+    /// query timeout (see `Client::set_query_timeout`). The events of a relay which fails or
+    /// doesn't answer in time are kept, the relay is listed in `Client::last_failed_relays`: the
+    /// query only fails when no relay received the subscription
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let events = client.get_events_of(vec![ReqFilter {
+    ///    ids: None,
+    ///    authors: Some(vec!["884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()]),
+    ///    kinds: Some(vec![3]),
+    ///    e: None,
+    ///    p: None,
+    ///    d: None,
+    ///    h: None,
+    ///    since: None,
+    ///    until: None,
+    ///    limit: Some(1),
+    /// }]).unwrap();
+    /// ```
+    ///
+    /// A relay sending a garbage frame doesn't break the query, with a mock relay
+    /// (see `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter, websocket::SimplifiedWS};
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, thread};
+    /// use tungstenite::Message;
+    ///
+    /// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    /// let url = format!("ws://{}", listener.local_addr().unwrap());
+    /// thread::spawn(move || {
+    ///     let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///
+    ///     while let Ok(message) = relay.read_message() {
+    ///         let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///
+    ///         if request[0] == "REQ" {
+    ///             let event = json!({
+    ///                 "id": "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4",
+    ///                 "pubkey": "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6",
+    ///                 "created_at": 1673131597,
+    ///                 "kind": 1,
+    ///                 "tags": [],
+    ///                 "content": "Hello",
+    ///                 "sig": "",
+    ///             });
+    ///             relay.write_message(Message::text("not json")).unwrap();
+    ///             relay.write_message(Message::text(format!("[\"EVENT\",{}", request[1]))).unwrap();
+    ///             relay.write_message(Message::text(json!(["EVENT", request[1], event]).to_string())).unwrap();
+    ///             relay.write_message(Message::text(json!(["EOSE", request[1]]).to_string())).unwrap();
     ///         }
-    ///     });
+    ///     }
+    /// });
     ///
-    ///     // Subscribe to the most beautiful Nostr profile event
-    ///     client
-    ///     .lock()
-    ///     .unwrap()
-    ///     .subscribe(vec![ReqFilter {
-    ///         ids: None,
-    ///         authors: Some(vec![
-    ///             "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
-    ///         ]),
-    ///         kinds: None,
-    ///         e: None,
-    ///         p: None,
-    ///         since: None,
-    ///         until: None,
-    ///         limit: Some(1),
-    ///     }])
-    ///     .await
-    ///     .unwrap();
+    /// let (socket, _) = tungstenite::connect(&url).unwrap();
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// client.add_connected_relay(&url, SimplifiedWS::from_socket(&url, socket).unwrap()).unwrap();
     ///
-    /// // Wait 3s for the thread to finish
-    /// std::thread::sleep(std::time::Duration::from_secs(3));
-    /// }
+    /// let events = client.get_events_of(vec![ReqFilter::default()]).unwrap();
+    /// assert_eq!(events.len(), 1);
+    /// assert_eq!(events[0].content, "Hello");
     /// ```
-    pub async fn next_data(&mut self) -> Result<Vec<(String, tungstenite::Message)>, ClientError> {
-        let mut events: Vec<(String, tungstenite::Message)> = Vec::new();
+    pub fn get_events_of(&mut self, filters: Vec<ReqFilter>) -> Result<Vec<Event>, ClientError> {
+        let events: Vec<Event> = self.cached_events(&filters);
+        let seen_ids: HashSet<String> = events.iter().map(|event| event.id.clone()).collect();
+        let total_limit = self.total_limit(&filters);
+        let started = Instant::now();
+        self.query_timings.clear();
 
-        for (relay_name, socket) in self.relays.iter() {
-            let message = socket.lock().await.read_message().await?;
-            events.push((relay_name.clone(), message));
-        }
+        // Subscribe, then get the events until every relay which received the subscription sent
+        // EOSE, failed or timed out
+        let id = self.subscribe(filters)?;
+        let relays = self.subscribed_relays(&id);
+        let mut answers = QueryAnswers::default();
+        self.wait_for_query(&id, relays, started, &mut answers);
 
-        Ok(events)
+        // unsubscribe, the relays which can't be reached are listed with their error
+        self.unsubscribe_per_relay(&id);
+
+        Ok(self.merge_query_answers(events, seen_ids, answers, total_limit))
     }
 
     #[cfg(not(feature = "async"))]
-    /// Subscribe
+    /// Get events of a given filters, grouped by the relay which sent them
+    ///
+    /// Unlike `Client::get_events_of`, the events are neither merged nor completed with the cache:
+    /// comparing the relays shows which one is missing events the others have.
+    /// Every relay is waited for until it sends EOSE or until the query timeout (see
+    /// `Client::set_query_timeout`). A relay which can't be reached, closes the subscription
+    /// (`ClientError::SubscriptionClosed`) or doesn't answer in time (`ClientError::Timeout`) gets
+    /// its error, without its events, while the others are still collected. With
+    /// `Client::set_max_connections`, the relays are queried batch by batch so each one is asked
+    ///
     /// # Example
     /// ```rust
     /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
     /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
-    /// client
-    /// .subscribe(vec![ReqFilter { // None means generate a random ID
-    ///     ids: None,
-    ///     authors: Some(vec![
-    ///         "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
-    ///     ]),
-    ///     kinds: None,
-    ///     e: None,
-    ///     p: None,
-    ///     since: None,
-    ///     until: None,
-    ///     limit: Some(1),
-    /// }])
-    /// .unwrap();
+    /// let events_per_relay = client.get_events_of_per_relay(vec![ReqFilter {
+    ///    authors: Some(vec!["884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()]),
+    ///    kinds: Some(vec![1]),
+    ///    limit: Some(10),
+    ///    ..Default::default()
+    /// }]).unwrap();
+    ///
+    /// for (relay, events) in events_per_relay {
+    ///     match events {
+    ///         Ok(events) => println!("{}: {} events", relay, events.len()),
+    ///         Err(error) => println!("{}: {}", relay, error),
+    ///     }
+    /// }
     /// ```
-    pub fn subscribe(&mut self, filters: Vec<ReqFilter>) -> Result<String, ClientError> {
-        let req = Req::new(None, filters);
+    ///
+    /// With a mock relay which never sends EOSE (see `Client::add_connected_relay`):
+    /// ```rust
+    /// use nostr_rust::{
+    ///     events::EventPrepare, nostr_client::{Client, ClientError}, req::ReqFilter,
+    ///     websocket::SimplifiedWS, Identity,
+    /// };
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, str::FromStr, thread, time::Duration};
+    /// use tungstenite::Message;
+    ///
+    /// let identity = Identity::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
+    /// let event = EventPrepare::builder(&identity.public_key_str).content("hi").build().unwrap().to_event(&identity, 0);
+    ///
+    /// // Answer every REQ with the event, followed by EOSE if `send_eose`
+    /// let spawn_relay = |send_eose: bool| {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     let url = format!("ws://{}", listener.local_addr().unwrap());
+    ///     let event = event.clone();
+    ///     thread::spawn(move || {
+    ///         let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///
+    ///         while let Ok(message) = relay.read_message() {
+    ///             let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///
+    ///             if request[0] == "REQ" {
+    ///                 let answer = json!(["EVENT", request[1], event]);
+    ///                 relay.write_message(Message::text(answer.to_string())).unwrap();
+    ///                 if send_eose {
+    ///                     let eose = json!(["EOSE", request[1]]);
+    ///                     relay.write_message(Message::text(eose.to_string())).unwrap();
+    ///                 }
+    ///             }
+    ///         }
+    ///     });
+    ///
+    ///     url
+    /// };
+    ///
+    /// let mut client = Client::new(vec![]).unwrap();
+    /// let answering = spawn_relay(true);
+    /// let silent = spawn_relay(false);
+    /// for url in [&answering, &silent] {
+    ///     let (socket, _) = tungstenite::connect(url.as_str()).unwrap();
+    ///     client.add_connected_relay(url, SimplifiedWS::from_socket(url, socket).unwrap()).unwrap();
+    /// }
+    ///
+    /// client.set_query_timeout(Duration::from_millis(200));
+    /// let events_per_relay = client.get_events_of_per_relay(vec![ReqFilter::default()]).unwrap();
+    ///
+    /// assert_eq!(events_per_relay[&answering].as_ref().unwrap()[0].id, event.id);
+    /// assert!(matches!(&events_per_relay[&silent], Err(ClientError::Timeout(relay)) if relay == &silent));
+    /// assert_eq!(client.last_query_timings()[&silent], Duration::from_millis(200));
+    /// ```
+    ///
+    /// With a single connection, the relays are queried one after the other:
+    /// ```rust
+    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// use serde_json::{json, Value};
+    /// use std::{net::TcpListener, thread};
+    /// use tungstenite::Message;
+    ///
+    /// let mut client = Client::default();
+    /// client.set_max_connections(Some(1));
+    ///
+    /// for _ in 0..2 {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    ///     client.add_relay(&format!("ws://{}", listener.local_addr().unwrap())).unwrap();
+    ///     thread::spawn(move || {
+    ///         let mut relay = tungstenite::accept(listener.accept().unwrap().0).unwrap();
+    ///
+    ///         while let Ok(message) = relay.read_message() {
+    ///             let request: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    ///
+    ///             if request[0] == "REQ" {
+    ///                 relay.write_message(Message::text(json!(["EOSE", request[1]]).to_string())).unwrap();
+    ///             }
+    ///         }
+    ///     });
+    /// }
+    ///
+    /// let events_per_relay = client.get_events_of_per_relay(vec![ReqFilter::default()]).unwrap();
+    /// assert_eq!(events_per_relay.len(), 2);
+    /// assert!(events_per_relay.values().all(Result::is_ok));
+    /// ```
+    pub fn get_events_of_per_relay(
+        &mut self,
+        filters: Vec<ReqFilter>,
+    ) -> Result<EventsPerRelay, ClientError> {
+        let relays = self.query_relays();
+
+        self.get_events_of_relays(relays, filters)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Get events of a given filters from the given relays of the client, grouped by relay, see
+    /// `Client::get_events_of_per_relay`
+    pub(crate) fn get_events_of_relays(
+        &mut self,
+        mut pending_relays: Vec<String>,
+        filters: Vec<ReqFilter>,
+    ) -> Result<EventsPerRelay, ClientError> {
+        self.query_timings.clear();
+
+        let id = self.next_subscription_id()?;
+        let req = Req::new(Some(&id), filters);
         let message = Message::text(req.to_string());
 
-        for relay in self.relays.values() {
-            let mut relay = relay.lock().unwrap();
-            relay.send_message(&message)?;
+        let mut answers = QueryAnswers::default();
+
+        // With `Client::set_max_connections` the relays are queried batch by batch, a batch ends
+        // when each of its relays answered, failed or timed out, freeing its connection
+        while !pending_relays.is_empty() {
+            let started = Instant::now();
+            let mut waiting_relays: Vec<String> = Vec::new();
+            self.subscription_filters
+                .insert(id.clone(), req.filters.clone());
+
+            // Subscribe, a relay which can't be reached gets its error
+            for relay in std::mem::take(&mut pending_relays) {
+                match self.send_subscription(&relay, &id, &message) {
+                    Ok(()) => waiting_relays.push(relay),
+                    // Every connection is taken by the batch, the relay waits for the next one
+                    Err(ClientError::NoIdleConnection(_)) if !waiting_relays.is_empty() => {
+                        pending_relays.push(relay);
+                    }
+                    Err(error) => {
+                        answers.errors.insert(relay, error);
+                    }
+                }
+            }
+
+            self.wait_for_query(&id, waiting_relays, started, &mut answers);
+
+            // unsubscribe, the relays which can't be reached already have their error
+            self.unsubscribe_per_relay(&id);
+        }
+
+        Ok(self.group_events_per_relay(answers))
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Read the given relays until each one sent EOSE for the subscription, failed, closed it or
+    /// didn't answer within the query timeout from `started`, see `Client::last_query_timings`
+    fn wait_for_query(
+        &mut self,
+        subscription_id: &str,
+        mut waiting_relays: Vec<String>,
+        started: Instant,
+        answers: &mut QueryAnswers,
+    ) {
+        let deadline = started + self.query_timeout;
+
+        while !waiting_relays.is_empty() && Instant::now() < deadline {
+            let mut received = false;
+
+            for relay in waiting_relays.clone() {
+                let data = match self.next_data_of(&relay) {
+                    Ok(data) => data,
+                    Err(error) => {
+                        waiting_relays.retain(|r| r != &relay);
+                        answers.errors.insert(relay, error);
+                        continue;
+                    }
+                };
+                received |= !data.is_empty();
+
+                for (message, parsed) in data {
+                    match self.collect_until_eose(
+                        subscription_id,
+                        &relay,
+                        message,
+                        parsed,
+                        &mut answers.events,
+                    ) {
+                        Some(SubscriptionAnswer::Eose) => {
+                            waiting_relays.retain(|r| r != &relay);
+                            answers.answered_relays.push(relay.clone());
+                            self.query_timings.insert(relay.clone(), started.elapsed());
+                        }
+                        Some(SubscriptionAnswer::Closed(reason)) => {
+                            waiting_relays.retain(|r| r != &relay);
+                            answers.errors.insert(
+                                relay.clone(),
+                                ClientError::SubscriptionClosed(relay.clone(), reason),
+                            );
+                        }
+                        None => {}
+                    }
+                }
+            }
+
+            if !received {
+                std::thread::sleep(EOSE_POLL_INTERVAL);
+            }
         }
 
-        Ok(req.subscription_id)
+        // A relay which didn't send EOSE in time is listed with the whole query timeout
+        for relay in waiting_relays {
+            self.query_timings.insert(relay.clone(), self.query_timeout);
+            answers
+                .errors
+                .insert(relay.clone(), ClientError::Timeout(relay));
+        }
     }
 
-    #[cfg(feature = "async")]
-    /// Subscribe
+    #[cfg(not(feature = "async"))]
+    /// Get events of a given filters sent by at least `min_relays` relays, newest first
+    ///
+    /// An event served by fewer relays, e.g. by a single lying relay, is excluded. Every relay is
+    /// waited for until it sends EOSE or times out, the failing relays count as relays without
+    /// the event, see `Client::get_events_of_per_relay`
+    ///
     /// # Example
     /// ```rust
     /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
-    ///
-    /// #[tokio::test]
-    /// async fn test_subscribe() {
-    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
-    ///     client
-    ///     .subscribe(vec![ReqFilter { // None means generate a random ID
-    ///         ids: None,
-    ///         authors: Some(vec![
-    ///             "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
-    ///         ]),
-    ///         kinds: None,
-    ///         e: None,
-    ///         p: None,
-    ///         since: None,
-    ///         until: None,
-    ///         limit: Some(1),
-    ///     }])
-    ///     .await
-    ///     .unwrap();
-    /// }
+    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
+    /// let events = client.get_events_of_quorum(vec![ReqFilter {
+    ///    authors: Some(vec!["884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()]),
+    ///    kinds: Some(vec![1]),
+    ///    limit: Some(10),
+    ///    ..Default::default()
+    /// }], 2).unwrap();
     /// ```
-    pub async fn subscribe(&mut self, filters: Vec<ReqFilter>) -> Result<String, ClientError> {
-        let req = Req::new(None, filters);
-        let message = Message::text(req.to_string());
-
-        for relay in self.relays.values() {
-            let mut relay = relay.lock().await;
-            relay.send_message(&message).await?;
-        }
+    pub fn get_events_of_quorum(
+        &mut self,
+        filters: Vec<ReqFilter>,
+        min_relays: usize,
+    ) -> Result<Vec<Event>, ClientError> {
+        let events_per_relay = self.get_events_of_per_relay(filters)?;
 
-        Ok(req.subscription_id)
+        Ok(quorum_events(events_per_relay, min_relays))
     }
 
     #[cfg(not(feature = "async"))]
-    /// Subscribe with a specific ID
+    /// Get the newest version of a replaceable event of an author across the relays
+    ///
+    /// `limit: 1` is not enough since the relays may order the events differently: every relay
+    /// is waited for until it sends EOSE or times out (the failing relays are ignored) and the
+    /// candidate with the highest `created_at` is kept.
+    /// For an addressable kind (30000 to 39999), `d` is the identifier of the event requested with
+    /// a `#d` filter, `None` being the empty identifier
     ///
     /// # Example
     /// ```rust
-    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
+    /// use nostr_rust::nostr_client::Client;
     /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
-    /// client
-    /// .subscribe_with_id("my_subscription_id", vec![ReqFilter {
-    ///    ids: None,
-    ///    authors: Some(vec![
-    ///      "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
-    ///    ]),
-    ///    kinds: None,
-    ///    e: None,
-    ///    p: None,
-    ///    since: None,
-    ///    until: None,
-    ///    limit: Some(1),
-    /// }])
-    /// .unwrap();
+    ///
+    /// let metadata = client.get_latest_replaceable("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 0, None).unwrap();
+    /// let article = client.get_latest_replaceable("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 30023, Some("my-article")).unwrap();
     /// ```
-    pub fn subscribe_with_id(
+    pub fn get_latest_replaceable(
         &mut self,
-        subscription_id: &str,
-        filters: Vec<ReqFilter>,
-    ) -> Result<(), ClientError> {
-        let req = Req::new(Some(subscription_id), filters);
-        let message = Message::text(req.to_string());
-
-        for relay in self.relays.values() {
-            let mut relay = relay.lock().unwrap();
-            relay.send_message(&message)?;
-        }
+        pubkey: &str,
+        kind: u16,
+        d: Option<&str>,
+    ) -> Result<Option<Event>, ClientError> {
+        let events_per_relay =
+            self.get_events_of_per_relay(vec![latest_replaceable_filter(pubkey, kind, d)])?;
 
-        Ok(())
+        Ok(latest_replaceable_event(events_per_relay))
     }
 
     #[cfg(feature = "async")]
-    /// Subscribe with a specific ID
+    /// Get events of a given filters
+    ///
+    /// The events of the cache (see `Client::set_cache`) matching the filters are returned too,
+    /// invalid messages sent by the relays are ignored (see `Client::take_rejected_events`).
+    /// With `Client::set_aggregate_limit`, when every filter has a limit, only the newest events
+    /// are returned, newest first. With `Client::set_max_connections`, the relays without free
+    /// connection are not queried, see `Client::last_skipped_relays`.
+    /// Every relay which received the subscription is waited for until it sends EOSE or until the
+    /// query timeout (see `Client::set_query_timeout`). The events of a relay which fails or
+    /// doesn't answer in time are kept, the relay is listed in `Client::last_failed_relays`: the
+    /// query only fails when no relay received the subscription
     ///
     /// # Example
     /// ```rust
     /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
     ///
     /// #[tokio::test]
-    /// async fn test_subscribe_with_id() {
+    /// async fn test_get_events_of() {
     ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
-    ///     client
-    ///     .subscribe_with_id("my_subscription_id", vec![ReqFilter {
+    ///     let events = client.get_events_of(vec![ReqFilter {
     ///        ids: None,
-    ///        authors: Some(vec![
-    ///          "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
-    ///        ]),
-    ///        kinds: None,
+    ///        authors: Some(vec!["884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()]),
+    ///        kinds: Some(vec![3]),
     ///        e: None,
     ///        p: None,
+    ///        d: None,
+    ///        h: None,
     ///        since: None,
     ///        until: None,
     ///        limit: Some(1),
-    ///     }])
-    ///     .await
+    ///     }]).await
     ///     .unwrap();
     /// }
     /// ```
-    pub async fn subscribe_with_id(
+    pub async fn get_events_of(
         &mut self,
-        subscription_id: &str,
         filters: Vec<ReqFilter>,
-    ) -> Result<(), ClientError> {
-        let req = Req::new(Some(subscription_id), filters);
-        let message = Message::text(req.to_string());
-
-        for relay in self.relays.values() {
-            let mut relay = relay.lock().await;
-            relay.send_message(&message).await?;
-        }
-
-        Ok(())
-    }
+    ) -> Result<Vec<Event>, ClientError> {
+        let events: Vec<Event> = self.cached_events(&filters);
+        let seen_ids: HashSet<String> = events.iter().map(|event| event.id.clone()).collect();
+        let total_limit = self.total_limit(&filters);
+        let started = Instant::now();
+        self.query_timings.clear();
 
-    #[cfg(not(feature = "async"))]
-    /// Unsubscribe
-    /// # Example
-    /// ```rust
-    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
-    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
-    /// let subscription_id = client
-    /// .subscribe(vec![ReqFilter {
-    ///    ids: None,
-    ///   authors: Some(vec![
-    ///        "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
-    ///   ]),
-    ///  kinds: None,
-    ///  e: None,
-    ///  p: None,
-    ///  since: None,
-    ///  until: None,
-    ///  limit: Some(1),
-    /// }])
-    /// .unwrap();
-    /// client.unsubscribe(&subscription_id).unwrap();
-    /// ```
-    pub fn unsubscribe(&mut self, subscription_id: &str) -> Result<(), ClientError> {
-        let message = Message::text(json!(["CLOSE", subscription_id]).to_string());
+        // Subscribe, then get the events until every relay which received the subscription sent
+        // EOSE, failed or timed out
+        let id = self.subscribe(filters).await?;
+        let relays = self.subscribed_relays(&id);
+        let mut answers = QueryAnswers::default();
+        self.wait_for_query(&id, relays, started, &mut answers)
+            .await;
 
-        for relay in self.relays.values() {
-            let mut relay = relay.lock().unwrap();
-            relay.send_message(&message)?;
-        }
+        // unsubscribe, the relays which can't be reached are listed with their error
+        self.unsubscribe_per_relay(&id).await;
 
-        Ok(())
+        Ok(self.merge_query_answers(events, seen_ids, answers, total_limit))
     }
 
     #[cfg(feature = "async")]
-    /// Unsubscribe
+    /// Get events of a given filters, grouped by the relay which sent them
+    ///
+    /// Unlike `Client::get_events_of`, the events are neither merged nor completed with the cache:
+    /// comparing the relays shows which one is missing events the others have.
+    /// Every relay is waited for until it sends EOSE or until the query timeout (see
+    /// `Client::set_query_timeout`). A relay which can't be reached, closes the subscription
+    /// (`ClientError::SubscriptionClosed`) or doesn't answer in time (`ClientError::Timeout`) gets
+    /// its error, without its events, while the others are still collected. With
+    /// `Client::set_max_connections`, the relays are queried batch by batch so each one is asked
+    ///
     /// # Example
     /// ```rust
     /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
     ///
     /// #[tokio::test]
-    /// async fn test_unsubscribe() {
+    /// async fn test_get_events_of_per_relay() {
     ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
-    ///     let subscription_id = client
-    ///     .subscribe(vec![ReqFilter {
-    ///        ids: None,
-    ///       authors: Some(vec![
-    ///            "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
-    ///       ]),
-    ///      kinds: None,
-    ///      e: None,
-    ///      p: None,
-    ///      since: None,
-    ///      until: None,
-    ///      limit: Some(1),
-    ///     }])
-    ///     .await
+    ///     let events_per_relay = client.get_events_of_per_relay(vec![ReqFilter {
+    ///        authors: Some(vec!["884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()]),
+    ///        kinds: Some(vec![1]),
+    ///        limit: Some(10),
+    ///        ..Default::default()
+    ///     }]).await
     ///     .unwrap();
-    ///     client.unsubscribe(&subscription_id).await.unwrap();
+    ///
+    ///     for (relay, events) in events_per_relay {
+    ///         match events {
+    ///             Ok(events) => println!("{}: {} events", relay, events.len()),
+    ///             Err(error) => println!("{}: {}", relay, error),
+    ///         }
+    ///     }
     /// }
     /// ```
-    pub async fn unsubscribe(&mut self, subscription_id: &str) -> Result<(), ClientError> {
-        let message = Message::text(json!(["CLOSE", subscription_id]).to_string());
-
-        for relay in self.relays.values() {
-            let mut relay = relay.lock().await;
-            relay.send_message(&message).await?;
-        }
-
-        Ok(())
-    }
-
-    /// Add event to a subscription
-    pub fn add_event(&mut self, subscription_id: &str, message: Message) {
-        // Check if the subscription exists
-        if !self.subscriptions.contains_key(subscription_id) {
-            self.subscriptions
-                .insert(subscription_id.to_string(), Vec::new());
-        }
-
-        // Check if the message is already in the subscription
-        if !self.subscriptions[subscription_id].contains(&message) {
-            // Add the message to the subscription
-            self.subscriptions
-                .get_mut(subscription_id)
-                .unwrap()
-                .push(message);
-        }
-    }
+    pub async fn get_events_of_per_relay(
+        &mut self,
+        filters: Vec<ReqFilter>,
+    ) -> Result<EventsPerRelay, ClientError> {
+        let relays = self.query_relays();
 
-    /// Get events and remove them from the subscription
-    pub fn get_events(&mut self, subscription_id: &str) -> Option<Vec<Message>> {
-        self.subscriptions.remove(subscription_id)
+        self.get_events_of_relays(relays, filters).await
     }
 
-    #[cfg(not(feature = "async"))]
-    /// Get events of a given filters
-    ///
-    /// # Example
-    /// ```rust
-    /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
-    /// let mut client = Client::new(vec![env!("RELAY_URL")]).unwrap();
-    /// let events = client.get_events_of(vec![ReqFilter {
-    ///    ids: None,
-    ///    authors: Some(vec!["884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()]),
-    ///    kinds: Some(vec![3]),
-    ///    e: None,
-    ///    p: None,
-    ///    since: None,
-    ///    until: None,
-    ///    limit: Some(1),
-    /// }]).unwrap();
-    /// ```
-    pub fn get_events_of(&mut self, filters: Vec<ReqFilter>) -> Result<Vec<Event>, ClientError> {
-        let mut events: Vec<Event> = Vec::new();
-
-        // Subscribe
-        let id = self.subscribe(filters)?;
-
-        let mut waiting_relays: Vec<String> = self.relays.keys().map(|k| k.to_string()).collect();
+    #[cfg(feature = "async")]
+    /// Get events of a given filters from the given relays of the client, grouped by relay, see
+    /// `Client::get_events_of_per_relay`
+    pub(crate) async fn get_events_of_relays(
+        &mut self,
+        mut pending_relays: Vec<String>,
+        filters: Vec<ReqFilter>,
+    ) -> Result<EventsPerRelay, ClientError> {
+        self.query_timings.clear();
 
-        // Get the events
-        while !waiting_relays.is_empty() {
-            let data = self.next_data()?;
-            let mut break_loop = false;
+        let id = self.next_subscription_id()?;
+        let req = Req::new(Some(&id), filters);
+        let message = Message::text(req.to_string());
 
-            for (relay, message) in data {
-                let event: Value = serde_json::from_str(&message.to_string())?;
+        let mut answers = QueryAnswers::default();
 
-                if event[0] == "EOSE" && event[1].as_str() == Some(&id) {
-                    let index = waiting_relays.iter().position(|r| r == &relay).unwrap();
-                    waiting_relays.remove(index);
+        // With `Client::set_max_connections` the relays are queried batch by batch, a batch ends
+        // when each of its relays answered, failed or timed out, freeing its connection
+        while !pending_relays.is_empty() {
+            let started = Instant::now();
+            let mut waiting_relays: Vec<String> = Vec::new();
+            self.subscription_filters
+                .insert(id.clone(), req.filters.clone());
 
-                    break_loop = true;
-                    break;
+            // Subscribe, a relay which can't be reached gets its error
+            for relay in std::mem::take(&mut pending_relays) {
+                match self.send_subscription(&relay, &id, &message).await {
+                    Ok(()) => waiting_relays.push(relay),
+                    // Every connection is taken by the batch, the relay waits for the next one
+                    Err(ClientError::NoIdleConnection(_)) if !waiting_relays.is_empty() => {
+                        pending_relays.push(relay);
+                    }
+                    Err(error) => {
+                        answers.errors.insert(relay, error);
+                    }
                 }
-
-                self.add_event(&id, message);
             }
 
-            if break_loop {
-                break;
-            }
+            self.wait_for_query(&id, waiting_relays, started, &mut answers)
+                .await;
+
+            // unsubscribe, the relays which can't be reached already have their error
+            self.unsubscribe_per_relay(&id).await;
         }
 
-        // unsubscribe
-        self.unsubscribe(&id)?;
+        Ok(self.group_events_per_relay(answers))
+    }
 
-        // Get the events
-        if let Some(messages) = self.get_events(&id) {
-            for message in messages {
-                if !message.is_text() {
-                    continue;
-                }
+    #[cfg(feature = "async")]
+    /// Read the given relays until each one sent EOSE for the subscription, failed, closed it or
+    /// didn't answer within the query timeout from `started`, see `Client::last_query_timings`
+    async fn wait_for_query(
+        &mut self,
+        subscription_id: &str,
+        mut waiting_relays: Vec<String>,
+        started: Instant,
+        answers: &mut QueryAnswers,
+    ) {
+        let deadline = started + self.query_timeout;
 
-                let event: Value = serde_json::from_str(&message.to_string())?;
+        while !waiting_relays.is_empty() && Instant::now() < deadline {
+            let mut received = false;
 
-                let event_object = serde_json::from_value::<Event>(event[2].clone());
+            for relay in waiting_relays.clone() {
+                let data = match self.next_data_of(&relay).await {
+                    Ok(data) => data,
+                    Err(error) => {
+                        waiting_relays.retain(|r| r != &relay);
+                        answers.errors.insert(relay, error);
+                        continue;
+                    }
+                };
+                received |= !data.is_empty();
 
-                if event_object.is_err() {
-                    continue;
+                for (message, parsed) in data {
+                    match self.collect_until_eose(
+                        subscription_id,
+                        &relay,
+                        message,
+                        parsed,
+                        &mut answers.events,
+                    ) {
+                        Some(SubscriptionAnswer::Eose) => {
+                            waiting_relays.retain(|r| r != &relay);
+                            answers.answered_relays.push(relay.clone());
+                            self.query_timings.insert(relay.clone(), started.elapsed());
+                        }
+                        Some(SubscriptionAnswer::Closed(reason)) => {
+                            waiting_relays.retain(|r| r != &relay);
+                            answers.errors.insert(
+                                relay.clone(),
+                                ClientError::SubscriptionClosed(relay.clone(), reason),
+                            );
+                        }
+                        None => {}
+                    }
                 }
+            }
 
-                events.push(event_object.unwrap());
+            if !received {
+                tokio::time::sleep(EOSE_POLL_INTERVAL).await;
             }
         }
-        Ok(events)
+
+        // A relay which didn't send EOSE in time is listed with the whole query timeout
+        for relay in waiting_relays {
+            self.query_timings.insert(relay.clone(), self.query_timeout);
+            answers
+                .errors
+                .insert(relay.clone(), ClientError::Timeout(relay));
+        }
     }
 
     #[cfg(feature = "async")]
-    /// Get events of a given filters
+    /// Get events of a given filters sent by at least `min_relays` relays, newest first
+    ///
+    /// An event served by fewer relays, e.g. by a single lying relay, is excluded. Every relay is
+    /// waited for until it sends EOSE or times out, the failing relays count as relays without
+    /// the event, see `Client::get_events_of_per_relay`
     ///
     /// # Example
     /// ```rust
     /// use nostr_rust::{nostr_client::Client, req::ReqFilter};
     ///
     /// #[tokio::test]
-    /// async fn test_get_events_of() {
+    /// async fn test_get_events_of_quorum() {
     ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
-    ///     let events = client.get_events_of(vec![ReqFilter {
-    ///        ids: None,
+    ///     let events = client.get_events_of_quorum(vec![ReqFilter {
     ///        authors: Some(vec!["884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string()]),
-    ///        kinds: Some(vec![3]),
-    ///        e: None,
-    ///        p: None,
-    ///        since: None,
-    ///        until: None,
-    ///        limit: Some(1),
-    ///     }]).await
+    ///        kinds: Some(vec![1]),
+    ///        limit: Some(10),
+    ///        ..Default::default()
+    ///     }], 2).await
     ///     .unwrap();
     /// }
     /// ```
-    pub async fn get_events_of(
+    pub async fn get_events_of_quorum(
         &mut self,
         filters: Vec<ReqFilter>,
+        min_relays: usize,
     ) -> Result<Vec<Event>, ClientError> {
-        let mut events: Vec<Event> = Vec::new();
+        let events_per_relay = self.get_events_of_per_relay(filters).await?;
 
-        // Subscribe
-        let id = self.subscribe(filters).await?;
+        Ok(quorum_events(events_per_relay, min_relays))
+    }
 
-        let mut waiting_relays: Vec<String> = self.relays.keys().map(|k| k.to_string()).collect();
+    #[cfg(feature = "async")]
+    /// Get the newest version of a replaceable event of an author across the relays
+    ///
+    /// `limit: 1` is not enough since the relays may order the events differently: every relay
+    /// is waited for until it sends EOSE or times out (the failing relays are ignored) and the
+    /// candidate with the highest `created_at` is kept.
+    /// For an addressable kind (30000 to 39999), `d` is the identifier of the event requested with
+    /// a `#d` filter, `None` being the empty identifier
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::nostr_client::Client;
+    ///
+    /// #[tokio::test]
+    /// async fn test_get_latest_replaceable() {
+    ///     let mut client = Client::new(vec![env!("RELAY_URL")]).await.unwrap();
+    ///     let metadata = client.get_latest_replaceable("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 0, None).await.unwrap();
+    ///     let article = client.get_latest_replaceable("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6", 30023, Some("my-article")).await.unwrap();
+    /// }
+    /// ```
+    pub async fn get_latest_replaceable(
+        &mut self,
+        pubkey: &str,
+        kind: u16,
+        d: Option<&str>,
+    ) -> Result<Option<Event>, ClientError> {
+        let events_per_relay = self
+            .get_events_of_per_relay(vec![latest_replaceable_filter(pubkey, kind, d)])
+            .await?;
 
-        // Get the events
-        while !waiting_relays.is_empty() {
-            let data = self.next_data().await?;
-            let mut break_loop = false;
+        Ok(latest_replaceable_event(events_per_relay))
+    }
 
-            for (relay, message) in data {
-                let event: Value = serde_json::from_str(&message.to_string()).unwrap();
+    /// Keep an event of the given subscription along with its relay, store the other EVENT
+    /// messages in their subscription. Return the answer if the message is the EOSE or the
+    /// CLOSED of the given subscription
+    fn collect_until_eose(
+        &mut self,
+        subscription_id: &str,
+        relay: &str,
+        message: Message,
+        parsed: RelayMessage,
+        events: &mut Vec<(String, Event)>,
+    ) -> Option<SubscriptionAnswer> {
+        match parsed {
+            RelayMessage::Event(id, event) if id == subscription_id => {
+                events.push((relay.to_string(), event));
+                None
+            }
+            RelayMessage::Rejected(id) if id == subscription_id => {
+                self.reject_event(&message);
+                None
+            }
+            parsed => self.store_until_eose(subscription_id, message, parsed),
+        }
+    }
 
-                if event[0] == "EOSE" && event[1].as_str() == Some(&id) {
-                    let index = waiting_relays.iter().position(|r| r == &relay).unwrap();
-                    waiting_relays.remove(index);
+    /// Relays which received the given subscription
+    fn subscribed_relays(&self, subscription_id: &str) -> Vec<String> {
+        self.relay_subscriptions
+            .iter()
+            .filter(|(_, subscriptions)| subscriptions.contains(subscription_id))
+            .map(|(relay, _)| relay.clone())
+            .collect()
+    }
 
-                    break_loop = true;
-                    break;
-                }
+    /// Add the events received by `Client::get_events_of` to the cached events, skipping the
+    /// expired ones. The relays which failed are listed in `Client::last_failed_relays`, their
+    /// events are kept
+    fn merge_query_answers(
+        &mut self,
+        mut events: Vec<Event>,
+        mut seen_ids: HashSet<String>,
+        answers: QueryAnswers,
+        total_limit: Option<usize>,
+    ) -> Vec<Event> {
+        let now = get_timestamp();
+        self.failed_relays.extend(answers.errors);
 
-                self.add_event(&id, message);
+        for (_, event_object) in answers.events {
+            if event_object.is_expired_at(now) {
+                continue;
             }
 
-            if break_loop {
-                break;
+            self.cache.put(&event_object);
+
+            if seen_ids.insert(event_object.id.clone()) {
+                events.push(event_object);
             }
         }
 
-        // unsubscribe
-        self.unsubscribe(&id).await?;
+        if let Some(total_limit) = total_limit {
+            keep_newest(&mut events, total_limit);
+        }
 
-        // Get the events
-        if let Some(messages) = self.get_events(&id) {
-            for message in messages {
-                if !message.is_text() {
-                    continue;
-                }
+        events
+    }
 
-                let event: Value = serde_json::from_str(&message.to_string())?;
+    /// Group the events received from each relay which sent EOSE, the expired events are skipped.
+    /// The other relays get their error
+    fn group_events_per_relay(&mut self, answers: QueryAnswers) -> EventsPerRelay {
+        let QueryAnswers {
+            events,
+            answered_relays,
+            errors,
+        } = answers;
+        let now = get_timestamp();
+        let mut events_per_relay: HashMap<String, (HashSet<String>, Vec<Event>)> = answered_relays
+            .into_iter()
+            .map(|relay| (relay, (HashSet::new(), Vec::new())))
+            .collect();
 
-                let event_object = serde_json::from_value::<Event>(event[2].clone());
+        for (relay, event_object) in events {
+            if event_object.is_expired_at(now) {
+                continue;
+            }
 
-                if event_object.is_err() {
-                    continue;
-                }
+            self.cache.put(&event_object);
 
-                events.push(event_object.unwrap());
+            // The events of a failing relay may be incomplete
+            let Some((seen_ids, events)) = events_per_relay.get_mut(&relay) else {
+                continue;
+            };
+            if seen_ids.insert(event_object.id.clone()) {
+                events.push(event_object);
             }
         }
-        Ok(events)
+
+        events_per_relay
+            .into_iter()
+            .map(|(relay, (_, events))| (relay, Ok(events)))
+            .chain(errors.into_iter().map(|(relay, error)| (relay, Err(error))))
+            .collect()
     }
 }
@@ -1,7 +1,9 @@
-use crate::utils::random_hash;
+use crate::events::{Event, EventId};
+use crate::utils::{random_hash, tag_value, to_timestamp};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Req struct is used to request events and subscribe to new updates.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,7 +15,7 @@ pub struct Req {
 }
 
 /// ReqFilter is a JSON object that determines what events will be sent in that subscription.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ReqFilter {
     /// a list of event ids or prefixes
     pub ids: Option<Vec<String>>,
@@ -27,6 +29,12 @@ pub struct ReqFilter {
     /// a list of pubkeys that are referenced in a "p" tag
     #[serde(rename = "#p")]
     pub p: Option<Vec<String>>,
+    /// a list of identifiers of addressable events that are in a "d" tag
+    #[serde(rename = "#d")]
+    pub d: Option<Vec<String>>,
+    /// a list of group ids that are referenced in a "h" tag (NIP29)
+    #[serde(rename = "#h")]
+    pub h: Option<Vec<String>>,
     /// a timestamp, events must be newer than this to pass
     pub since: Option<u64>,
     /// a timestamp, events must be older than this to pass
@@ -36,6 +44,219 @@ pub struct ReqFilter {
 }
 
 impl ReqFilter {
+    /// Filter matching the events with the given ids
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventId, req::ReqFilter};
+    ///
+    /// let id = EventId::from_hex("f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4").unwrap();
+    /// let filter = ReqFilter::from_ids(&[id]);
+    /// assert_eq!(filter.ids, Some(vec![id.to_hex()]));
+    /// ```
+    pub fn from_ids(ids: &[EventId]) -> Self {
+        Self {
+            ids: Some(ids.iter().map(EventId::to_hex).collect()),
+            ..Default::default()
+        }
+    }
+
+    /// Filter matching the events referencing ("e" tag) one of the given events
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventId, req::ReqFilter};
+    ///
+    /// let id = EventId::from_hex("f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4").unwrap();
+    /// let filter = ReqFilter::referencing(&[id]);
+    /// assert_eq!(filter.e, Some(vec![id.to_hex()]));
+    /// ```
+    pub fn referencing(ids: &[EventId]) -> Self {
+        Self {
+            e: Some(ids.iter().map(EventId::to_hex).collect()),
+            ..Default::default()
+        }
+    }
+
+    /// Filter matching the events newer than `now - duration`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{req::ReqFilter, utils::get_timestamp};
+    /// use std::time::Duration;
+    ///
+    /// let filter = ReqFilter::since_ago(Duration::from_secs(3600));
+    /// assert!(filter.since.unwrap() + 3600 >= get_timestamp() - 1);
+    /// assert_eq!(filter.until, None);
+    /// ```
+    pub fn since_ago(duration: Duration) -> Self {
+        Self {
+            since: Some(to_timestamp(
+                SystemTime::now()
+                    .checked_sub(duration)
+                    .unwrap_or(UNIX_EPOCH),
+            )),
+            ..Default::default()
+        }
+    }
+
+    /// Filter matching the events created between `start` and `end`
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::req::ReqFilter;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let filter = ReqFilter::between(UNIX_EPOCH + Duration::from_secs(10), UNIX_EPOCH + Duration::from_secs(20));
+    /// assert_eq!(filter.since, Some(10));
+    /// assert_eq!(filter.until, Some(20));
+    /// ```
+    pub fn between(start: SystemTime, end: SystemTime) -> Self {
+        Self {
+            since: Some(to_timestamp(start)),
+            until: Some(to_timestamp(end)),
+            ..Default::default()
+        }
+    }
+
+    /// Check if an event matches the filter, `limit` is ignored
+    ///
+    /// `ids` and `authors` match by prefix
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::Event, req::ReqFilter};
+    ///
+    /// let event = Event {
+    ///   id: "f0382d932ddc5876bad3f9c5fdb84fb4c2af7ccefebfb491f13fbc47c38f8ae4".to_string(),
+    ///   pub_key: "884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6".to_string(),
+    ///   created_at: 1673131597,
+    ///   kind: 1,
+    ///   tags: vec![vec!["p".to_string(), "pubkey".to_string()]],
+    ///   content: String::new(),
+    ///   sig: String::new(),
+    /// };
+    ///
+    /// assert!(ReqFilter::default().matches(&event));
+    /// assert!(ReqFilter { authors: Some(vec!["884704".to_string()]), kinds: Some(vec![1]), ..Default::default() }.matches(&event));
+    /// assert!(ReqFilter { p: Some(vec!["pubkey".to_string()]), ..Default::default() }.matches(&event));
+    /// assert!(!ReqFilter { e: Some(vec!["event_id".to_string()]), ..Default::default() }.matches(&event));
+    /// assert!(!ReqFilter { h: Some(vec!["group".to_string()]), ..Default::default() }.matches(&event));
+    /// assert!(!ReqFilter { since: Some(1673131598), ..Default::default() }.matches(&event));
+    /// ```
+    pub fn matches(&self, event: &Event) -> bool {
+        let prefix_match = |values: &Option<Vec<String>>, field: &str| {
+            values.as_ref().map_or(true, |values| {
+                values.iter().any(|value| field.starts_with(value.as_str()))
+            })
+        };
+
+        let tag_match = |values: &Option<Vec<String>>, name: &str| {
+            values.as_ref().map_or(true, |values| {
+                event.tags_by_name(name).any(|tag| {
+                    tag_value(tag, 1).is_some_and(|value| values.iter().any(|v| v == value))
+                })
+            })
+        };
+
+        prefix_match(&self.ids, &event.id)
+            && prefix_match(&self.authors, &event.pub_key)
+            && self
+                .kinds
+                .as_ref()
+                .map_or(true, |kinds| kinds.contains(&event.kind))
+            && tag_match(&self.e, "e")
+            && tag_match(&self.p, "p")
+            && tag_match(&self.d, "d")
+            && tag_match(&self.h, "h")
+            && self.since.map_or(true, |since| event.created_at >= since)
+            && self.until.map_or(true, |until| event.created_at <= until)
+    }
+
+    /// Combine the filters matching the same events but for one list (`ids`, `authors`, `kinds`,
+    /// `#e`, `#p`, `#d` or `#h`), the lists are merged and the combined filter matches exactly the events of
+    /// the original ones. Duplicated filters are removed.
+    ///
+    /// Filters with a `limit` are only combined with identical filters: the limit applies to
+    /// each filter, a combined filter would return fewer events.
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::req::ReqFilter;
+    ///
+    /// let filter = |author: &str, kind: u16| ReqFilter {
+    ///   authors: Some(vec![author.to_string()]),
+    ///   kinds: Some(vec![kind]),
+    ///   ..Default::default()
+    /// };
+    ///
+    /// let merged = ReqFilter::merge(vec![filter("alice", 1), filter("bob", 1), filter("bob", 7), filter("alice", 7)]);
+    /// assert_eq!(merged, vec![ReqFilter {
+    ///   authors: Some(vec!["alice".to_string(), "bob".to_string()]),
+    ///   kinds: Some(vec![1, 7]),
+    ///   ..Default::default()
+    /// }]);
+    ///
+    /// // Different authors and kinds: merging would also match bob's kind 1 events
+    /// assert_eq!(ReqFilter::merge(vec![filter("alice", 1), filter("bob", 7)]).len(), 2);
+    ///
+    /// let limited = ReqFilter { limit: Some(10), ..filter("alice", 1) };
+    /// assert_eq!(ReqFilter::merge(vec![limited.clone(), filter("bob", 1)]).len(), 2);
+    /// assert_eq!(ReqFilter::merge(vec![limited.clone(), limited]).len(), 1);
+    /// ```
+    pub fn merge(filters: Vec<ReqFilter>) -> Vec<ReqFilter> {
+        let mut merged: Vec<ReqFilter> = Vec::new();
+
+        for mut filter in filters {
+            // A combined filter may now be combined with one it differed from before
+            while let Some(index) = merged.iter().position(|other| other.can_merge(&filter)) {
+                filter = merged.remove(index).merged_with(filter);
+            }
+
+            merged.push(filter);
+        }
+
+        merged
+    }
+
+    /// Check if the union of the two filters is a single filter: same `since`, `until` and no
+    /// `limit`, and at most one different list
+    fn can_merge(&self, other: &ReqFilter) -> bool {
+        if self == other {
+            return true;
+        }
+
+        if self.limit.is_some() || other.limit.is_some() {
+            return false;
+        }
+
+        let different_lists = [
+            !same_values(&self.ids, &other.ids),
+            !same_values(&self.authors, &other.authors),
+            !same_values(&self.kinds, &other.kinds),
+            !same_values(&self.e, &other.e),
+            !same_values(&self.p, &other.p),
+            !same_values(&self.d, &other.d),
+            !same_values(&self.h, &other.h),
+        ];
+
+        self.since == other.since
+            && self.until == other.until
+            && different_lists
+                .iter()
+                .filter(|different| **different)
+                .count()
+                <= 1
+    }
+
+    /// Union of two filters accepted by `can_merge`
+    fn merged_with(self, other: ReqFilter) -> ReqFilter {
+        ReqFilter {
+            ids: union(self.ids, other.ids),
+            authors: union(self.authors, other.authors),
+            kinds: union(self.kinds, other.kinds),
+            e: union(self.e, other.e),
+            p: union(self.p, other.p),
+            d: union(self.d, other.d),
+            h: union(self.h, other.h),
+            ..self
+        }
+    }
+
     /// Return a clean json object (Value)
     pub fn to_json(&self) -> serde_json::Value {
         let mut json = json!({});
@@ -60,6 +281,14 @@ impl ReqFilter {
             json["#p"] = json!(p);
         }
 
+        if let Some(d) = &self.d {
+            json["#d"] = json!(d);
+        }
+
+        if let Some(h) = &self.h {
+            json["#h"] = json!(h);
+        }
+
         if let Some(since) = &self.since {
             json["since"] = json!(since);
         }
@@ -76,6 +305,30 @@ impl ReqFilter {
     }
 }
 
+/// Check if two lists of a filter hold the same values, in any order
+fn same_values<T: PartialEq>(values: &Option<Vec<T>>, other: &Option<Vec<T>>) -> bool {
+    match (values, other) {
+        (Some(values), Some(other)) => {
+            values.iter().all(|value| other.contains(value))
+                && other.iter().all(|value| values.contains(value))
+        }
+        (values, other) => values.is_none() && other.is_none(),
+    }
+}
+
+/// Union of two lists of a filter, `None` (anything) absorbs the other list
+fn union<T: PartialEq>(values: Option<Vec<T>>, other: Option<Vec<T>>) -> Option<Vec<T>> {
+    let (mut values, other) = (values?, other?);
+
+    for value in other {
+        if !values.contains(&value) {
+            values.push(value);
+        }
+    }
+
+    Some(values)
+}
+
 impl Req {
     pub fn new(subscription_id: Option<&str>, filters: Vec<ReqFilter>) -> Self {
         Self {
@@ -0,0 +1,60 @@
+use crate::events::{Event, EventPrepare};
+use crate::Identity;
+use thiserror::Error;
+
+#[derive(Error, Debug, Eq, PartialEq)]
+pub enum SignerError {
+    #[error("The event public key {} is not the signer public key", _0)]
+    WrongPublicKey(String),
+
+    #[error("The signer rejected the request: {}", _0)]
+    Rejected(String),
+
+    #[error("The signer did not answer in time")]
+    Timeout,
+
+    #[error("The signer failed: {}", _0)]
+    Failed(String),
+}
+
+/// Something able to sign events, a local `Identity` or a remote signer
+///
+/// Client methods taking a `&dyn Signer` work the same whatever signs the events
+pub trait Signer {
+    /// 32-bytes hex-encoded public key of the signer
+    fn public_key(&self) -> String;
+
+    /// Sign an event prepared for the public key of the signer
+    fn sign(&self, event: EventPrepare) -> Result<Event, SignerError>;
+}
+
+impl Signer for Identity {
+    fn public_key(&self) -> String {
+        self.public_key_str.clone()
+    }
+
+    /// Sign the event with the secret key of the identity
+    ///
+    /// # Example
+    /// ```rust
+    /// use nostr_rust::{events::EventPrepare, signer::Signer, Identity};
+    /// use std::str::FromStr;
+    ///
+    /// let identity = Identity::from_str(env!("SECRET_KEY")).unwrap();
+    /// let signer: &dyn Signer = &identity;
+    ///
+    /// let event = EventPrepare::builder(&signer.public_key()).content("hi").build().unwrap();
+    /// let event = signer.sign(event).unwrap();
+    /// event.verify().unwrap();
+    ///
+    /// let other = EventPrepare::builder("884704bd421721e292edbff42eb77547fe115c6ff9825b08fc366be4cd69e9f6").build().unwrap();
+    /// assert!(signer.sign(other).is_err());
+    /// ```
+    fn sign(&self, mut event: EventPrepare) -> Result<Event, SignerError> {
+        if event.pub_key != self.public_key_str {
+            return Err(SignerError::WrongPublicKey(event.pub_key));
+        }
+
+        Ok(event.to_event(self, 0))
+    }
+}
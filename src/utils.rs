@@ -1,5 +1,5 @@
 use rand::Rng;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 /// Get actual timestamp in seconds
 /// # Example
@@ -15,6 +15,59 @@ pub fn get_timestamp() -> u64 {
     since_the_epoch.as_secs()
 }
 
+/// Get the timestamp in seconds of a given time, times before the unix epoch give 0
+/// # Example
+/// ```rust
+/// use nostr_rust::utils::to_timestamp;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// assert_eq!(to_timestamp(UNIX_EPOCH + Duration::from_millis(1_500)), 1);
+/// assert_eq!(to_timestamp(UNIX_EPOCH - Duration::from_secs(1)), 0);
+/// ```
+pub fn to_timestamp(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Get the current timestamp randomly backdated by up to `max_past`, never in the future
+///
+/// Precise timestamps let observers correlate events, NIP-59 recommends it for the gift wraps
+/// # Example
+/// ```rust
+/// use nostr_rust::utils::{get_timestamp, jittered_timestamp};
+/// use std::time::Duration;
+///
+/// let now = get_timestamp();
+/// let timestamp = jittered_timestamp(Duration::from_secs(2 * 24 * 60 * 60));
+///
+/// assert!(timestamp <= get_timestamp());
+/// assert!(timestamp >= now - 2 * 24 * 60 * 60);
+/// assert!(jittered_timestamp(Duration::ZERO) >= now);
+/// ```
+pub fn jittered_timestamp(max_past: Duration) -> u64 {
+    let jitter = rand::thread_rng().gen_range(0..=max_past.as_secs());
+
+    get_timestamp().saturating_sub(jitter)
+}
+
+/// Get the element of a tag at the given position, `None` when the tag is too short
+///
+/// The name of a tag is at position 0 and its value at position 1
+/// # Example
+/// ```rust
+/// use nostr_rust::utils::tag_value;
+///
+/// let tag = vec!["e".to_string(), "event_id".to_string()];
+/// assert_eq!(tag_value(&tag, 0), Some("e"));
+/// assert_eq!(tag_value(&tag, 1), Some("event_id"));
+/// assert_eq!(tag_value(&tag, 3), None);
+/// assert_eq!(tag_value(&[], 0), None);
+/// ```
+pub fn tag_value(tag: &[String], index: usize) -> Option<&str> {
+    tag.get(index).map(String::as_str)
+}
+
 /// Random sha256 hash
 /// # Example
 /// ```rust
@@ -38,8 +91,26 @@ pub struct ParsedTagsResult {
     pub tags: Vec<Vec<String>>,
 }
 
+/// Split a word into its leading punctuation, its core and its trailing punctuation
+///
+/// The core starts with `#` or `@` when there is one, the characters of `alphabet` are kept
+fn split_punctuation<'a>(word: &'a str, alphabet: &str) -> (&'a str, &'a str, &'a str) {
+    let is_punctuation = |c: char| c.is_ascii_punctuation() && !alphabet.contains(c);
+
+    let start = word
+        .find(|c: char| c == '#' || c == '@' || !is_punctuation(c))
+        .unwrap_or(word.len());
+    let rest = &word[start..];
+    let marker_len = rest.chars().next().map_or(0, char::len_utf8);
+    let end = start + marker_len + rest[marker_len..].trim_end_matches(is_punctuation).len();
+
+    (&word[..start], &word[start..end], &word[end..])
+}
+
 /// Parse string to generate tags
 ///
+/// Punctuation around hashtags and mentions is ignored: `(#nostr)` and `@npub...,` are detected
+///
 /// # Arguments
 /// * `text` - Text to parse
 /// * `hashtag_alphabet` - Alphabet to use for hashtag detection, None for no hashtag detection, you can use nostr_rust::DEFAULT_HASHTAG
@@ -52,6 +123,14 @@ pub struct ParsedTagsResult {
 /// let tags = parse_content_tags("hello #world", vec![], Some(nostr_rust::DEFAULT_HASHTAG), true, true);
 /// assert_eq!(tags.content, "hello #world");
 /// assert_eq!(tags.tags, vec![vec!["t", "world"]]);
+///
+/// let tags = parse_content_tags("I love #rust, #nostr! (#tips)", vec![], Some(nostr_rust::DEFAULT_HASHTAG), true, true);
+/// assert_eq!(tags.content, "I love #rust, #nostr! (#tips)");
+/// assert_eq!(tags.tags, vec![vec!["t", "rust"], vec!["t", "nostr"], vec!["t", "tips"]]);
+///
+/// let tags = parse_content_tags("thanks (@npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6).", vec![], None, true, true);
+/// assert_eq!(tags.content, "thanks (#[0]).");
+/// assert_eq!(tags.tags, vec![vec!["p", "3bf0c63fcb93463407af97a5e5ee64fa883d107ef9e558472c4eb9aaaefa459d"]]);
 /// ```
 pub fn parse_content_tags(
     text: &str,
@@ -67,16 +146,13 @@ pub fn parse_content_tags(
 
     for part in parts {
         if let Some(hash) = hashtag_alphabet {
-            if part.starts_with('#') {
-                let is_hash = !part
-                    .strip_prefix('#')
-                    .unwrap()
-                    .chars()
-                    .any(|c| !hash.contains(c));
+            let (_, word, _) = split_punctuation(part, hash);
+
+            if let Some(hashtag) = word.strip_prefix('#') {
+                let is_hash = !hashtag.is_empty() && !hashtag.chars().any(|c| !hash.contains(c));
 
                 if is_hash {
-                    let s = part.strip_prefix('#').unwrap().to_string();
-                    tags.push(vec!["t".to_string(), s]);
+                    tags.push(vec!["t".to_string(), hashtag.to_string()]);
                 }
 
                 contents.push(part.to_string());
@@ -85,16 +161,18 @@ pub fn parse_content_tags(
             }
         }
 
-        if detect_note && part.to_lowercase().starts_with("@note") && part.len() == (NOTE_LEN + 1) {
+        let (before, word, after) = split_punctuation(part, "");
+
+        if detect_note && word.to_lowercase().starts_with("@note") && word.len() == (NOTE_LEN + 1) {
             let hex = crate::bech32::from_hb_to_hex(
                 crate::bech32::ToBech32Kind::Note,
-                &part.strip_prefix('@').unwrap().to_lowercase(),
+                &word.strip_prefix('@').unwrap().to_lowercase(),
             );
 
-            if hex.is_ok() {
-                tags.push(vec!["e".to_string(), hex.unwrap()]);
+            if let Ok(hex) = hex {
+                tags.push(vec!["e".to_string(), hex]);
                 let last_index = tags.len() - 1;
-                contents.push(format!("#[{last_index}]"));
+                contents.push(format!("{before}#[{last_index}]{after}"));
             } else {
                 contents.push(part.to_string());
             }
@@ -102,16 +180,16 @@ pub fn parse_content_tags(
             continue;
         }
 
-        if detect_npub && part.to_lowercase().starts_with("@npub") && part.len() == (NPUB_LEN + 1) {
+        if detect_npub && word.to_lowercase().starts_with("@npub") && word.len() == (NPUB_LEN + 1) {
             let hex = crate::bech32::from_hb_to_hex(
                 crate::bech32::ToBech32Kind::PublicKey,
-                &part.strip_prefix('@').unwrap().to_lowercase(),
+                &word.strip_prefix('@').unwrap().to_lowercase(),
             );
 
-            if hex.is_ok() {
-                tags.push(vec!["p".to_string(), hex.unwrap()]);
+            if let Ok(hex) = hex {
+                tags.push(vec!["p".to_string(), hex]);
                 let last_index = tags.len() - 1;
-                contents.push(format!("#[{last_index}]"));
+                contents.push(format!("{before}#[{last_index}]{after}"));
             } else {
                 contents.push(part.to_string());
             }
@@ -1,13 +1,16 @@
 // Simplified websocket implementation
 #[cfg(feature = "async")]
-use futures::StreamExt;
+use futures::{FutureExt, StreamExt};
 #[cfg(feature = "async")]
 use futures_util::sink::SinkExt;
 use thiserror::Error;
 #[cfg(feature = "async")]
-use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+use tokio_tungstenite::connect_async_with_config;
+#[cfg(feature = "async")]
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
 #[cfg(not(feature = "async"))]
-use tungstenite::{connect, stream::MaybeTlsStream, Message, WebSocket};
+use tungstenite::{client::connect_with_config, stream::MaybeTlsStream, Message, WebSocket};
+use tungstenite::{error::CapacityError, protocol::WebSocketConfig};
 use url::Url;
 
 #[derive(Error, Debug, Eq, PartialEq)]
@@ -23,8 +26,96 @@ pub enum SimplifiedWSError {
 
     #[error("Error while receiving the message from the websocket server")]
     ReceiveMessageError,
+
+    #[error("The message received from the websocket server is larger than the size limit")]
+    MessageTooLarge,
+}
+
+/// Maximum size of the messages received from a relay, see `SimplifiedWS::new_with_limits`
+///
+/// A relay sending a larger message gets a `SimplifiedWSError::MessageTooLarge` error instead of
+/// exhausting the memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    /// Maximum size of a message in bytes, 10 MB by default
+    pub max_message_size: usize,
+    /// Maximum size of a single frame of a message in bytes, 10 MB by default
+    pub max_frame_size: usize,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        Self {
+            max_message_size: 10 << 20,
+            max_frame_size: 10 << 20,
+        }
+    }
+}
+
+impl SizeLimits {
+    fn to_config(self) -> WebSocketConfig {
+        WebSocketConfig {
+            max_message_size: Some(self.max_message_size),
+            max_frame_size: Some(self.max_frame_size),
+            ..Default::default()
+        }
+    }
+}
+
+fn read_error(error: tungstenite::Error) -> SimplifiedWSError {
+    match error {
+        tungstenite::Error::Capacity(CapacityError::MessageTooLong { .. }) => {
+            SimplifiedWSError::MessageTooLarge
+        }
+        _ => SimplifiedWSError::ReceiveMessageError,
+    }
+}
+
+/// Validate a relay url and return its canonical form, to compare or dedupe relay lists
+///
+/// The scheme must be `ws` or `wss`. The scheme and the host are lowercased, the default port
+/// (80 for `ws`, 443 for `wss`), the trailing slashes and the fragment are removed
+/// # Example
+/// ```rust
+/// use nostr_rust::websocket::{normalize_relay_url, SimplifiedWSError};
+///
+/// assert_eq!(normalize_relay_url("Wss://Relay.Example/"), Ok("wss://relay.example".to_string()));
+/// assert_eq!(normalize_relay_url("ws://relay.example:80/nostr/"), Ok("ws://relay.example/nostr".to_string()));
+/// assert_eq!(normalize_relay_url("wss://relay.example:8080"), Ok("wss://relay.example:8080".to_string()));
+/// assert_eq!(normalize_relay_url("https://relay.example"), Err(SimplifiedWSError::UrlParseError));
+/// assert_eq!(normalize_relay_url("relay.example"), Err(SimplifiedWSError::UrlParseError));
+/// ```
+pub fn normalize_relay_url(url: &str) -> Result<String, SimplifiedWSError> {
+    let url = Url::parse(url.trim()).map_err(|_| SimplifiedWSError::UrlParseError)?;
+
+    if !matches!(url.scheme(), "ws" | "wss") {
+        return Err(SimplifiedWSError::UrlParseError);
+    }
+
+    let host = url.host_str().ok_or(SimplifiedWSError::UrlParseError)?;
+
+    // `Url` lowercases the scheme and the host and has no port when it is the default one
+    let mut normalized = format!("{}://{}", url.scheme(), host);
+    if let Some(port) = url.port() {
+        normalized.push_str(&format!(":{}", port));
+    }
+    normalized.push_str(url.path().trim_end_matches('/'));
+    if let Some(query) = url.query() {
+        normalized.push_str(&format!("?{}", query));
+    }
+
+    Ok(normalized)
 }
 
+/// Websocket connection to a relay
+///
+/// `wss://` relays are reached with rustls (`tls-rustls` feature, the default) or with the
+/// platform TLS library (`tls-native` feature). Without any of them only `ws://` relays work.
+///
+/// Messages are sent and received uncompressed. There is no option to negotiate
+/// `permessage-deflate`: `tungstenite` doesn't implement the extension and rejects compressed
+/// frames (RSV1 bit set) with a protocol error, so offering it would break the connection to the
+/// relays accepting it.
 pub struct SimplifiedWS {
     pub url: Url,
     #[cfg(not(feature = "async"))]
@@ -35,13 +126,20 @@ pub struct SimplifiedWS {
 
 impl SimplifiedWS {
     #[cfg(not(feature = "async"))]
+    /// Connect to a relay with the default `SizeLimits`
     pub fn new(url: &str) -> Result<Self, SimplifiedWSError> {
+        Self::new_with_limits(url, SizeLimits::default())
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Connect to a relay, the messages larger than the limits are rejected
+    pub fn new_with_limits(url: &str, limits: SizeLimits) -> Result<Self, SimplifiedWSError> {
         let url = match Url::parse(url) {
             Ok(url) => url,
             Err(_) => return Err(SimplifiedWSError::UrlParseError),
         };
 
-        let (socket, _) = match connect(&url) {
+        let (socket, _) = match connect_with_config(&url, Some(limits.to_config()), 3) {
             Ok((socket, response)) => (socket, response),
             Err(_) => return Err(SimplifiedWSError::ConnectionError),
         };
@@ -49,14 +147,47 @@ impl SimplifiedWS {
         Ok(Self { url, socket })
     }
 
+    #[cfg(not(feature = "async"))]
+    /// Wrap an already connected websocket, to configure the connection or to connect to a mock relay in tests
+    ///
+    /// See `Client::add_connected_relay`
+    pub fn from_socket(
+        url: &str,
+        socket: WebSocket<MaybeTlsStream<std::net::TcpStream>>,
+    ) -> Result<Self, SimplifiedWSError> {
+        let url = Url::parse(url).map_err(|_| SimplifiedWSError::UrlParseError)?;
+
+        Ok(Self { url, socket })
+    }
+
+    #[cfg(feature = "async")]
+    /// Wrap an already connected websocket, to configure the connection or to connect to a mock relay in tests
+    ///
+    /// See `Client::add_connected_relay`
+    pub fn from_socket(
+        url: &str,
+        socket: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+    ) -> Result<Self, SimplifiedWSError> {
+        let url = Url::parse(url).map_err(|_| SimplifiedWSError::UrlParseError)?;
+
+        Ok(Self { url, socket })
+    }
+
     #[cfg(feature = "async")]
+    /// Connect to a relay with the default `SizeLimits`
     pub async fn new(url: &str) -> Result<Self, SimplifiedWSError> {
+        Self::new_with_limits(url, SizeLimits::default()).await
+    }
+
+    #[cfg(feature = "async")]
+    /// Connect to a relay, the messages larger than the limits are rejected
+    pub async fn new_with_limits(url: &str, limits: SizeLimits) -> Result<Self, SimplifiedWSError> {
         let url = match Url::parse(url) {
             Ok(url) => url,
             Err(_) => return Err(SimplifiedWSError::UrlParseError),
         };
 
-        let (socket, _) = match connect_async(&url).await {
+        let (socket, _) = match connect_async_with_config(&url, Some(limits.to_config())).await {
             Ok((socket, response)) => (socket, response),
             Err(_) => return Err(SimplifiedWSError::ConnectionError),
         };
@@ -82,18 +213,60 @@ impl SimplifiedWS {
 
     #[cfg(not(feature = "async"))]
     pub fn read_message(&mut self) -> Result<Message, SimplifiedWSError> {
-        match self.socket.read_message() {
-            Ok(message) => Ok(message),
-            Err(_) => Err(SimplifiedWSError::ReceiveMessageError),
-        }
+        self.socket.read_message().map_err(read_error)
     }
 
     #[cfg(feature = "async")]
     pub async fn read_message(&mut self) -> Result<Message, SimplifiedWSError> {
         match self.socket.next().await {
             Some(Ok(message)) => Ok(message),
-            Some(Err(_)) => Err(SimplifiedWSError::ReceiveMessageError),
+            Some(Err(error)) => Err(read_error(error)),
             None => Err(SimplifiedWSError::ReceiveMessageError),
         }
     }
+
+    #[cfg(not(feature = "async"))]
+    /// Read a message only if one is immediately available, `None` means no message is ready
+    pub fn try_read_message(&mut self) -> Result<Option<Message>, SimplifiedWSError> {
+        self.set_nonblocking(true)?;
+        let result = self.socket.read_message();
+        self.set_nonblocking(false)?;
+
+        match result {
+            Ok(message) => Ok(Some(message)),
+            Err(tungstenite::Error::Io(error))
+                if error.kind() == std::io::ErrorKind::WouldBlock =>
+            {
+                Ok(None)
+            }
+            Err(error) => Err(read_error(error)),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    /// Read a message only if one is immediately available, `None` means no message is ready
+    pub async fn try_read_message(&mut self) -> Result<Option<Message>, SimplifiedWSError> {
+        match self.socket.next().now_or_never() {
+            None => Ok(None),
+            Some(Some(Ok(message))) => Ok(Some(message)),
+            Some(Some(Err(error))) => Err(read_error(error)),
+            Some(None) => Err(SimplifiedWSError::ReceiveMessageError),
+        }
+    }
+
+    #[cfg(not(feature = "async"))]
+    fn set_nonblocking(&mut self, nonblocking: bool) -> Result<(), SimplifiedWSError> {
+        let stream = match self.socket.get_mut() {
+            MaybeTlsStream::Plain(stream) => stream,
+            #[cfg(feature = "tls-rustls")]
+            MaybeTlsStream::Rustls(stream) => &mut stream.sock,
+            #[cfg(feature = "tls-native")]
+            MaybeTlsStream::NativeTls(stream) => stream.get_mut(),
+            _ => return Err(SimplifiedWSError::ReceiveMessageError),
+        };
+
+        stream
+            .set_nonblocking(nonblocking)
+            .map_err(|_| SimplifiedWSError::ReceiveMessageError)
+    }
 }